@@ -12,9 +12,14 @@ macro_rules! impl_audiotag_config {
     };
 }
 
+/// Implement a tag type wrapping `$inner`. `$read_from` (`$inner::read_from` by default) reads an
+/// `$inner` from a `&mut (impl Read + Seek)`.
 #[macro_export]
 macro_rules! impl_tag {
     ($tag:ident , $inner:ident, $tag_type:expr) => {
+        $crate::impl_tag!($tag, $inner, $tag_type, $inner::read_from);
+    };
+    ($tag:ident , $inner:ident, $tag_type:expr, $read_from:path) => {
         #[derive(Default)]
         pub struct $tag {
             inner: $inner,
@@ -30,6 +35,12 @@ macro_rules! impl_tag {
                     config: Config::default(),
                 })
             }
+            pub fn read_from(reader: &mut (impl std::io::Read + std::io::Seek)) -> crate::Result<Self> {
+                Ok(Self {
+                    inner: $read_from(reader)?,
+                    config: Config::default(),
+                })
+            }
         }
         impl_audiotag_config!($tag);
 
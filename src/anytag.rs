@@ -12,13 +12,42 @@ pub struct AnyTag<'a> {
     pub album_title: Option<&'a str>,
     pub album_artists: Option<Vec<&'a str>>,
     pub album_cover: Option<Picture<'a>>,
+    /// All the pictures, usually including the front cover
+    pub pictures: Vec<EmbeddedPicture<'a>>,
     pub track_number: Option<u16>,
     pub total_tracks: Option<u16>,
     pub disc_number: Option<u16>,
     pub total_discs: Option<u16>,
-    pub genre: Option<&'a str>,
-    pub composer: Option<&'a str>,
+    pub genres: Option<Vec<&'a str>>,
+    pub composers: Option<Vec<&'a str>>,
     pub comment: Option<&'a str>,
+    pub lyrics: Option<Lyrics<'a>>,
+    pub synced_lyrics: Option<SyncedLyrics>,
+    pub replay_gain: Option<ReplayGain>,
+    pub title_sort: Option<&'a str>,
+    pub artist_sort: Option<&'a str>,
+    pub album_sort: Option<&'a str>,
+    pub album_artist_sort: Option<&'a str>,
+    pub composer_sort: Option<&'a str>,
+    pub isrc: Option<&'a str>,
+    pub label: Option<&'a str>,
+    pub catalog_number: Option<&'a str>,
+    pub copyright: Option<&'a str>,
+    pub encoded_by: Option<&'a str>,
+    pub grouping: Option<&'a str>,
+    pub mood: Option<&'a str>,
+    pub language: Option<&'a str>,
+    pub conductor: Option<&'a str>,
+    pub lyricist: Option<&'a str>,
+    pub bpm: Option<u16>,
+    pub compilation: bool,
+    pub original_date: Option<Timestamp>,
+    /// From 0.0 to 1.0
+    pub rating: Option<f64>,
+    pub play_count: Option<u64>,
+    pub musicbrainz_ids: Vec<(MusicBrainzId, &'a str)>,
+    /// `(name, value)` pairs, one per value
+    pub custom_fields: Vec<(&'a str, &'a str)>,
 }
 
 impl AudioTagConfig for AnyTag<'_> {
@@ -62,6 +91,22 @@ impl<'a> AnyTag<'a> {
     pub fn album_artists(&self) -> Option<&[&str]> {
         self.album_artists.as_deref()
     }
+    pub fn album_cover(&self) -> Option<Picture<'a>> {
+        self.album_cover.clone()
+    }
+    /// The pictures, with `album_cover` added as the front cover if none of them is one.
+    pub fn pictures(&self) -> Vec<EmbeddedPicture<'a>> {
+        let mut pictures = self.pictures.clone();
+        if let Some(cover) = self.album_cover() {
+            if !pictures
+                .iter()
+                .any(|p| p.picture_type == PictureType::CoverFront)
+            {
+                pictures.insert(0, cover.into());
+            }
+        }
+        pictures
+    }
     pub fn track_number(&self) -> Option<u16> {
         self.track_number
     }
@@ -75,14 +120,92 @@ impl<'a> AnyTag<'a> {
         self.total_discs
     }
     pub fn genre(&self) -> Option<&str> {
-        self.genre
+        self.genres()?.first().copied()
+    }
+    pub fn genres(&self) -> Option<&[&str]> {
+        self.genres.as_deref()
     }
     pub fn composer(&self) -> Option<&str> {
-        self.composer
+        self.composers()?.first().copied()
+    }
+    pub fn composers(&self) -> Option<&[&str]> {
+        self.composers.as_deref()
     }
     pub fn comment(&self) -> Option<&str> {
         self.comment
     }
+    pub fn lyrics(&self) -> Option<Lyrics<'a>> {
+        self.lyrics.clone()
+    }
+    pub fn synced_lyrics(&self) -> Option<&SyncedLyrics> {
+        self.synced_lyrics.as_ref()
+    }
+    pub fn replay_gain(&self) -> Option<ReplayGain> {
+        self.replay_gain
+    }
+    pub fn sort_order(&self, field: SortField) -> Option<&'a str> {
+        match field {
+            SortField::Title => self.title_sort,
+            SortField::Artist => self.artist_sort,
+            SortField::Album => self.album_sort,
+            SortField::AlbumArtist => self.album_artist_sort,
+            SortField::Composer => self.composer_sort,
+        }
+    }
+    pub fn text_field(&self, field: TextField) -> Option<&'a str> {
+        match field {
+            TextField::Isrc => self.isrc,
+            TextField::Label => self.label,
+            TextField::CatalogNumber => self.catalog_number,
+            TextField::Copyright => self.copyright,
+            TextField::EncodedBy => self.encoded_by,
+            TextField::Grouping => self.grouping,
+            TextField::Mood => self.mood,
+            TextField::Language => self.language,
+            TextField::Conductor => self.conductor,
+            TextField::Lyricist => self.lyricist,
+        }
+    }
+    pub fn bpm(&self) -> Option<u16> {
+        self.bpm
+    }
+    pub fn compilation(&self) -> bool {
+        self.compilation
+    }
+    pub fn original_date(&self) -> Option<Timestamp> {
+        self.original_date
+    }
+    pub fn rating(&self) -> Option<f64> {
+        self.rating
+    }
+    pub fn play_count(&self) -> Option<u64> {
+        self.play_count
+    }
+    pub fn musicbrainz_ids(&self, id: MusicBrainzId) -> Vec<&'a str> {
+        self.musicbrainz_ids
+            .iter()
+            .filter(|(i, _)| *i == id)
+            .map(|&(_, v)| v)
+            .collect()
+    }
+    /// The values of a custom field, with `name` in any case.
+    pub fn custom_field(&self, name: &str) -> Vec<&'a str> {
+        self.custom_fields
+            .iter()
+            .filter(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|&(_, v)| v)
+            .collect()
+    }
+    /// The names of the custom fields, without duplicates (in any case).
+    pub fn custom_field_names(&self) -> Vec<&'a str> {
+        let mut names: Vec<&str> = Vec::new();
+        for &(name, _) in &self.custom_fields {
+            if !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                names.push(name);
+            }
+        }
+        names
+    }
 }
 
 impl AnyTag<'_> {
@@ -95,3 +218,328 @@ impl AnyTag<'_> {
             .map(|artists| artists.join(self.config.sep_artist))
     }
 }
+
+/// Read every field of `tag` through its `AudioTagEdit` accessors.
+pub(crate) fn anytag_from<'a>(tag: &'a impl AudioTagEdit) -> AnyTag<'a> {
+    AnyTag {
+        config: *tag.config(),
+        title: tag.title(),
+        artists: tag.artists(),
+        date: tag.date(),
+        year: tag.year(),
+        duration: tag.duration(),
+        album_title: tag.album_title(),
+        album_artists: tag.album_artists(),
+        album_cover: tag.album_cover(),
+        pictures: tag.pictures(),
+        track_number: tag.track_number(),
+        total_tracks: tag.total_tracks(),
+        disc_number: tag.disc_number(),
+        total_discs: tag.total_discs(),
+        genres: tag.genres(),
+        composers: tag.composers(),
+        comment: tag.comment(),
+        lyrics: tag.lyrics(),
+        synced_lyrics: tag.synced_lyrics(),
+        replay_gain: tag.replay_gain(),
+        title_sort: tag.title_sort(),
+        artist_sort: tag.artist_sort(),
+        album_sort: tag.album_sort(),
+        album_artist_sort: tag.album_artist_sort(),
+        composer_sort: tag.composer_sort(),
+        isrc: tag.isrc(),
+        label: tag.label(),
+        catalog_number: tag.catalog_number(),
+        copyright: tag.copyright(),
+        encoded_by: tag.encoded_by(),
+        grouping: tag.grouping(),
+        mood: tag.mood(),
+        language: tag.language(),
+        conductor: tag.conductor(),
+        lyricist: tag.lyricist(),
+        bpm: tag.bpm(),
+        compilation: tag.compilation(),
+        original_date: tag.original_date(),
+        rating: tag.rating(),
+        play_count: tag.play_count(),
+        musicbrainz_ids: tag.all_musicbrainz_ids(),
+        custom_fields: tag.custom_fields(),
+    }
+}
+
+/// Write every field of `inp` to `tag` through its `AudioTagEdit` setters. The `year` is only
+/// written when there is no `date`, which usually shares its field.
+pub(crate) fn apply_anytag(tag: &mut impl AudioTagEdit, inp: AnyTag) {
+    if let Some(v) = inp.title() {
+        tag.set_title(v)
+    }
+    if let Some(v) = inp.artists() {
+        tag.set_artists(v)
+    }
+    if let Some(v) = inp.date() {
+        tag.set_date(v)
+    } else if let Some(v) = inp.year() {
+        tag.set_year(v)
+    }
+    if let Some(v) = inp.album_title() {
+        tag.set_album_title(v)
+    }
+    if let Some(v) = inp.album_artists() {
+        tag.set_album_artists(v)
+    }
+    if let Some(v) = inp.track_number() {
+        tag.set_track_number(v)
+    }
+    if let Some(v) = inp.total_tracks() {
+        tag.set_total_tracks(v)
+    }
+    if let Some(v) = inp.disc_number() {
+        tag.set_disc_number(v)
+    }
+    if let Some(v) = inp.total_discs() {
+        tag.set_total_discs(v)
+    }
+    if let Some(v) = inp.genres() {
+        tag.set_genres(v)
+    }
+    for v in inp.pictures() {
+        tag.add_picture(v)
+    }
+    if let Some(v) = inp.composers() {
+        tag.set_composers(v)
+    }
+    if let Some(v) = inp.comment() {
+        tag.set_comment(v.to_owned())
+    }
+    if let Some(v) = inp.lyrics() {
+        tag.set_lyrics(v)
+    }
+    if let Some(v) = inp.synced_lyrics() {
+        // the lyrics may already be the LRC text, e.g. from an `Mp4Tag`
+        if tag.synced_lyrics().as_ref() != Some(v) {
+            tag.set_synced_lyrics(v)
+        }
+    }
+    if let Some(v) = inp.replay_gain() {
+        tag.set_replay_gain(v)
+    }
+    for field in SortField::ALL {
+        if let Some(v) = inp.sort_order(field) {
+            tag.set_sort_order(field, v)
+        }
+    }
+    for field in TextField::ALL {
+        if let Some(v) = inp.text_field(field) {
+            tag.set_text_field(field, v)
+        }
+    }
+    if let Some(v) = inp.bpm() {
+        tag.set_bpm(v)
+    }
+    tag.set_compilation(inp.compilation());
+    if let Some(v) = inp.original_date() {
+        tag.set_original_date(v)
+    }
+    if let Some(v) = inp.rating() {
+        tag.set_rating(v)
+    }
+    if let Some(v) = inp.play_count() {
+        tag.set_play_count(v)
+    }
+    for id in MusicBrainzId::ALL {
+        let values = inp.musicbrainz_ids(id);
+        if !values.is_empty() {
+            tag.set_musicbrainz_ids(id, &values)
+        }
+    }
+    for name in inp.custom_field_names() {
+        tag.set_custom_field(name, &inp.custom_field(name))
+    }
+}
+
+/// The fields of a source tag that a conversion (`ToAnyTag::to_dyn_tag_with_report`) could not
+/// carry over as they were.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    pub lost: Vec<LostField>,
+}
+
+/// A field that could not be represented in the target format, e.g. `("composer", Unsupported)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LostField {
+    /// The name of the `AnyTag` field, e.g. `"album_cover"`
+    pub field: &'static str,
+    pub reason: LossReason,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LossReason {
+    /// The target format cannot store the field (or this kind of value) at all
+    Unsupported,
+    /// The target format stores the field differently, e.g. truncated or with less precision
+    Altered { source: String, target: String },
+}
+
+impl std::fmt::Display for LossReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported => write!(f, "not supported by the target format"),
+            Self::Altered { source, target } => write!(f, "{source:?} is stored as {target:?}"),
+        }
+    }
+}
+
+impl ConversionReport {
+    /// Compare the fields of `source` with those of the converted tag, `target`.
+    pub fn compare(source: &AnyTag, target: &AnyTag) -> Self {
+        fn describe_picture(p: &Picture) -> String {
+            format!("{} ({} bytes)", p.mime_type, p.data.len())
+        }
+        let mut lost = Vec::new();
+        macro_rules! compare {
+            ($field:ident, $describe:expr) => {
+                if let Some(source) = &source.$field {
+                    let reason = match &target.$field {
+                        None => Some(LossReason::Unsupported),
+                        Some(target) if target != source => Some(LossReason::Altered {
+                            source: $describe(source),
+                            target: $describe(target),
+                        }),
+                        _ => None,
+                    };
+                    if let Some(reason) = reason {
+                        lost.push(LostField {
+                            field: stringify!($field),
+                            reason,
+                        });
+                    }
+                }
+            };
+            ($field:ident) => {
+                compare!($field, |v: &dyn std::fmt::Display| v.to_string())
+            };
+        }
+        compare!(title);
+        compare!(artists, |v: &Vec<&str>| v.join(source.config.sep_artist));
+        compare!(date);
+        compare!(year);
+        compare!(album_title);
+        compare!(album_artists, |v: &Vec<&str>| v
+            .join(source.config.sep_artist));
+        compare!(album_cover, describe_picture);
+        // the front cover is compared as `album_cover`
+        let target_pictures = target.pictures();
+        for picture in source
+            .pictures()
+            .iter()
+            .filter(|p| p.picture_type != PictureType::CoverFront)
+        {
+            let describe = |p: &EmbeddedPicture| {
+                format!(
+                    "{:?}: {}",
+                    p.picture_type,
+                    describe_picture(&p.as_picture())
+                )
+            };
+            let reason = match target_pictures.iter().find(|p| p.data == picture.data) {
+                None => LossReason::Unsupported,
+                Some(target) if target.picture_type != picture.picture_type => {
+                    LossReason::Altered {
+                        source: describe(picture),
+                        target: describe(target),
+                    }
+                }
+                _ => continue,
+            };
+            lost.push(LostField {
+                field: "pictures",
+                reason,
+            });
+        }
+        compare!(track_number);
+        compare!(total_tracks);
+        compare!(disc_number);
+        compare!(total_discs);
+        compare!(genres, |v: &Vec<&str>| v.join(source.config.sep_artist));
+        compare!(composers, |v: &Vec<&str>| v.join(source.config.sep_artist));
+        compare!(comment);
+        compare!(lyrics, |l: &Lyrics| format!(
+            "{:?} (lang {:?}, description {:?})",
+            l.text, l.lang, l.description
+        ));
+        compare!(synced_lyrics, |l: &SyncedLyrics| format!(
+            "{} lines (lang {:?}, description {:?})",
+            l.lines.len(),
+            l.lang,
+            l.description
+        ));
+        compare!(replay_gain, |g: &ReplayGain| format!("{:?}", g));
+        compare!(title_sort);
+        compare!(artist_sort);
+        compare!(album_sort);
+        compare!(album_artist_sort);
+        compare!(composer_sort);
+        compare!(isrc);
+        compare!(label);
+        compare!(catalog_number);
+        compare!(copyright);
+        compare!(encoded_by);
+        compare!(grouping);
+        compare!(mood);
+        compare!(language);
+        compare!(conductor);
+        compare!(lyricist);
+        compare!(bpm);
+        if source.compilation && !target.compilation {
+            lost.push(LostField {
+                field: "compilation",
+                reason: LossReason::Unsupported,
+            });
+        }
+        compare!(original_date);
+        compare!(rating);
+        compare!(play_count);
+        for id in MusicBrainzId::ALL {
+            let source_ids = source.musicbrainz_ids(id);
+            let target_ids = target.musicbrainz_ids(id);
+            let reason = if source_ids.is_empty() || source_ids == target_ids {
+                continue;
+            } else if target_ids.is_empty() {
+                LossReason::Unsupported
+            } else {
+                LossReason::Altered {
+                    source: format!("{:?}: {}", id, source_ids.join(", ")),
+                    target: format!("{:?}: {}", id, target_ids.join(", ")),
+                }
+            };
+            lost.push(LostField {
+                field: "musicbrainz_ids",
+                reason,
+            });
+        }
+        for name in source.custom_field_names() {
+            let source_values = source.custom_field(name);
+            let target_values = target.custom_field(name);
+            let reason = if source_values == target_values {
+                continue;
+            } else if target_values.is_empty() {
+                LossReason::Unsupported
+            } else {
+                LossReason::Altered {
+                    source: format!("{}: {}", name, source_values.join(", ")),
+                    target: format!("{}: {}", name, target_values.join(", ")),
+                }
+            };
+            lost.push(LostField {
+                field: "custom_fields",
+                reason,
+            });
+        }
+        Self { lost }
+    }
+
+    /// Whether every field of the source tag was carried over.
+    pub fn is_lossless(&self) -> bool {
+        self.lost.is_empty()
+    }
+}
@@ -1,6 +1,22 @@
+#[macro_use]
 mod id3_tag;
+#[macro_use]
+mod vorbis_comment;
+
 pub use id3_tag::Id3v2Tag;
+mod id3v1_tag;
+pub use id3v1_tag::{Id3v1InnerTag, Id3v1Tag};
+mod aiff_tag;
+pub use aiff_tag::{AiffInnerTag, AiffTag};
+mod ape_tag;
+pub use ape_tag::{ApeInnerTag, ApeItem, ApeItemValue, ApeTag};
 mod flac_tag;
 mod mp4_tag;
+mod ogg_tag;
+mod opus_tag;
 pub use flac_tag::FlacTag;
 pub use mp4_tag::Mp4Tag;
+pub use ogg_tag::{OggInnerTag, OggTag};
+pub use opus_tag::{OpusInnerTag, OpusTag};
+mod wav_tag;
+pub use wav_tag::{WavInnerTag, WavTag};
@@ -0,0 +1,203 @@
+use crate::riff::{Container, Endianness};
+use crate::*;
+use id3::{self, Frame, TagLike, Timestamp};
+use std::io::{Cursor, Read, Seek};
+
+/// The native text chunks, and the ID3 frames they are kept in sync with.
+const TEXT_CHUNKS: [(&[u8; 4], &str); 4] = [
+    (b"NAME", "TIT2"),
+    (b"AUTH", "TPE1"),
+    (b"(c) ", "TCOP"),
+    (b"ANNO", "COMM"),
+];
+
+/// The metadata of an AIFF/AIFC file: an embedded `ID3 ` chunk and the native `NAME`, `AUTH`,
+/// `(c) ` and `ANNO` text chunks, plus the audio properties of the `COMM` chunk.
+///
+/// ID3 takes precedence: on read, the text chunks only fill in what the ID3 tag lacks; on write,
+/// they are regenerated from the ID3 tag so both stay in sync. Only the first `ANNO` chunk maps
+/// to the comment, the other ones are preserved.
+#[derive(Clone, Debug, Default)]
+pub struct AiffInnerTag {
+    id3: id3::Tag,
+    text: Vec<([u8; 4], String)>,
+    /// The `ANNO` chunks following the one mapped to the comment.
+    annotations: Vec<String>,
+    duration: Option<f64>,
+}
+
+impl AiffInnerTag {
+    pub fn read_from_path(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::read_from(&mut File::open(path)?)
+    }
+    pub fn read_from<R: Read + Seek + ?Sized>(reader: &mut R) -> crate::Result<Self> {
+        let form = Container::read_from(reader, b"FORM", Endianness::Big)?;
+        if &form.form_type != b"AIFF" && &form.form_type != b"AIFC" {
+            return Err(Error::ChunkError("not an AIFF file".to_owned()));
+        }
+        let mut tag = Self::default();
+        for chunk in &form.chunks {
+            match &chunk.id {
+                b"ID3 " | b"id3 " => {
+                    let data = Container::read_chunk(reader, chunk)?;
+                    tag.id3 = id3::Tag::read_from2(Cursor::new(data))?;
+                }
+                b"COMM" => {
+                    let data = Container::read_chunk(reader, chunk)?;
+                    tag.duration = comm_duration(&data);
+                }
+                id if TEXT_CHUNKS.iter().any(|(c, _)| *c == id) => {
+                    let data = Container::read_chunk(reader, chunk)?;
+                    let text = String::from_utf8_lossy(&data);
+                    tag.text.push((*id, text.trim_end_matches('\0').to_owned()));
+                }
+                _ => {}
+            }
+        }
+        tag.annotations = tag
+            .text
+            .iter()
+            .filter(|(id, _)| id == b"ANNO")
+            .skip(1)
+            .map(|(_, text)| text.clone())
+            .collect();
+        tag.merge_text();
+        Ok(tag)
+    }
+    /// Replace the `ID3 ` and text chunks of `file`. The ID3 tag keeps the version it was read with.
+    pub fn write_to(&mut self, file: &mut dyn Storage) -> crate::Result<()> {
+        self.write_with_config(file, &Config::default())
+    }
+    pub fn write_to_path(&mut self, path: impl AsRef<Path>) -> crate::Result<()> {
+        let mut file = File::options().read(true).write(true).open(path)?;
+        self.write_to(&mut file)
+    }
+    /// Like `write_to`, with the ID3 tag written in `config.id3v2_version` if it is set.
+    pub(crate) fn write_with_config(
+        &mut self,
+        file: &mut dyn Storage,
+        config: &Config,
+    ) -> crate::Result<()> {
+        self.sync_text(config.sep_artist);
+        let form = Container::read_from(file, b"FORM", Endianness::Big)?;
+        let removed = form
+            .chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| {
+                matches!(&c.id, b"ID3 " | b"id3 ") || TEXT_CHUNKS.iter().any(|(id, _)| **id == c.id)
+            })
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let mut added = self
+            .text
+            .iter()
+            .map(|(id, v)| (*id, v.clone().into_bytes()))
+            .collect::<Vec<_>>();
+        if self.id3.frames().next().is_some() {
+            let (id3, version) =
+                crate::components::id3_tag::to_write_version(&self.id3, config.id3v2_version);
+            let mut data = Vec::new();
+            id3.write_to(&mut data, version)?;
+            added.push((*b"ID3 ", data));
+        }
+        form.rewrite(file, &removed, &added)
+    }
+
+    pub fn id3(&self) -> &id3::Tag {
+        &self.id3
+    }
+    pub fn id3_mut(&mut self) -> &mut id3::Tag {
+        &mut self.id3
+    }
+
+    /// The native text chunks, e.g. `("NAME", "Title")`. There may be several `ANNO`s.
+    pub fn text_chunks(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.text
+            .iter()
+            .map(|(id, v)| (std::str::from_utf8(id).unwrap_or_default(), v.as_str()))
+    }
+
+    pub fn duration(&self) -> Option<f64> {
+        self.duration
+    }
+
+    /// Fill in the frames missing from the ID3 tag with the text chunks.
+    fn merge_text(&mut self) {
+        for (chunk, frame) in TEXT_CHUNKS {
+            let Some((_, text)) = self.text.iter().find(|(id, _)| id == chunk) else {
+                continue;
+            };
+            if self.id3.get(frame).is_some() {
+                continue;
+            }
+            if frame == "COMM" {
+                self.id3.add_frame(id3::frame::Comment {
+                    lang: "XXX".to_string(),
+                    description: "".to_string(),
+                    text: text.clone(),
+                });
+            } else {
+                self.id3.add_frame(Frame::text(frame, text.clone()));
+            }
+        }
+    }
+
+    /// Regenerate the text chunks from the ID3 tag, with multiple values joined with `sep`, then add
+    /// back the `ANNO` chunks not mapped to the comment.
+    fn sync_text(&mut self, sep: &str) {
+        self.text.clear();
+        for (chunk, frame) in TEXT_CHUNKS {
+            let text = if frame == "COMM" {
+                self.id3
+                    .comments()
+                    .find(|c| c.description.is_empty())
+                    .map(|c| c.text.clone())
+            } else {
+                self.id3
+                    .get(frame)
+                    .and_then(|f| f.content().text())
+                    .map(|t| t.replace('\0', sep))
+            };
+            if let Some(text) = text {
+                self.text.push((*chunk, text));
+            }
+        }
+        for text in &self.annotations {
+            self.text.push((*b"ANNO", text.clone()));
+        }
+    }
+}
+
+/// Compute the duration from a `COMM` chunk: the number of sample frames divided by the sample
+/// rate, which is an 80-bit IEEE 754 extended precision float.
+fn comm_duration(comm: &[u8]) -> Option<f64> {
+    let frames = u32::from_be_bytes(comm.get(2..6)?.try_into().ok()?);
+    let rate = comm.get(8..18)?;
+    let exponent = i32::from(u16::from_be_bytes([rate[0], rate[1]]) & 0x7FFF) - 16383;
+    let mantissa = u64::from_be_bytes(rate[2..10].try_into().ok()?);
+    let sample_rate = mantissa as f64 * 2f64.powi(exponent - 63);
+    if sample_rate > 0.0 {
+        Some(f64::from(frames) / sample_rate)
+    } else {
+        None
+    }
+}
+
+impl_tag!(AiffTag, AiffInnerTag, TagType::Aiff);
+
+impl_id3v2_tag!(AiffTag, inner.id3, {
+    fn duration(&self) -> Option<f64> {
+        self.inner.duration()
+    }
+});
+
+impl AudioTagWrite for AiffTag {
+    fn write_to(&mut self, file: &mut dyn Storage) -> crate::Result<()> {
+        self.inner.write_with_config(file, &self.config)
+    }
+    fn write_to_path(&mut self, path: &str) -> crate::Result<()> {
+        let mut file = File::options().read(true).write(true).open(path)?;
+        self.write_to(&mut file)
+    }
+}
@@ -0,0 +1,792 @@
+use crate::*;
+use id3::Timestamp;
+use std::borrow::Cow;
+use std::io::{Read, Seek, SeekFrom};
+use std::str::FromStr;
+
+const PREAMBLE: &[u8; 8] = b"APETAGEX";
+const HEADER_LEN: u64 = 32;
+const ID3V1_LEN: u64 = 128;
+const FLAG_HAS_HEADER: u32 = 1 << 31;
+const FLAG_IS_HEADER: u32 = 1 << 29;
+const FLAG_READ_ONLY: u32 = 1;
+/// The keys of the binary cover art items for each picture type.
+const COVER_KEYS: [(PictureType, &str); 21] = [
+    (PictureType::Other, "Cover Art (Other)"),
+    (PictureType::Icon, "Cover Art (Png Icon)"),
+    (PictureType::OtherIcon, "Cover Art (Icon)"),
+    (PictureType::CoverFront, "Cover Art (Front)"),
+    (PictureType::CoverBack, "Cover Art (Back)"),
+    (PictureType::Leaflet, "Cover Art (Leaflet)"),
+    (PictureType::Media, "Cover Art (Media)"),
+    (PictureType::LeadArtist, "Cover Art (Lead Artist)"),
+    (PictureType::Artist, "Cover Art (Artist)"),
+    (PictureType::Conductor, "Cover Art (Conductor)"),
+    (PictureType::Band, "Cover Art (Band)"),
+    (PictureType::Composer, "Cover Art (Composer)"),
+    (PictureType::Lyricist, "Cover Art (Lyricist)"),
+    (
+        PictureType::RecordingLocation,
+        "Cover Art (Recording Location)",
+    ),
+    (PictureType::DuringRecording, "Cover Art (During Recording)"),
+    (
+        PictureType::DuringPerformance,
+        "Cover Art (During Performance)",
+    ),
+    (PictureType::ScreenCapture, "Cover Art (Video Capture)"),
+    (PictureType::BrightFish, "Cover Art (Fish)"),
+    (PictureType::Illustration, "Cover Art (Illustration)"),
+    (PictureType::BandLogo, "Cover Art (Band Logotype)"),
+    (PictureType::PublisherLogo, "Cover Art (Publisher Logotype)"),
+];
+
+/// The item key for `picture_type`; undefined types are stored as "Cover Art (Other)".
+fn cover_key(picture_type: PictureType) -> &'static str {
+    COVER_KEYS
+        .iter()
+        .find(|(t, _)| *t == picture_type)
+        .map_or(COVER_KEYS[0].1, |(_, key)| key)
+}
+
+/// The value of an APE tag item.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ApeItemValue {
+    /// UTF-8 text; multiple values are separated by null bytes in the file.
+    Text(Vec<String>),
+    /// Binary data, e.g. cover art (a file name, a null byte, then the image data).
+    Binary(Vec<u8>),
+    /// A link to external data.
+    Locator(String),
+}
+
+/// An APE tag item. Keys are case-insensitive.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ApeItem {
+    pub key: String,
+    pub value: ApeItemValue,
+    pub read_only: bool,
+}
+
+impl ApeItem {
+    pub fn text(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: ApeItemValue::Text(vec![value.into()]),
+            read_only: false,
+        }
+    }
+    pub fn binary(key: impl Into<String>, value: Vec<u8>) -> Self {
+        Self {
+            key: key.into(),
+            value: ApeItemValue::Binary(value),
+            read_only: false,
+        }
+    }
+}
+
+/// An APEv2 tag (APEv1 tags are read too, and upgraded when written), as found at the end of
+/// Monkey's Audio, WavPack and Musepack files.
+///
+/// ## References
+///
+/// - <https://wiki.hydrogenaud.io/index.php?title=APEv2_specification>
+#[derive(Clone, Debug, Default)]
+pub struct ApeInnerTag {
+    items: Vec<ApeItem>,
+    duration: Option<f64>,
+}
+
+impl ApeInnerTag {
+    pub fn read_from_path(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::read_from(&mut File::open(path)?)
+    }
+    pub fn read_from<R: Read + Seek + ?Sized>(reader: &mut R) -> crate::Result<Self> {
+        let duration = monkeys_audio_duration(reader)?;
+        let mut items = Vec::new();
+        if let Some(location) = locate(reader)? {
+            reader.seek(SeekFrom::Start(location.items_start))?;
+            let mut bytes = vec![0u8; location.items_len as usize];
+            reader.read_exact(&mut bytes)?;
+            items = parse_items(&bytes, location.item_count)?;
+        }
+        Ok(Self { items, duration })
+    }
+    /// Replace the APE tag of `file`, keeping any ID3v1 tag after it.
+    pub fn write_to(&self, file: &mut dyn Storage) -> crate::Result<()> {
+        let end = file.seek(SeekFrom::End(0))?;
+        let (start, trailer_start) = match locate(file)? {
+            Some(location) => (location.start, location.end),
+            None => {
+                let trailer_start = end - id3v1_len(file)?;
+                (trailer_start, trailer_start)
+            }
+        };
+        let mut trailer = Vec::new();
+        file.seek(SeekFrom::Start(trailer_start))?;
+        file.read_to_end(&mut trailer)?;
+        file.set_len(start)?;
+        file.seek(SeekFrom::Start(start))?;
+        if !self.items.is_empty() {
+            file.write_all(&self.to_bytes())?;
+        }
+        file.write_all(&trailer)?;
+        Ok(())
+    }
+    pub fn write_to_path(&self, path: impl AsRef<Path>) -> crate::Result<()> {
+        let mut file = File::options().read(true).write(true).open(path)?;
+        self.write_to(&mut file)
+    }
+
+    /// Serialize the tag, with both a header and a footer.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut items = Vec::new();
+        for item in &self.items {
+            let (kind, value) = match &item.value {
+                ApeItemValue::Text(v) => (0, v.join("\0").into_bytes()),
+                ApeItemValue::Binary(v) => (1, v.clone()),
+                ApeItemValue::Locator(v) => (2, v.clone().into_bytes()),
+            };
+            let flags = kind << 1 | if item.read_only { FLAG_READ_ONLY } else { 0 };
+            items.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            items.extend_from_slice(&flags.to_le_bytes());
+            items.extend_from_slice(item.key.as_bytes());
+            items.push(0);
+            items.extend(value);
+        }
+        let size = items.len() as u32 + HEADER_LEN as u32;
+        let header = |flags: u32| {
+            let mut h = PREAMBLE.to_vec();
+            h.extend_from_slice(&2000u32.to_le_bytes());
+            h.extend_from_slice(&size.to_le_bytes());
+            h.extend_from_slice(&(self.items.len() as u32).to_le_bytes());
+            h.extend_from_slice(&(FLAG_HAS_HEADER | flags).to_le_bytes());
+            h.extend_from_slice(&[0; 8]);
+            h
+        };
+        let mut bytes = header(FLAG_IS_HEADER);
+        bytes.extend(items);
+        bytes.extend(header(0));
+        bytes
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &ApeItem> {
+        self.items.iter()
+    }
+    pub fn item(&self, key: &str) -> Option<&ApeItem> {
+        self.items.iter().find(|i| i.key.eq_ignore_ascii_case(key))
+    }
+    /// Add an item, replacing any existing one with the same key.
+    pub fn set_item(&mut self, item: ApeItem) {
+        match self
+            .items
+            .iter_mut()
+            .find(|i| i.key.eq_ignore_ascii_case(&item.key))
+        {
+            Some(existing) => *existing = item,
+            None => self.items.push(item),
+        }
+    }
+    pub fn remove_item(&mut self, key: &str) {
+        self.items.retain(|i| !i.key.eq_ignore_ascii_case(key));
+    }
+
+    /// Duration in seconds, only available for Monkey's Audio files.
+    pub fn duration(&self) -> Option<f64> {
+        self.duration
+    }
+}
+
+/// Where an APE tag sits in a file.
+struct Location {
+    /// Start of the tag, including the header if there is one.
+    start: u64,
+    items_start: u64,
+    items_len: u64,
+    item_count: u32,
+    /// End of the footer.
+    end: u64,
+}
+
+/// Find the APE tag footer, either at the very end of the file or just before an ID3v1 tag.
+fn locate<R: Read + Seek + ?Sized>(reader: &mut R) -> crate::Result<Option<Location>> {
+    let end = reader.seek(SeekFrom::End(0))? - id3v1_len(reader)?;
+    if end < HEADER_LEN {
+        return Ok(None);
+    }
+    let mut footer = [0u8; HEADER_LEN as usize];
+    reader.seek(SeekFrom::Start(end - HEADER_LEN))?;
+    reader.read_exact(&mut footer)?;
+    if &footer[..8] != PREAMBLE {
+        return Ok(None);
+    }
+    let field = |i: usize| u32::from_le_bytes(footer[i..i + 4].try_into().unwrap());
+    let (version, size, item_count, flags) = (field(8), field(12), field(16), field(20));
+    let size = u64::from(size);
+    // APEv1 tags have no header
+    let header_len = if version >= 2000 && flags & FLAG_HAS_HEADER != 0 {
+        HEADER_LEN
+    } else {
+        0
+    };
+    if size < HEADER_LEN || size + header_len > end {
+        return Err(Error::ApeError("invalid tag size".to_owned()));
+    }
+    let items_start = end - size;
+    Ok(Some(Location {
+        start: items_start - header_len,
+        items_start,
+        items_len: size - HEADER_LEN,
+        item_count,
+        end,
+    }))
+}
+
+fn id3v1_len<R: Read + Seek + ?Sized>(reader: &mut R) -> crate::Result<u64> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    if len < ID3V1_LEN {
+        return Ok(0);
+    }
+    let mut magic = [0u8; 3];
+    reader.seek(SeekFrom::Start(len - ID3V1_LEN))?;
+    reader.read_exact(&mut magic)?;
+    Ok(if &magic == b"TAG" { ID3V1_LEN } else { 0 })
+}
+
+fn parse_items(mut bytes: &[u8], count: u32) -> crate::Result<Vec<ApeItem>> {
+    let malformed = || Error::ApeError("malformed item".to_owned());
+    // the count is untrusted, and an item takes at least 9 bytes
+    let mut items = Vec::with_capacity((count as usize).min(bytes.len() / 9));
+    for _ in 0..count {
+        let (size, rest) = bytes.split_first_chunk::<4>().ok_or_else(malformed)?;
+        let (flags, rest) = rest.split_first_chunk::<4>().ok_or_else(malformed)?;
+        let (size, flags) = (
+            u32::from_le_bytes(*size) as usize,
+            u32::from_le_bytes(*flags),
+        );
+        let key_len = rest.iter().position(|&b| b == 0).ok_or_else(malformed)?;
+        let key = String::from_utf8_lossy(&rest[..key_len]).into_owned();
+        let value = rest
+            .get(key_len + 1..key_len + 1 + size)
+            .ok_or_else(malformed)?;
+        bytes = &rest[key_len + 1 + size..];
+        let value = match flags >> 1 & 3 {
+            1 => ApeItemValue::Binary(value.to_vec()),
+            2 => ApeItemValue::Locator(String::from_utf8_lossy(value).into_owned()),
+            _ => ApeItemValue::Text(
+                String::from_utf8_lossy(value)
+                    .split('\0')
+                    .map(str::to_owned)
+                    .collect(),
+            ),
+        };
+        items.push(ApeItem {
+            key,
+            value,
+            read_only: flags & FLAG_READ_ONLY != 0,
+        });
+    }
+    Ok(items)
+}
+
+/// Read the duration from the header of a Monkey's Audio file (version 3.98 and later).
+fn monkeys_audio_duration<R: Read + Seek + ?Sized>(reader: &mut R) -> crate::Result<Option<f64>> {
+    let mut descriptor = [0u8; 52];
+    reader.seek(SeekFrom::Start(0))?;
+    if reader.read_exact(&mut descriptor).is_err() || &descriptor[..4] != b"MAC " {
+        return Ok(None);
+    }
+    let version = u16::from_le_bytes([descriptor[4], descriptor[5]]);
+    let descriptor_len = u32::from_le_bytes(descriptor[8..12].try_into().unwrap());
+    if version < 3980 {
+        return Ok(None);
+    }
+    let mut header = [0u8; 24];
+    reader.seek(SeekFrom::Start(u64::from(descriptor_len)))?;
+    reader.read_exact(&mut header)?;
+    let field = |i: usize| u32::from_le_bytes(header[i..i + 4].try_into().unwrap());
+    let (blocks_per_frame, final_frame_blocks, total_frames) = (field(4), field(8), field(12));
+    let sample_rate = field(20);
+    if total_frames == 0 || sample_rate == 0 {
+        return Ok(None);
+    }
+    let blocks =
+        u64::from(total_frames - 1) * u64::from(blocks_per_frame) + u64::from(final_frame_blocks);
+    Ok(Some(blocks as f64 / f64::from(sample_rate)))
+}
+
+/// The file name of a cover art item with an empty description, without the extension.
+const DEFAULT_COVER_NAME: &str = "cover";
+
+/// Split a binary cover art item (a file name, a null byte, then the image data) into the
+/// description (the file name without its extension), the mime type and the data.
+fn parse_cover(item: &ApeItem) -> Option<(Cow<'_, str>, MimeType, &[u8])> {
+    let ApeItemValue::Binary(v) = &item.value else {
+        return None;
+    };
+    let name_len = v.iter().position(|&b| b == 0)?;
+    let data = &v[name_len + 1..];
+    let name = String::from_utf8_lossy(&v[..name_len]);
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) => (stem.to_owned(), ext),
+        None => (name.to_string(), ""),
+    };
+    // the extension, e.g. `jpg` for `image/jpg`, is only used if the format is not detected
+    let mime_type = MimeType::guess(&format!("image/{}", ext), data)?;
+    let description = if stem == DEFAULT_COVER_NAME {
+        Cow::Borrowed("")
+    } else {
+        Cow::Owned(stem)
+    };
+    Some((description, mime_type, data))
+}
+
+impl_tag!(ApeTag, ApeInnerTag, TagType::Ape);
+
+impl<'a> From<&'a ApeTag> for AnyTag<'a> {
+    fn from(inp: &'a ApeTag) -> Self {
+        crate::anytag::anytag_from(inp)
+    }
+}
+
+impl<'a> From<AnyTag<'a>> for ApeTag {
+    fn from(inp: AnyTag<'a>) -> Self {
+        let mut t = ApeTag {
+            config: inp.config,
+            ..ApeTag::default()
+        };
+        crate::anytag::apply_anytag(&mut t, inp);
+        t
+    }
+}
+
+impl ApeTag {
+    pub fn get_first(&self, key: &str) -> Option<&str> {
+        match self.inner.item(key).map(|i| &i.value) {
+            Some(ApeItemValue::Text(v)) => v.first().map(String::as_str),
+            _ => None,
+        }
+    }
+    pub fn set_first(&mut self, key: &str, val: &str) {
+        self.inner.set_item(ApeItem::text(key, val));
+    }
+    pub fn remove(&mut self, key: &str) {
+        self.inner.remove_item(key);
+    }
+
+    /// Parse an `n/total` pair, as used by `Track` and `Disc`.
+    fn get_pair(&self, key: &str) -> (Option<u16>, Option<u16>) {
+        let Some(v) = self.get_first(key) else {
+            return (None, None);
+        };
+        let mut split = v.splitn(2, '/');
+        let number = split.next().and_then(|n| n.trim().parse().ok());
+        let total = split.next().and_then(|n| n.trim().parse().ok());
+        (number, total)
+    }
+    fn set_pair(&mut self, key: &str, number: Option<u16>, total: Option<u16>) {
+        match (number, total) {
+            (Some(n), Some(t)) => self.set_first(key, &format!("{}/{}", n, t)),
+            (Some(n), None) => self.set_first(key, &n.to_string()),
+            // the number is required before the slash; 0 stands for "unknown"
+            (None, Some(t)) => self.set_first(key, &format!("0/{}", t)),
+            (None, None) => self.remove(key),
+        }
+    }
+}
+
+impl AudioTagEdit for ApeTag {
+    fn title(&self) -> Option<&str> {
+        self.get_first("Title")
+    }
+    fn set_title(&mut self, title: &str) {
+        self.set_first("Title", title);
+    }
+    fn remove_title(&mut self) {
+        self.remove("Title");
+    }
+
+    fn artist(&self) -> Option<&str> {
+        self.get_first("Artist")
+    }
+    fn set_artist(&mut self, artist: &str) {
+        self.set_first("Artist", artist);
+    }
+    fn remove_artist(&mut self) {
+        self.remove("Artist");
+    }
+
+    fn date(&self) -> Option<Timestamp> {
+        if let Some(Ok(timestamp)) = self.get_first("Year").map(Timestamp::from_str) {
+            Some(timestamp)
+        } else {
+            None
+        }
+    }
+    fn set_date(&mut self, date: Timestamp) {
+        self.set_first("Year", &date.to_string());
+    }
+    fn remove_date(&mut self) {
+        self.remove("Year");
+    }
+
+    fn year(&self) -> Option<i32> {
+        self.get_first("Year")
+            .and_then(|s| s.chars().take(4).collect::<String>().parse().ok())
+    }
+    fn set_year(&mut self, year: i32) {
+        self.set_first("Year", &year.to_string());
+    }
+    fn remove_year(&mut self) {
+        self.remove("Year");
+    }
+
+    fn duration(&self) -> Option<f64> {
+        self.inner.duration()
+    }
+
+    fn album_title(&self) -> Option<&str> {
+        self.get_first("Album")
+    }
+    fn set_album_title(&mut self, v: &str) {
+        self.set_first("Album", v);
+    }
+    fn remove_album_title(&mut self) {
+        self.remove("Album");
+    }
+
+    fn album_artist(&self) -> Option<&str> {
+        self.get_first("Album Artist")
+    }
+    fn set_album_artist(&mut self, v: &str) {
+        self.set_first("Album Artist", v);
+    }
+    fn remove_album_artist(&mut self) {
+        self.remove("Album Artist");
+    }
+
+    fn album_cover(&self) -> Option<Picture<'_>> {
+        let item = self.inner.item(cover_key(PictureType::CoverFront))?;
+        let (_, mime_type, data) = parse_cover(item)?;
+        Some(Picture { data, mime_type })
+    }
+    fn set_album_cover(&mut self, cover: Picture) {
+        self.add_picture(cover.into());
+    }
+    fn remove_album_cover(&mut self) {
+        self.remove_pictures_by_type(PictureType::CoverFront);
+    }
+
+    fn pictures(&self) -> Vec<EmbeddedPicture<'_>> {
+        COVER_KEYS
+            .iter()
+            .filter_map(|&(picture_type, key)| {
+                let (description, mime_type, data) = parse_cover(self.inner.item(key)?)?;
+                Some(
+                    EmbeddedPicture::new(picture_type, mime_type, data)
+                        .with_description(description),
+                )
+            })
+            .collect()
+    }
+    /// The description is stored as the file name of the picture, `cover.<ext>` if empty.
+    fn add_picture(&mut self, picture: EmbeddedPicture) {
+        let ext = match &picture.mime_type {
+            MimeType::Png => "png",
+            MimeType::Jpeg => "jpg",
+            MimeType::Tiff => "tiff",
+            MimeType::Bmp => "bmp",
+            MimeType::Gif => "gif",
+            MimeType::Webp => "webp",
+            MimeType::Avif => "avif",
+            MimeType::Other(mime) => mime.trim_start_matches("image/"),
+        };
+        let name = match picture.description.as_ref() {
+            "" => DEFAULT_COVER_NAME,
+            description => description,
+        };
+        let mut value = format!("{}.{}\0", name, ext).into_bytes();
+        value.extend_from_slice(&picture.data);
+        self.inner
+            .set_item(ApeItem::binary(cover_key(picture.picture_type), value));
+    }
+    fn remove_pictures_by_type(&mut self, picture_type: PictureType) {
+        self.inner.remove_item(cover_key(picture_type));
+    }
+
+    fn composer(&self) -> Option<&str> {
+        self.get_first("Composer")
+    }
+    fn set_composer(&mut self, composer: String) {
+        self.set_first("Composer", &composer);
+    }
+    fn remove_composer(&mut self) {
+        self.remove("Composer");
+    }
+
+    fn track_number(&self) -> Option<u16> {
+        self.get_pair("Track").0
+    }
+    fn set_track_number(&mut self, v: u16) {
+        self.set_pair("Track", Some(v), self.total_tracks());
+    }
+    fn remove_track_number(&mut self) {
+        self.set_pair("Track", None, self.total_tracks());
+    }
+
+    fn total_tracks(&self) -> Option<u16> {
+        self.get_pair("Track").1
+    }
+    fn set_total_tracks(&mut self, v: u16) {
+        self.set_pair("Track", self.track_number(), Some(v));
+    }
+    fn remove_total_tracks(&mut self) {
+        self.set_pair("Track", self.track_number(), None);
+    }
+
+    fn disc_number(&self) -> Option<u16> {
+        self.get_pair("Disc").0
+    }
+    fn set_disc_number(&mut self, v: u16) {
+        self.set_pair("Disc", Some(v), self.total_discs());
+    }
+    fn remove_disc_number(&mut self) {
+        self.set_pair("Disc", None, self.total_discs());
+    }
+
+    fn total_discs(&self) -> Option<u16> {
+        self.get_pair("Disc").1
+    }
+    fn set_total_discs(&mut self, v: u16) {
+        self.set_pair("Disc", self.disc_number(), Some(v));
+    }
+    fn remove_total_discs(&mut self) {
+        self.set_pair("Disc", self.disc_number(), None);
+    }
+
+    fn genre(&self) -> Option<&str> {
+        self.get_first("Genre")
+    }
+    fn set_genre(&mut self, v: &str) {
+        self.set_first("Genre", v);
+    }
+    fn remove_genre(&mut self) {
+        self.remove("Genre");
+    }
+
+    fn comment(&self) -> Option<&str> {
+        self.get_first("Comment")
+    }
+    fn set_comment(&mut self, v: String) {
+        self.set_first("Comment", &v);
+    }
+    fn remove_comment(&mut self) {
+        self.remove("Comment");
+    }
+
+    /// `Lyrics`, which also holds synchronised lyrics as LRC text
+    fn lyrics(&self) -> Option<Lyrics<'_>> {
+        self.get_first("Lyrics").map(Lyrics::new)
+    }
+    fn set_lyrics(&mut self, lyrics: Lyrics) {
+        self.set_first("Lyrics", &lyrics.text);
+    }
+    fn remove_lyrics(&mut self) {
+        self.remove("Lyrics");
+    }
+
+    fn replay_gain(&self) -> Option<ReplayGain> {
+        ReplayGain::from_fields(|field| self.get_first(field))
+    }
+    fn set_replay_gain(&mut self, replay_gain: ReplayGain) {
+        for (field, value) in replay_gain.to_fields() {
+            match value {
+                Some(value) => self.set_first(field, &value),
+                None => self.remove(field),
+            }
+        }
+    }
+    fn remove_replay_gain(&mut self) {
+        for field in ReplayGain::FIELDS {
+            self.remove(field);
+        }
+    }
+
+    fn sort_order(&self, field: SortField) -> Option<&str> {
+        self.get_first(field.vorbis_key())
+    }
+    fn set_sort_order(&mut self, field: SortField, v: &str) {
+        self.set_first(field.vorbis_key(), v);
+    }
+    fn remove_sort_order(&mut self, field: SortField) {
+        self.remove(field.vorbis_key());
+    }
+
+    fn text_field(&self, field: TextField) -> Option<&str> {
+        self.get_first(field.vorbis_key())
+    }
+    fn set_text_field(&mut self, field: TextField, v: &str) {
+        self.set_first(field.vorbis_key(), v);
+    }
+    fn remove_text_field(&mut self, field: TextField) {
+        self.remove(field.vorbis_key());
+    }
+
+    fn bpm(&self) -> Option<u16> {
+        self.get_first("BPM").and_then(parse_bpm)
+    }
+    fn set_bpm(&mut self, bpm: u16) {
+        self.set_first("BPM", &bpm.to_string());
+    }
+    fn remove_bpm(&mut self) {
+        self.remove("BPM");
+    }
+
+    fn compilation(&self) -> bool {
+        self.get_first("Compilation").is_some_and(parse_flag)
+    }
+    fn set_compilation(&mut self, compilation: bool) {
+        if compilation {
+            self.set_first("Compilation", "1");
+        } else {
+            self.remove("Compilation");
+        }
+    }
+
+    fn original_date(&self) -> Option<Timestamp> {
+        self.get_first("OriginalDate")
+            .and_then(|v| Timestamp::from_str(v).ok())
+    }
+    fn set_original_date(&mut self, date: Timestamp) {
+        self.set_first("OriginalDate", &date.to_string());
+    }
+    fn remove_original_date(&mut self) {
+        self.remove("OriginalDate");
+    }
+
+    /// `FMPS_RATING`, or else `RATING` as a percentage
+    fn rating(&self) -> Option<f64> {
+        self.get_first("FMPS_RATING")
+            .and_then(|v| parse_rating(v, 1.0))
+            .or_else(|| {
+                self.get_first("RATING")
+                    .and_then(|v| parse_rating(v, 100.0))
+            })
+    }
+    fn set_rating(&mut self, rating: f64) {
+        let rating = rating.clamp(0.0, 1.0);
+        self.set_first("FMPS_RATING", &rating.to_string());
+        self.set_first("RATING", &(rating * 100.0).round().to_string());
+    }
+    fn remove_rating(&mut self) {
+        self.remove("FMPS_RATING");
+        self.remove("RATING");
+    }
+
+    fn play_count(&self) -> Option<u64> {
+        self.get_first("FMPS_PLAYCOUNT").and_then(parse_count)
+    }
+    fn set_play_count(&mut self, count: u64) {
+        self.set_first("FMPS_PLAYCOUNT", &count.to_string());
+    }
+    fn remove_play_count(&mut self) {
+        self.remove("FMPS_PLAYCOUNT");
+    }
+
+    fn get(&self, key: &TagKey) -> Vec<&str> {
+        match self.inner.item(key.ape_key()).map(|i| &i.value) {
+            Some(ApeItemValue::Text(v)) => v.iter().map(String::as_str).collect(),
+            _ => Vec::new(),
+        }
+    }
+    fn set(&mut self, key: &TagKey, values: &[&str]) {
+        if values.is_empty() {
+            self.remove(key.ape_key());
+        } else {
+            self.inner.set_item(ApeItem {
+                key: key.ape_key().to_owned(),
+                value: ApeItemValue::Text(values.iter().map(|&v| v.to_owned()).collect()),
+                read_only: false,
+            });
+        }
+    }
+
+    /// Binary items and locators have `Native` keys; cover art items are listed as pictures,
+    /// locators as text.
+    fn items(&self) -> Vec<(TagKey, Value<'_>)> {
+        let mut items = Vec::new();
+        for item in self.inner.items() {
+            let key = TagKey::from_ape_key(&item.key);
+            match &item.value {
+                ApeItemValue::Text(values) => items.extend(
+                    values
+                        .iter()
+                        .map(|v| (key.clone(), Value::Text(v.as_str().into()))),
+                ),
+                ApeItemValue::Locator(v) => {
+                    let key = TagKey::Native(item.key.clone());
+                    items.push((key, Value::Text(v.as_str().into())))
+                }
+                ApeItemValue::Binary(data) => {
+                    let cover = COVER_KEYS
+                        .iter()
+                        .find(|(_, k)| k.eq_ignore_ascii_case(&item.key))
+                        .and_then(|&(picture_type, _)| {
+                            let (description, mime_type, data) = parse_cover(item)?;
+                            Some(
+                                EmbeddedPicture::new(picture_type, mime_type, data)
+                                    .with_description(description),
+                            )
+                        });
+                    let value = match cover {
+                        Some(picture) => Value::Picture(picture),
+                        None => Value::Binary(data),
+                    };
+                    items.push((TagKey::Native(item.key.clone()), value));
+                }
+            }
+        }
+        items
+    }
+
+    fn custom_fields(&self) -> Vec<(&str, &str)> {
+        self.inner
+            .items()
+            .filter(|i| matches!(TagKey::from_ape_key(&i.key), TagKey::Custom(_)))
+            .flat_map(|i| match &i.value {
+                ApeItemValue::Text(values) => values
+                    .iter()
+                    .map(|v| (i.key.as_str(), v.as_str()))
+                    .collect(),
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    fn musicbrainz_ids(&self, id: MusicBrainzId) -> Vec<&str> {
+        match self.inner.item(id.vorbis_key()).map(|i| &i.value) {
+            Some(ApeItemValue::Text(v)) => v.iter().map(String::as_str).collect(),
+            _ => Vec::new(),
+        }
+    }
+    fn set_musicbrainz_ids(&mut self, id: MusicBrainzId, values: &[&str]) {
+        if values.is_empty() {
+            self.remove(id.vorbis_key());
+        } else {
+            self.inner.set_item(ApeItem {
+                key: id.vorbis_key().to_owned(),
+                value: ApeItemValue::Text(values.iter().map(|&v| v.to_owned()).collect()),
+                read_only: false,
+            });
+        }
+    }
+}
+
+impl AudioTagWrite for ApeTag {
+    fn write_to(&mut self, file: &mut dyn Storage) -> crate::Result<()> {
+        self.inner.write_to(file)
+    }
+    fn write_to_path(&mut self, path: &str) -> crate::Result<()> {
+        self.inner.write_to_path(path)
+    }
+}
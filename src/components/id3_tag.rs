@@ -1,271 +1,1094 @@
 use crate::*;
 use id3::{self, Content, Frame, TagLike, Timestamp};
+use std::borrow::Cow;
 
 pub use id3::Tag as Id3v2InnerTag;
 
-impl_tag!(Id3v2Tag, Id3v2InnerTag, TagType::Id3v2);
-
-impl<'a> From<&'a Id3v2Tag> for AnyTag<'a> {
-    fn from(inp: &'a Id3v2Tag) -> Self {
-        Self {
-            config: inp.config,
-
-            title: inp.title(),
-            artists: inp.artists(),
-            date: inp.date(),
-            year: inp.year(),
-            duration: inp.inner.duration().map(f64::from),
-            album_title: inp.album_title(),
-            album_artists: inp.album_artists(),
-            album_cover: inp.album_cover(),
-            track_number: inp.track_number(),
-            total_tracks: inp.total_tracks(),
-            disc_number: inp.disc_number(),
-            total_discs: inp.total_discs(),
-            genre: inp.genre(),
-            composer: inp.composer(),
-            comment: inp.comment(),
-        }
-    }
-}
-
-impl<'a> From<AnyTag<'a>> for Id3v2Tag {
-    fn from(inp: AnyTag<'a>) -> Self {
-        Self {
-            config: inp.config,
-            inner: {
-                let mut t = id3::Tag::new();
-                if let Some(v) = inp.title() {
-                    t.set_title(v)
-                }
-                if let Some(v) = inp.artists_as_string() {
-                    t.set_artist(&v)
-                }
-                if let Some(v) = inp.date() {
-                    t.set_date_recorded(v)
-                }
-                if let Some(v) = inp.year {
-                    t.set_year(v)
-                }
-                if let Some(v) = inp.album_title() {
-                    t.set_album(v)
-                }
-                if let Some(v) = inp.album_artists_as_string() {
-                    t.set_album_artist(&v)
-                }
-                if let Some(v) = inp.track_number() {
-                    t.set_track(v as u32)
-                }
-                if let Some(v) = inp.total_tracks() {
-                    t.set_total_tracks(v as u32)
-                }
-                if let Some(v) = inp.disc_number() {
-                    t.set_disc(v as u32)
-                }
-                if let Some(v) = inp.total_discs() {
-                    t.set_total_discs(v as u32)
-                }
-                if let Some(v) = inp.genre() {
-                    t.set_genre(v)
-                }
-                t
-            },
-        }
-    }
-}
-
 impl<'a> std::convert::TryFrom<&'a id3::frame::Picture> for Picture<'a> {
     type Error = crate::Error;
     fn try_from(inp: &'a id3::frame::Picture) -> crate::Result<Self> {
         let id3::frame::Picture {
             mime_type, data, ..
         } = inp;
-        let mime_type: MimeType = mime_type.as_str().try_into()?;
+        let mime_type = MimeType::guess(mime_type, data)
+            .ok_or_else(|| Error::UnsupportedMimeType(mime_type.to_owned()))?;
         Ok(Self { data, mime_type })
     }
 }
 
-impl AudioTagEdit for Id3v2Tag {
-    fn title(&self) -> Option<&str> {
-        self.inner.title()
-    }
-    fn set_title(&mut self, title: &str) {
-        self.inner.set_title(title)
-    }
-    fn remove_title(&mut self) {
-        self.inner.remove_title();
+impl<'a> std::convert::TryFrom<&'a id3::frame::Picture> for EmbeddedPicture<'a> {
+    type Error = crate::Error;
+    fn try_from(inp: &'a id3::frame::Picture) -> crate::Result<Self> {
+        let mime_type = MimeType::guess(&inp.mime_type, &inp.data)
+            .ok_or_else(|| Error::UnsupportedMimeType(inp.mime_type.to_owned()))?;
+        Ok(
+            Self::new(inp.picture_type.into(), mime_type, inp.data.as_slice())
+                .with_description(inp.description.as_str()),
+        )
     }
+}
 
-    fn artist(&self) -> Option<&str> {
-        self.inner.artist()
+/// The width, height and depth are dropped: `APIC` frames do not store them.
+impl From<EmbeddedPicture<'_>> for id3::frame::Picture {
+    fn from(inp: EmbeddedPicture<'_>) -> Self {
+        Self {
+            mime_type: String::from(inp.mime_type),
+            picture_type: inp.picture_type.into(),
+            description: inp.description.into_owned(),
+            data: inp.data.into_owned(),
+        }
     }
-    fn set_artist(&mut self, artist: &str) {
-        self.inner.set_artist(artist)
+}
+
+/// ID3 requires a three-letter language code, `XXX` if unknown.
+fn id3_lang(lang: &str) -> String {
+    if lang.len() == 3 && lang.bytes().all(|b| b.is_ascii_alphabetic()) {
+        lang.to_ascii_lowercase()
+    } else {
+        "XXX".to_owned()
     }
-    fn remove_artist(&mut self) {
-        self.inner.remove_artist();
+}
+
+fn known_lang(lang: &str) -> &str {
+    if lang.eq_ignore_ascii_case("XXX") {
+        ""
+    } else {
+        lang
     }
+}
 
-    fn date(&self) -> Option<Timestamp> {
-        self.inner.date_recorded()
+impl<'a> From<&'a id3::frame::Lyrics> for Lyrics<'a> {
+    fn from(inp: &'a id3::frame::Lyrics) -> Self {
+        Lyrics::new(inp.text.as_str())
+            .with_lang(known_lang(&inp.lang))
+            .with_description(inp.description.as_str())
     }
-    fn set_date(&mut self, timestamp: Timestamp) {
-        self.inner.set_date_recorded(timestamp)
+}
+
+impl From<Lyrics<'_>> for id3::frame::Lyrics {
+    fn from(inp: Lyrics<'_>) -> Self {
+        Self {
+            lang: id3_lang(&inp.lang),
+            description: inp.description.into_owned(),
+            text: inp.text.into_owned(),
+        }
     }
-    fn remove_date(&mut self) {
-        self.inner.remove_date_recorded()
+}
+
+/// Lines are often stored with a leading or trailing newline, which is removed.
+impl From<&id3::frame::SynchronisedLyrics> for SyncedLyrics {
+    fn from(inp: &id3::frame::SynchronisedLyrics) -> Self {
+        Self {
+            lang: known_lang(&inp.lang).to_owned(),
+            description: inp.description.clone(),
+            lines: inp
+                .content
+                .iter()
+                .map(|(time, text)| (*time, text.trim_matches(['\r', '\n']).to_owned()))
+                .collect(),
+        }
     }
+}
 
-    fn year(&self) -> Option<i32> {
-        self.inner.year()
+impl From<&SyncedLyrics> for id3::frame::SynchronisedLyrics {
+    fn from(inp: &SyncedLyrics) -> Self {
+        Self {
+            lang: id3_lang(&inp.lang),
+            timestamp_format: id3::frame::TimestampFormat::Ms,
+            content_type: id3::frame::SynchronisedLyricsType::Lyrics,
+            description: inp.description.clone(),
+            content: inp.lines.clone(),
+        }
     }
-    fn set_year(&mut self, year: i32) {
-        self.inner.set_year(year);
+}
+
+/// The identification and the channels of an `RVA2` frame.
+fn rva2(frame: &Frame) -> Option<(&[u8], &[u8])> {
+    match frame.content() {
+        Content::Unknown(u) if frame.id() == "RVA2" => {
+            let end = u.data.iter().position(|&b| b == 0)?;
+            Some((&u.data[..end], &u.data[end + 1..]))
+        }
+        _ => None,
     }
-    fn remove_year(&mut self) {
-        self.inner.remove_date_recorded();
-        self.inner.remove_year();
+}
+
+/// The master volume adjustment (in dB) and peak of the `RVA2` frame identified as `track` or
+/// `album`, as written by e.g. mutagen.
+fn rva2_master(tag: &id3::Tag, identification: &str) -> Option<(f64, Option<f64>)> {
+    let (_, mut channels) = tag
+        .frames()
+        .filter_map(rva2)
+        .find(|(id, _)| id.eq_ignore_ascii_case(identification.as_bytes()))?;
+    // channel type, volume adjustment in 1/512 dB, bits representing the peak, peak
+    while let [channel, a, b, bits, rest @ ..] = channels {
+        let peak_len = usize::from(*bits).div_ceil(8);
+        let peak = rest.get(..peak_len)?;
+        if *channel == 1 {
+            let gain = f64::from(i16::from_be_bytes([*a, *b])) / 512.0;
+            let peak = (1..=64).contains(bits).then(|| {
+                let v = peak.iter().fold(0u64, |v, &b| v << 8 | u64::from(b));
+                v as f64 / 2f64.powi(i32::from(*bits) - 1)
+            });
+            return Some((gain, peak));
+        }
+        channels = &rest[peak_len..];
     }
-    fn duration(&self) -> Option<f64> {
-        self.inner.duration().map(f64::from)
+    None
+}
+
+/// `TXXX:REPLAYGAIN_*` frames (in any case), or else the `track` and `album` `RVA2` frames.
+pub(crate) fn replay_gain(tag: &id3::Tag) -> Option<ReplayGain> {
+    ReplayGain::from_fields(|field| {
+        tag.extended_texts()
+            .find(|t| t.description.eq_ignore_ascii_case(field))
+            .map(|t| t.value.as_str())
+    })
+    .or_else(|| {
+        let track = rva2_master(tag, "track");
+        let album = rva2_master(tag, "album");
+        Some(ReplayGain {
+            track_gain: track.map(|(gain, _)| gain),
+            track_peak: track.and_then(|(_, peak)| peak),
+            album_gain: album.map(|(gain, _)| gain),
+            album_peak: album.and_then(|(_, peak)| peak),
+        })
+        .filter(|g| !g.is_empty())
+    })
+}
+
+/// `RVA2` frames are only read: they are removed so that they do not disagree with the new
+/// `TXXX` frames.
+pub(crate) fn set_replay_gain(tag: &mut id3::Tag, replay_gain: ReplayGain) {
+    remove_replay_gain(tag);
+    for (field, value) in replay_gain.to_fields() {
+        if let Some(value) = value {
+            tag.add_frame(id3::frame::ExtendedText {
+                description: field.to_owned(),
+                value,
+            });
+        }
     }
+}
 
-    fn album_title(&self) -> Option<&str> {
-        self.inner.album()
+pub(crate) fn remove_replay_gain(tag: &mut id3::Tag) {
+    for field in ReplayGain::FIELDS {
+        remove_extended_texts(tag, field);
     }
-    fn set_album_title(&mut self, v: &str) {
-        self.inner.set_album(v)
+    for frame in tag.remove("RVA2") {
+        let is_replay_gain = rva2(&frame).is_some_and(|(id, _)| {
+            id.eq_ignore_ascii_case(b"track") || id.eq_ignore_ascii_case(b"album")
+        });
+        if !is_replay_gain {
+            tag.add_frame(frame);
+        }
     }
-    fn remove_album_title(&mut self) {
-        self.inner.remove_album();
+}
+
+/// The values of the `TXXX` frame with `description` (in any case), separated by null bytes.
+fn extended_text_values<'a>(tag: &'a id3::Tag, description: &str) -> Vec<&'a str> {
+    tag.extended_texts()
+        .find(|t| t.description.eq_ignore_ascii_case(description))
+        .map(|t| t.value.split('\0').collect())
+        .unwrap_or_default()
+}
+
+fn set_extended_text_values(tag: &mut id3::Tag, description: &str, values: &[&str]) {
+    remove_extended_texts(tag, description);
+    if !values.is_empty() {
+        tag.add_frame(id3::frame::ExtendedText {
+            description: description.to_owned(),
+            value: values.join("\0"),
+        });
     }
+}
 
-    fn album_artist(&self) -> Option<&str> {
-        self.inner.album_artist()
+/// Remove the `TXXX` frames whose description is `description`, in any case.
+fn remove_extended_texts(tag: &mut id3::Tag, description: &str) {
+    let descriptions: Vec<_> = tag
+        .extended_texts()
+        .filter(|t| t.description.eq_ignore_ascii_case(description))
+        .map(|t| t.description.clone())
+        .collect();
+    for description in descriptions {
+        tag.remove_extended_text(Some(&description), None);
     }
-    fn set_album_artist(&mut self, v: &str) {
-        self.inner.set_album_artist(v)
+}
+
+/// The owner of the `UFID` frame holding the MusicBrainz recording ID.
+const MUSICBRAINZ_UFID_OWNER: &str = "http://musicbrainz.org";
+
+/// The recording ID from the `UFID` frame, the other IDs from `TXXX` frames (in any case), with
+/// multiple values separated by null bytes (ID3v2.4) or slashes (ID3v2.3).
+pub(crate) fn musicbrainz_ids(tag: &id3::Tag, id: MusicBrainzId) -> Vec<&str> {
+    if id == MusicBrainzId::Recording {
+        return tag
+            .unique_file_identifiers()
+            .filter(|u| u.owner_identifier == MUSICBRAINZ_UFID_OWNER)
+            .filter_map(|u| std::str::from_utf8(&u.identifier).ok())
+            .take(1)
+            .collect();
     }
-    fn remove_album_artist(&mut self) {
-        self.inner.remove_album_artist();
+    tag.extended_texts()
+        .find(|t| t.description.eq_ignore_ascii_case(id.description()))
+        .map(|t| {
+            t.value
+                .split(['\0', '/'])
+                .filter(|v| !v.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn set_musicbrainz_ids(tag: &mut id3::Tag, id: MusicBrainzId, values: &[&str]) {
+    if id == MusicBrainzId::Recording {
+        tag.remove_unique_file_identifier_by_owner_identifier(MUSICBRAINZ_UFID_OWNER);
+        if let Some(value) = values.first() {
+            tag.add_frame(id3::frame::UniqueFileIdentifier {
+                owner_identifier: MUSICBRAINZ_UFID_OWNER.to_owned(),
+                identifier: value.as_bytes().to_vec(),
+            });
+        }
+        return;
     }
+    set_extended_text_values(tag, id.description(), values);
+}
 
-    fn album_cover(&self) -> Option<Picture> {
-        self.inner
-            .pictures()
-            .find(|&pic| matches!(pic.picture_type, id3::frame::PictureType::CoverFront))
-            .and_then(|pic| {
-                Some(Picture {
-                    data: &pic.data,
-                    mime_type: (pic.mime_type.as_str()).try_into().ok()?,
-                })
-            })
+/// `TSO2` and `TSOC` are not part of the ID3 standard, but written by iTunes and most taggers.
+pub(crate) fn sort_frame_id(field: SortField) -> &'static str {
+    match field {
+        SortField::Title => "TSOT",
+        SortField::Artist => "TSOP",
+        SortField::Album => "TSOA",
+        SortField::AlbumArtist => "TSO2",
+        SortField::Composer => "TSOC",
     }
-    fn set_album_cover(&mut self, cover: Picture) {
-        self.remove_album_cover();
-        self.inner.add_frame(id3::frame::Picture {
-            mime_type: String::from(cover.mime_type),
-            picture_type: id3::frame::PictureType::CoverFront,
-            description: "".to_owned(),
-            data: cover.data.to_owned(),
-        });
+}
+
+/// The text frame of a `TextField`, or `None` for the catalog number, which is stored in a
+/// `TXXX:CATALOGNUMBER` frame.
+fn text_frame_id(field: TextField) -> Option<&'static str> {
+    Some(match field {
+        TextField::Isrc => "TSRC",
+        TextField::Label => "TPUB",
+        TextField::CatalogNumber => return None,
+        TextField::Copyright => "TCOP",
+        TextField::EncodedBy => "TENC",
+        TextField::Grouping => "TIT1",
+        TextField::Mood => "TMOO",
+        TextField::Language => "TLAN",
+        TextField::Conductor => "TPE3",
+        TextField::Lyricist => "TEXT",
+    })
+}
+
+pub(crate) fn text_field(tag: &id3::Tag, field: TextField) -> Option<&str> {
+    match text_frame_id(field) {
+        Some(id) => tag.get(id).and_then(|f| f.content().text()),
+        None => tag
+            .extended_texts()
+            .find(|t| t.description.eq_ignore_ascii_case(field.vorbis_key()))
+            .map(|t| t.value.as_str()),
     }
-    fn remove_album_cover(&mut self) {
-        self.inner
-            .remove_picture_by_type(id3::frame::PictureType::CoverFront);
+}
+
+pub(crate) fn set_text_field(tag: &mut id3::Tag, field: TextField, v: &str) {
+    match text_frame_id(field) {
+        Some(id) => tag.set_text(id, v),
+        None => set_extended_text_values(tag, field.vorbis_key(), &[v]),
     }
+}
 
-    fn composer(&self) -> Option<&str> {
-        if let Some(Content::Text(text)) = self.inner.get("TCOM").map(Frame::content) {
-            return Some(text);
+pub(crate) fn remove_text_field(tag: &mut id3::Tag, field: TextField) {
+    match text_frame_id(field) {
+        Some(id) => {
+            tag.remove(id);
         }
-
-        None
+        None => remove_extended_texts(tag, field.vorbis_key()),
     }
-    fn set_composer(&mut self, composer: String) {
-        self.inner.add_frame(Frame::text("TCOM", composer));
+}
+
+/// `TDRC`, or else the ID3v2.3 `TYER`, `TDAT` (`DDMM`) and `TIME` (`HHMM`) frames.
+pub(crate) fn date(tag: &id3::Tag) -> Option<Timestamp> {
+    tag.date_recorded().or_else(|| id3v23_date(tag))
+}
+
+fn id3v23_date(tag: &id3::Tag) -> Option<Timestamp> {
+    let text = |id| tag.get(id).and_then(|f| f.content().text()).map(str::trim);
+    let pair = |id| {
+        let text: &str = text(id).filter(|t| t.len() == 4 && t.is_ascii())?;
+        Some((text[..2].parse::<u8>().ok()?, text[2..].parse::<u8>().ok()?))
+    };
+    let (day, month) = pair("TDAT").unzip();
+    let (hour, minute) = pair("TIME").unzip();
+    Some(Timestamp {
+        year: text("TYER")?.parse().ok()?,
+        month,
+        day,
+        hour: hour.filter(|_| day.is_some()),
+        minute: minute.filter(|_| day.is_some()),
+        second: None,
+    })
+}
+
+/// `TDOR`, or else the year of the ID3v2.3 `TORY` frame.
+pub(crate) fn original_date(tag: &id3::Tag) -> Option<Timestamp> {
+    tag.original_date_released().or_else(|| {
+        let year = tag.get("TORY")?.content().text()?.trim().parse().ok()?;
+        Some(Timestamp {
+            year,
+            month: None,
+            day: None,
+            hour: None,
+            minute: None,
+            second: None,
+        })
+    })
+}
+
+/// The `POPM` frames, the one of `email` first.
+fn popularimeters<'a>(tag: &'a id3::Tag, email: &str) -> Vec<&'a id3::frame::Popularimeter> {
+    let mut popms: Vec<_> = tag
+        .frames()
+        .filter_map(|f| f.content().popularimeter())
+        .collect();
+    popms.sort_by_key(|p| p.user != email);
+    popms
+}
+
+/// Apply `f` to the `POPM` frames, adding one for `email` if there is none, and drop those left
+/// with neither rating nor counter.
+fn update_popularimeters(
+    tag: &mut id3::Tag,
+    email: &str,
+    f: impl Fn(&mut id3::frame::Popularimeter),
+) {
+    let mut popms: Vec<_> = popularimeters(tag, email).into_iter().cloned().collect();
+    if !popms.iter().any(|p| p.user == email) {
+        popms.insert(
+            0,
+            id3::frame::Popularimeter {
+                user: email.to_owned(),
+                rating: 0,
+                counter: 0,
+            },
+        );
     }
-    fn remove_composer(&mut self) {
-        self.inner.remove("TCOM");
+    tag.remove("POPM");
+    for mut popm in popms {
+        f(&mut popm);
+        if popm.rating != 0 || popm.counter != 0 {
+            tag.add_frame(popm);
+        }
     }
+}
 
-    fn track_number(&self) -> Option<u16> {
-        self.inner.track().map(|x| x as u16)
-    }
-    fn set_track_number(&mut self, track: u16) {
-        self.inner.set_track(track as u32);
+/// The `POPM` rating of `email`, or else the first one; 0 is no rating, 1 to 255 are scaled to
+/// 0.0 to 1.0.
+pub(crate) fn rating(tag: &id3::Tag, email: &str) -> Option<f64> {
+    popularimeters(tag, email)
+        .into_iter()
+        .find(|p| p.rating != 0)
+        .map(|p| f64::from(p.rating - 1) / 254.0)
+}
+
+pub(crate) fn set_rating(tag: &mut id3::Tag, email: &str, rating: f64) {
+    let rating = 1 + (rating.clamp(0.0, 1.0) * 254.0).round() as u8;
+    update_popularimeters(tag, email, |p| {
+        if p.user == email {
+            p.rating = rating;
+        }
+    });
+}
+
+pub(crate) fn remove_rating(tag: &mut id3::Tag, email: &str) {
+    update_popularimeters(tag, email, |p| p.rating = 0);
+}
+
+/// The `PCNT` frame, or else the `POPM` counter of `email` (or of the first one).
+pub(crate) fn play_count(tag: &id3::Tag, email: &str) -> Option<u64> {
+    match tag.get("PCNT").map(Frame::content) {
+        Some(Content::Unknown(pcnt)) if pcnt_count(&pcnt.data).is_some() => pcnt_count(&pcnt.data),
+        _ => popularimeters(tag, email)
+            .into_iter()
+            .find(|p| p.counter != 0)
+            .map(|p| p.counter),
     }
-    fn remove_track_number(&mut self) {
-        self.inner.remove_track();
+}
+
+/// The big-endian counter of a `PCNT` frame.
+fn pcnt_count(data: &[u8]) -> Option<u64> {
+    (1..=8)
+        .contains(&data.len())
+        .then(|| data.iter().fold(0, |count, &b| count << 8 | u64::from(b)))
+}
+
+/// Write the `PCNT` frame (at least 4 bytes, as the spec requires), and the `POPM` counter of
+/// `email` if it has one.
+pub(crate) fn set_play_count(tag: &mut id3::Tag, email: &str, count: u64) {
+    let bytes = count.to_be_bytes();
+    let skip = (count.leading_zeros() / 8).min(4) as usize;
+    let pcnt = Content::Unknown(id3::frame::Unknown {
+        data: bytes[skip..].to_vec(),
+        version: tag.version(),
+    });
+    tag.add_frame(Frame::with_content("PCNT", pcnt));
+    update_popularimeters(tag, email, |p| {
+        if p.user == email && p.counter != 0 {
+            p.counter = count;
+        }
+    });
+}
+
+pub(crate) fn remove_play_count(tag: &mut id3::Tag, email: &str) {
+    tag.remove("PCNT");
+    update_popularimeters(tag, email, |p| p.counter = 0);
+}
+
+/// Where the values of a `TagKey` are stored.
+enum Id3Field<'a> {
+    Text(&'a str),
+    Native(&'a str),
+    Extended(&'a str),
+    Comment,
+    Lyrics,
+    MusicBrainz(MusicBrainzId),
+}
+
+fn id3_field(key: &TagKey) -> Id3Field<'_> {
+    Id3Field::Text(match key {
+        TagKey::Title => "TIT2",
+        TagKey::Artist => "TPE1",
+        TagKey::Album => "TALB",
+        TagKey::AlbumArtist => "TPE2",
+        TagKey::Genre => "TCON",
+        TagKey::Composer => "TCOM",
+        TagKey::Date => "TDRC",
+        TagKey::Comment => return Id3Field::Comment,
+        TagKey::Lyrics => return Id3Field::Lyrics,
+        TagKey::Text(field) => match text_frame_id(*field) {
+            Some(id) => id,
+            None => return Id3Field::Extended(field.vorbis_key()),
+        },
+        TagKey::Sort(field) => sort_frame_id(*field),
+        TagKey::MusicBrainz(id) => return Id3Field::MusicBrainz(*id),
+        TagKey::Custom(name) => return Id3Field::Extended(name),
+        TagKey::Native(id) => return Id3Field::Native(id),
+    })
+}
+
+/// The comments are those without description, as with `AudioTagEdit::comment`.
+pub(crate) fn values<'a>(tag: &'a id3::Tag, key: &TagKey) -> Vec<&'a str> {
+    match id3_field(key) {
+        Id3Field::Text(id) => tag
+            .get(id)
+            .and_then(|f| f.content().text_values())
+            .map(Iterator::collect)
+            .unwrap_or_default(),
+        Id3Field::Native(id) => tag
+            .frames()
+            .filter(|f| f.id() == id)
+            .filter_map(|f| f.content().text_values())
+            .flatten()
+            .collect(),
+        Id3Field::Extended(description) => extended_text_values(tag, description),
+        Id3Field::Comment => tag
+            .comments()
+            .filter(|c| c.description.is_empty())
+            .map(|c| c.text.as_str())
+            .collect(),
+        Id3Field::Lyrics => tag.lyrics().map(|l| l.text.as_str()).collect(),
+        Id3Field::MusicBrainz(id) => musicbrainz_ids(tag, id),
     }
+}
 
-    fn total_tracks(&self) -> Option<u16> {
-        self.inner.total_tracks().map(|x| x as u16)
+/// `COMM` and `USLT` frames hold a single value, so the values are joined with line breaks.
+pub(crate) fn set_values(tag: &mut id3::Tag, key: &TagKey, values: &[&str]) {
+    match id3_field(key) {
+        Id3Field::Text(id) if values.is_empty() => {
+            tag.remove(id);
+        }
+        Id3Field::Text(id) => tag.set_text_values(id, values.iter().copied()),
+        Id3Field::Native(id) if values.is_empty() => {
+            tag.remove(id);
+        }
+        // only text frames can be set
+        Id3Field::Native(id) if id.starts_with('T') && id != "TXXX" => {
+            tag.set_text_values(id, values.iter().copied())
+        }
+        Id3Field::Native(_) => {}
+        Id3Field::Extended(description) => set_extended_text_values(tag, description, values),
+        Id3Field::Comment => {
+            tag.remove_comment(Some(""), None);
+            if !values.is_empty() {
+                tag.add_frame(id3::frame::Comment {
+                    lang: "XXX".to_string(),
+                    description: "".to_string(),
+                    text: values.join("\n"),
+                });
+            }
+        }
+        Id3Field::Lyrics => {
+            tag.remove_all_lyrics();
+            if !values.is_empty() {
+                tag.add_frame(id3::frame::Lyrics::from(Lyrics::new(values.join("\n"))));
+            }
+        }
+        Id3Field::MusicBrainz(id) => set_musicbrainz_ids(tag, id, values),
     }
-    fn set_total_tracks(&mut self, total_track: u16) {
-        self.inner.set_total_tracks(total_track as u32);
+}
+
+/// The key of a text frame: a standard key, or else `Native`.
+fn text_frame_key(id: &str) -> TagKey {
+    TagKey::standard()
+        .find(|k| matches!(id3_field(k), Id3Field::Text(i) if i == id))
+        .unwrap_or_else(|| TagKey::Native(id.to_owned()))
+}
+
+/// The key of a `TXXX` frame: a standard key, `Native` for the ReplayGain ones, or else `Custom`.
+fn extended_text_key(description: &str) -> TagKey {
+    let standard = TagKey::standard().find(|k| match id3_field(k) {
+        Id3Field::Extended(d) => d.eq_ignore_ascii_case(description),
+        Id3Field::MusicBrainz(id) => {
+            id != MusicBrainzId::Recording && id.description().eq_ignore_ascii_case(description)
+        }
+        _ => false,
+    });
+    match standard {
+        Some(key) => key,
+        None if ReplayGain::FIELDS
+            .iter()
+            .any(|f| f.eq_ignore_ascii_case(description)) =>
+        {
+            TagKey::Native("TXXX".to_owned())
+        }
+        None => TagKey::Custom(description.to_owned()),
     }
-    fn remove_total_tracks(&mut self) {
-        self.inner.remove_total_tracks();
+}
+
+/// The values of the `TXXX` frames with a `Custom` key.
+pub(crate) fn custom_fields(tag: &id3::Tag) -> Vec<(&str, &str)> {
+    tag.extended_texts()
+        .filter(|t| matches!(extended_text_key(&t.description), TagKey::Custom(_)))
+        .flat_map(|t| t.value.split('\0').map(|v| (t.description.as_str(), v)))
+        .collect()
+}
+
+/// Frames with neither text nor picture are listed with their `Display`, or their data if
+/// unknown to the `id3` crate.
+pub(crate) fn items(tag: &id3::Tag) -> Vec<(TagKey, Value<'_>)> {
+    let mut items = Vec::new();
+    for frame in tag.frames() {
+        let id = frame.id();
+        match frame.content() {
+            Content::Text(_) => {
+                let key = text_frame_key(id);
+                let values = frame.content().text_values().into_iter().flatten();
+                items.extend(values.map(|v| (key.clone(), Value::Text(v.into()))));
+            }
+            Content::ExtendedText(t) => {
+                let key = extended_text_key(&t.description);
+                let values = t.value.split('\0');
+                items.extend(values.map(|v| (key.clone(), Value::Text(v.into()))));
+            }
+            Content::Comment(c) if c.description.is_empty() => {
+                items.push((TagKey::Comment, Value::Text(c.text.as_str().into())))
+            }
+            Content::Lyrics(l) => items.push((TagKey::Lyrics, Value::Text(l.text.as_str().into()))),
+            Content::UniqueFileIdentifier(u) if u.owner_identifier == MUSICBRAINZ_UFID_OWNER => {
+                items.push((
+                    TagKey::MusicBrainz(MusicBrainzId::Recording),
+                    Value::Text(String::from_utf8_lossy(&u.identifier)),
+                ))
+            }
+            Content::Picture(p) => {
+                if let Ok(picture) = EmbeddedPicture::try_from(p) {
+                    items.push((TagKey::Native(id.to_owned()), Value::Picture(picture)))
+                }
+            }
+            Content::Unknown(u) => {
+                let value = match pcnt_count(&u.data) {
+                    Some(count) if id == "PCNT" => Value::Number(count as i64),
+                    _ => Value::Binary(&u.data),
+                };
+                items.push((TagKey::Native(id.to_owned()), value))
+            }
+            content => items.push((
+                TagKey::Native(id.to_owned()),
+                Value::Text(content.to_string().into()),
+            )),
+        }
     }
+    items
+}
+
+/// Implement `AudioTagEdit` and the `AnyTag` conversions for a tag type holding an `id3::Tag` at
+/// `self.$inner`. Format-specific methods (i.e. `duration`) are passed in the braces.
+macro_rules! impl_id3v2_tag {
+    ($tag:ident, $($inner:ident).+, { $($extra:tt)* }) => {
+        impl<'a> From<&'a $tag> for AnyTag<'a> {
+            fn from(inp: &'a $tag) -> Self {
+                crate::anytag::anytag_from(inp)
+            }
+        }
+
+        impl<'a> From<AnyTag<'a>> for $tag {
+            fn from(inp: AnyTag<'a>) -> Self {
+                let mut t = $tag {
+                    config: inp.config,
+                    ..$tag::default()
+                };
+                crate::anytag::apply_anytag(&mut t, inp);
+                t
+            }
+        }
+
+        impl AudioTagEdit for $tag {
+            fn title(&self) -> Option<&str> {
+                self.$($inner).+.title()
+            }
+            fn set_title(&mut self, title: &str) {
+                self.$($inner).+.set_title(title)
+            }
+            fn remove_title(&mut self) {
+                self.$($inner).+.remove_title();
+            }
+
+            fn artist(&self) -> Option<&str> {
+                crate::components::id3_tag::values(&self.$($inner).+, &TagKey::Artist).into_iter().next()
+            }
+            fn set_artist(&mut self, artist: &str) {
+                self.$($inner).+.set_artist(artist)
+            }
+            fn remove_artist(&mut self) {
+                self.$($inner).+.remove_artist();
+            }
+
+            fn date(&self) -> Option<Timestamp> {
+                crate::components::id3_tag::date(&self.$($inner).+)
+            }
+            fn set_date(&mut self, timestamp: Timestamp) {
+                self.$($inner).+.remove("TDAT");
+                self.$($inner).+.remove("TIME");
+                self.$($inner).+.set_date_recorded(timestamp)
+            }
+            fn remove_date(&mut self) {
+                self.$($inner).+.remove("TDAT");
+                self.$($inner).+.remove("TIME");
+                self.$($inner).+.remove_date_recorded()
+            }
+
+            fn year(&self) -> Option<i32> {
+                self.$($inner).+.year().or_else(|| self.date().map(|d| d.year))
+            }
+            /// Set `TYER`, and the year of `TDRC` if there is one, which takes precedence.
+            fn set_year(&mut self, year: i32) {
+                if let Some(date) = self.$($inner).+.date_recorded() {
+                    self.$($inner).+.set_date_recorded(Timestamp { year, ..date });
+                }
+                self.$($inner).+.set_year(year);
+            }
+            fn remove_year(&mut self) {
+                self.$($inner).+.remove_date_recorded();
+                self.$($inner).+.remove_year();
+            }
+
+            fn album_title(&self) -> Option<&str> {
+                self.$($inner).+.album()
+            }
+            fn set_album_title(&mut self, v: &str) {
+                self.$($inner).+.set_album(v)
+            }
+            fn remove_album_title(&mut self) {
+                self.$($inner).+.remove_album();
+            }
+
+            fn album_artist(&self) -> Option<&str> {
+                crate::components::id3_tag::values(&self.$($inner).+, &TagKey::AlbumArtist).into_iter().next()
+            }
+            fn set_album_artist(&mut self, v: &str) {
+                self.$($inner).+.set_album_artist(v)
+            }
+            fn remove_album_artist(&mut self) {
+                self.$($inner).+.remove_album_artist();
+            }
+
+            fn album_cover(&self) -> Option<Picture<'_>> {
+                self.$($inner).+
+                    .pictures()
+                    .find(|&pic| matches!(pic.picture_type, id3::frame::PictureType::CoverFront))
+                    .and_then(|pic| {
+                        Some(Picture {
+                            data: &pic.data,
+                            mime_type: MimeType::guess(&pic.mime_type, &pic.data)?,
+                        })
+                    })
+            }
+            fn set_album_cover(&mut self, cover: Picture) {
+                self.remove_album_cover();
+                self.$($inner).+.add_frame(id3::frame::Picture {
+                    mime_type: String::from(cover.mime_type),
+                    picture_type: id3::frame::PictureType::CoverFront,
+                    description: "".to_owned(),
+                    data: cover.data.to_owned(),
+                });
+            }
+            fn remove_album_cover(&mut self) {
+                self.$($inner).+
+                    .remove_picture_by_type(id3::frame::PictureType::CoverFront);
+            }
+
+            fn pictures(&self) -> Vec<EmbeddedPicture<'_>> {
+                self.$($inner).+
+                    .pictures()
+                    .filter_map(|pic| pic.try_into().ok())
+                    .collect()
+            }
+            fn add_picture(&mut self, picture: EmbeddedPicture) {
+                // `add_frame` replaces the `APIC` frame of the same picture type
+                self.$($inner).+.add_frame(id3::frame::Picture::from(picture));
+            }
+            fn remove_pictures_by_type(&mut self, picture_type: PictureType) {
+                self.$($inner).+.remove_picture_by_type(picture_type.into());
+            }
+
+            fn composer(&self) -> Option<&str> {
+                crate::components::id3_tag::values(&self.$($inner).+, &TagKey::Composer).into_iter().next()
+            }
+            fn set_composer(&mut self, composer: String) {
+                self.$($inner).+.add_frame(Frame::text("TCOM", composer));
+            }
+            fn remove_composer(&mut self) {
+                self.$($inner).+.remove("TCOM");
+            }
+
+            fn track_number(&self) -> Option<u16> {
+                self.$($inner).+.track().map(|x| x as u16)
+            }
+            fn set_track_number(&mut self, track: u16) {
+                self.$($inner).+.set_track(track as u32);
+            }
+            fn remove_track_number(&mut self) {
+                self.$($inner).+.remove_track();
+            }
+
+            fn total_tracks(&self) -> Option<u16> {
+                self.$($inner).+.total_tracks().map(|x| x as u16)
+            }
+            fn set_total_tracks(&mut self, total_track: u16) {
+                self.$($inner).+.set_total_tracks(total_track as u32);
+            }
+            fn remove_total_tracks(&mut self) {
+                self.$($inner).+.remove_total_tracks();
+            }
+
+            fn disc_number(&self) -> Option<u16> {
+                self.$($inner).+.disc().map(|x| x as u16)
+            }
+            fn set_disc_number(&mut self, disc_number: u16) {
+                self.$($inner).+.set_disc(disc_number as u32)
+            }
+            fn remove_disc_number(&mut self) {
+                self.$($inner).+.remove_disc();
+            }
+
+            fn total_discs(&self) -> Option<u16> {
+                self.$($inner).+.total_discs().map(|x| x as u16)
+            }
+            fn set_total_discs(&mut self, total_discs: u16) {
+                self.$($inner).+.set_total_discs(total_discs as u32)
+            }
+            fn remove_total_discs(&mut self) {
+                self.$($inner).+.remove_total_discs();
+            }
+
+            fn genre(&self) -> Option<&str> {
+                crate::components::id3_tag::values(&self.$($inner).+, &TagKey::Genre).into_iter().next()
+            }
+            fn set_genre(&mut self, v: &str) {
+                self.$($inner).+.set_genre(v);
+            }
+            fn remove_genre(&mut self) {
+                self.$($inner).+.remove_genre();
+            }
+
+            fn comment(&self) -> Option<&str> {
+                for comment in self.$($inner).+.comments() {
+                    if comment.description.is_empty() {
+                        return Some(comment.text.as_str());
+                    }
+                }
+                None
+            }
+            fn set_comment(&mut self, comment: String) {
+                self.$($inner).+.add_frame(id3::frame::Comment {
+                    lang: "XXX".to_string(),
+                    description: "".to_string(),
+                    text: comment,
+                });
+            }
+            fn remove_comment(&mut self) {
+                self.$($inner).+.remove("COMM");
+            }
 
-    fn disc_number(&self) -> Option<u16> {
-        self.inner.disc().map(|x| x as u16)
+            fn lyrics(&self) -> Option<Lyrics<'_>> {
+                self.$($inner).+.lyrics().next().map(Lyrics::from)
+            }
+            fn set_lyrics(&mut self, lyrics: Lyrics) {
+                self.remove_lyrics();
+                self.$($inner).+.add_frame(id3::frame::Lyrics::from(lyrics));
+            }
+            fn remove_lyrics(&mut self) {
+                self.$($inner).+.remove_all_lyrics();
+            }
+
+            /// The first `SYLT` frame with timestamps in milliseconds (rather than MPEG frames).
+            fn synced_lyrics(&self) -> Option<SyncedLyrics> {
+                self.$($inner).+
+                    .synchronised_lyrics()
+                    .find(|l| l.timestamp_format == id3::frame::TimestampFormat::Ms)
+                    .map(SyncedLyrics::from)
+            }
+            fn set_synced_lyrics(&mut self, lyrics: &SyncedLyrics) {
+                self.remove_synced_lyrics();
+                self.$($inner).+
+                    .add_frame(id3::frame::SynchronisedLyrics::from(lyrics));
+            }
+            fn remove_synced_lyrics(&mut self) {
+                self.$($inner).+.remove_all_synchronised_lyrics();
+            }
+
+            fn replay_gain(&self) -> Option<ReplayGain> {
+                crate::components::id3_tag::replay_gain(&self.$($inner).+)
+            }
+            fn set_replay_gain(&mut self, replay_gain: ReplayGain) {
+                crate::components::id3_tag::set_replay_gain(&mut self.$($inner).+, replay_gain)
+            }
+            fn remove_replay_gain(&mut self) {
+                crate::components::id3_tag::remove_replay_gain(&mut self.$($inner).+)
+            }
+
+            fn sort_order(&self, field: SortField) -> Option<&str> {
+                self.$($inner).+
+                    .get(crate::components::id3_tag::sort_frame_id(field))
+                    .and_then(|f| f.content().text())
+            }
+            fn set_sort_order(&mut self, field: SortField, v: &str) {
+                self.$($inner).+
+                    .set_text(crate::components::id3_tag::sort_frame_id(field), v);
+            }
+            fn remove_sort_order(&mut self, field: SortField) {
+                self.$($inner).+
+                    .remove(crate::components::id3_tag::sort_frame_id(field));
+            }
+
+            fn text_field(&self, field: TextField) -> Option<&str> {
+                crate::components::id3_tag::text_field(&self.$($inner).+, field)
+            }
+            fn set_text_field(&mut self, field: TextField, v: &str) {
+                crate::components::id3_tag::set_text_field(&mut self.$($inner).+, field, v)
+            }
+            fn remove_text_field(&mut self, field: TextField) {
+                crate::components::id3_tag::remove_text_field(&mut self.$($inner).+, field)
+            }
+
+            fn bpm(&self) -> Option<u16> {
+                self.$($inner).+
+                    .get("TBPM")
+                    .and_then(|f| f.content().text())
+                    .and_then(parse_bpm)
+            }
+            fn set_bpm(&mut self, bpm: u16) {
+                self.$($inner).+.set_text("TBPM", bpm.to_string());
+            }
+            fn remove_bpm(&mut self) {
+                self.$($inner).+.remove("TBPM");
+            }
+
+            /// The iTunes `TCMP` frame
+            fn compilation(&self) -> bool {
+                self.$($inner).+
+                    .get("TCMP")
+                    .and_then(|f| f.content().text())
+                    .is_some_and(parse_flag)
+            }
+            fn set_compilation(&mut self, compilation: bool) {
+                if compilation {
+                    self.$($inner).+.set_text("TCMP", "1");
+                } else {
+                    self.$($inner).+.remove("TCMP");
+                }
+            }
+
+            fn original_date(&self) -> Option<Timestamp> {
+                crate::components::id3_tag::original_date(&self.$($inner).+)
+            }
+            fn set_original_date(&mut self, date: Timestamp) {
+                self.$($inner).+.remove("TORY");
+                self.$($inner).+.set_original_date_released(date);
+            }
+            fn remove_original_date(&mut self) {
+                self.$($inner).+.remove("TORY");
+                self.$($inner).+.remove_original_date_released();
+            }
+
+            fn rating(&self) -> Option<f64> {
+                crate::components::id3_tag::rating(&self.$($inner).+, self.config.popm_email)
+            }
+            fn set_rating(&mut self, rating: f64) {
+                let email = self.config.popm_email;
+                crate::components::id3_tag::set_rating(&mut self.$($inner).+, email, rating)
+            }
+            fn remove_rating(&mut self) {
+                let email = self.config.popm_email;
+                crate::components::id3_tag::remove_rating(&mut self.$($inner).+, email)
+            }
+
+            fn play_count(&self) -> Option<u64> {
+                crate::components::id3_tag::play_count(&self.$($inner).+, self.config.popm_email)
+            }
+            fn set_play_count(&mut self, count: u64) {
+                let email = self.config.popm_email;
+                crate::components::id3_tag::set_play_count(&mut self.$($inner).+, email, count)
+            }
+            fn remove_play_count(&mut self) {
+                let email = self.config.popm_email;
+                crate::components::id3_tag::remove_play_count(&mut self.$($inner).+, email)
+            }
+
+            fn get(&self, key: &TagKey) -> Vec<&str> {
+                crate::components::id3_tag::values(&self.$($inner).+, key)
+            }
+            fn set(&mut self, key: &TagKey, values: &[&str]) {
+                crate::components::id3_tag::set_values(&mut self.$($inner).+, key, values)
+            }
+
+            fn items(&self) -> Vec<(TagKey, Value<'_>)> {
+                crate::components::id3_tag::items(&self.$($inner).+)
+            }
+            fn custom_fields(&self) -> Vec<(&str, &str)> {
+                crate::components::id3_tag::custom_fields(&self.$($inner).+)
+            }
+
+            fn musicbrainz_ids(&self, id: MusicBrainzId) -> Vec<&str> {
+                crate::components::id3_tag::musicbrainz_ids(&self.$($inner).+, id)
+            }
+            fn set_musicbrainz_ids(&mut self, id: MusicBrainzId, values: &[&str]) {
+                crate::components::id3_tag::set_musicbrainz_ids(&mut self.$($inner).+, id, values)
+            }
+
+            $($extra)*
+        }
+    };
+}
+
+impl_tag!(
+    Id3v2Tag,
+    Id3v2InnerTag,
+    TagType::Id3v2,
+    Id3v2InnerTag::read_from2
+);
+
+impl_id3v2_tag!(Id3v2Tag, inner, {
+    fn duration(&self) -> Option<f64> {
+        self.inner.duration().map(f64::from)
     }
-    fn set_disc_number(&mut self, disc_number: u16) {
-        self.inner.set_disc(disc_number as u32)
+});
+
+/// Convert the frames ID3v2.2 and ID3v2.3 lack: `TDRC` becomes `TYER`/`TDAT`/`TIME` and `TDOR`
+/// becomes `TORY`.
+///
+/// Multiple values of text frames are joined with `/` by the `id3` crate, and only split back for
+/// `TPE1`, `TCOM`, `TEXT`, `TOLY` and `TOPE`, so a value containing `/` (e.g. the artist "AC/DC")
+/// can't survive the round trip. `TXXX` values keep their `\0` separators.
+fn to_id3v23(tag: &id3::Tag) -> id3::Tag {
+    let mut tag = tag.clone();
+    if let Some(date) = tag.date_recorded() {
+        tag.remove_date_recorded();
+        tag.set_year(date.year);
+        if let (Some(month), Some(day)) = (date.month, date.day) {
+            tag.set_text("TDAT", format!("{day:02}{month:02}"));
+        }
+        if let (Some(hour), Some(minute)) = (date.hour, date.minute) {
+            tag.set_text("TIME", format!("{hour:02}{minute:02}"));
+        }
     }
-    fn remove_disc_number(&mut self) {
-        self.inner.remove_disc();
+    if let Some(date) = tag.original_date_released() {
+        tag.remove_original_date_released();
+        tag.set_text("TORY", format!("{:04}", date.year));
     }
+    tag
+}
 
-    fn total_discs(&self) -> Option<u16> {
-        self.inner.total_discs().map(|x| x as u16)
+/// Convert the ID3v2.3 date frames: `TYER`/`TDAT`/`TIME` become `TDRC` (unless there is one) and
+/// `TORY` becomes `TDOR`.
+fn to_id3v24(tag: &id3::Tag) -> id3::Tag {
+    let mut tag = tag.clone();
+    if let Some(date) = date(&tag) {
+        tag.set_date_recorded(date);
     }
-    fn set_total_discs(&mut self, total_discs: u16) {
-        self.inner.set_total_discs(total_discs as u32)
+    for id in ["TYER", "TDAT", "TIME"] {
+        tag.remove(id);
     }
-    fn remove_total_discs(&mut self) {
-        self.inner.remove_total_discs();
+    if let Some(date) = original_date(&tag) {
+        tag.remove("TORY");
+        tag.set_original_date_released(date);
     }
+    tag
+}
 
-    fn genre(&self) -> Option<&str> {
-        self.inner.genre()
-    }
-    fn set_genre(&mut self, v: &str) {
-        self.inner.set_genre(v);
+/// The tag to write, converted to `version` or else kept in the version it was read with.
+pub(crate) fn to_write_version(
+    tag: &id3::Tag,
+    version: Option<id3::Version>,
+) -> (Cow<'_, id3::Tag>, id3::Version) {
+    match version.unwrap_or(tag.version()) {
+        id3::Version::Id3v24
+            if ["TYER", "TDAT", "TIME", "TORY"]
+                .iter()
+                .any(|id| tag.get(id).is_some()) =>
+        {
+            (Cow::Owned(to_id3v24(tag)), id3::Version::Id3v24)
+        }
+        id3::Version::Id3v24 => (Cow::Borrowed(tag), id3::Version::Id3v24),
+        version => (Cow::Owned(to_id3v23(tag)), version),
     }
-    fn remove_genre(&mut self) {
-        self.inner.remove_genre();
+}
+
+impl Id3v2Tag {
+    /// The tag to write, converted to `Config::id3v2_version` or else the version it was read with.
+    fn to_write_version(&self) -> (Cow<'_, id3::Tag>, id3::Version) {
+        to_write_version(&self.inner, self.config.id3v2_version)
     }
 
-    fn comment(&self) -> Option<&str> {
-        for comment in self.inner.comments() {
-            if comment.description.is_empty() {
-                return Some(comment.text.as_str());
+    /// Update or strip the ID3v1 trailer of `file` according to `Config::id3v1_write`.
+    fn sync_id3v1(&self, file: &mut dyn Storage) -> crate::Result<()> {
+        match self.config.id3v1_write {
+            Id3v1Write::Keep => {}
+            Id3v1Write::Update => {
+                let v1 = Id3v1Tag::from(self.to_anytag());
+                super::id3v1_tag::write_id3v1(file, &v1.into())?;
+            }
+            Id3v1Write::Strip => {
+                super::id3v1_tag::remove_id3v1(file)?;
             }
         }
-        None
-    }
-    fn set_comment(&mut self, comment: String) {
-        self.inner.add_frame(id3::frame::Comment {
-            lang: "XXX".to_string(),
-            description: "".to_string(),
-            text: comment,
-        });
-    }
-    fn remove_comment(&mut self) {
-        self.inner.remove("COMM");
+        Ok(())
     }
 }
 
 impl AudioTagWrite for Id3v2Tag {
-    fn write_to(&mut self, file: &mut File) -> crate::Result<()> {
-        self.inner.write_to(file, id3::Version::Id3v24)?;
-        Ok(())
+    fn write_to(&mut self, file: &mut dyn Storage) -> crate::Result<()> {
+        let (tag, version) = self.to_write_version();
+        write_via_file(file, |f| Ok(tag.write_to_file(f, version)?))?;
+        self.sync_id3v1(file)
     }
     fn write_to_path(&mut self, path: &str) -> crate::Result<()> {
-        self.inner.write_to_path(path, id3::Version::Id3v24)?;
+        let (tag, version) = self.to_write_version();
+        tag.write_to_path(path, version)?;
+        if self.config.id3v1_write != Id3v1Write::Keep {
+            self.sync_id3v1(&mut File::options().read(true).write(true).open(path)?)?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,420 @@
+use crate::*;
+use id3::Timestamp;
+use std::io::SeekFrom;
+
+pub use id3::v1::Tag as Id3v1InnerTag;
+
+/// The genre byte of a tag without genre, past the end of the (Winamp extended) genre list.
+const GENRE_UNKNOWN: u8 = 255;
+
+impl_tag!(Id3v1Tag, Id3v1InnerTag, TagType::Id3v1);
+
+impl<'a> From<&'a Id3v1Tag> for AnyTag<'a> {
+    fn from(inp: &'a Id3v1Tag) -> Self {
+        Self {
+            config: inp.config,
+            title: inp.title(),
+            artists: inp.artists(),
+            date: inp.date(),
+            year: inp.year(),
+            album_title: inp.album_title(),
+            track_number: inp.track_number(),
+            genres: inp.genres(),
+            comment: inp.comment(),
+            ..Self::default()
+        }
+    }
+}
+
+impl<'a> From<AnyTag<'a>> for Id3v1Tag {
+    fn from(inp: AnyTag<'a>) -> Self {
+        let mut t = Id3v1Tag {
+            config: inp.config,
+            ..Id3v1Tag::default()
+        };
+        if let Some(v) = inp.title() {
+            t.set_title(v)
+        }
+        if let Some(v) = inp.artists_as_string() {
+            t.set_artist(&v)
+        }
+        if let Some(v) = inp.year.or_else(|| inp.date().map(|d| d.year)) {
+            t.set_year(v)
+        }
+        if let Some(v) = inp.album_title() {
+            t.set_album_title(v)
+        }
+        if let Some(v) = inp.track_number() {
+            t.set_track_number(v)
+        }
+        if let Some(v) = inp.genre() {
+            t.set_genre(v)
+        }
+        if let Some(v) = inp.comment() {
+            t.set_comment(v.to_owned())
+        }
+        t
+    }
+}
+
+fn non_empty(s: &str) -> Option<&str> {
+    if s.is_empty() {
+        None
+    } else {
+        Some(s)
+    }
+}
+
+/// ID3v1 has no room for album artists, composers, pictures, lyrics, ReplayGain, sort orders,
+/// `TextField`s, BPM, compilation, original date, ratings, play counts, MusicBrainz IDs, total
+/// tracks or discs: setting them does nothing.
+impl AudioTagEdit for Id3v1Tag {
+    fn title(&self) -> Option<&str> {
+        non_empty(&self.inner.title)
+    }
+    fn set_title(&mut self, title: &str) {
+        self.inner.title = title.to_owned();
+    }
+    fn remove_title(&mut self) {
+        self.inner.title.clear();
+    }
+
+    fn artist(&self) -> Option<&str> {
+        non_empty(&self.inner.artist)
+    }
+    fn set_artist(&mut self, artist: &str) {
+        self.inner.artist = artist.to_owned();
+    }
+    fn remove_artist(&mut self) {
+        self.inner.artist.clear();
+    }
+
+    /// ID3v1 has a single artist field: the artists are separated by `Config::sep_artist` (if
+    /// `Config::parse_multiple_artists`).
+    fn artists(&self) -> Option<Vec<&str>> {
+        if self.config.parse_multiple_artists {
+            self.artist()
+                .map(|a| a.split(self.config.sep_artist).collect::<Vec<&str>>())
+        } else {
+            self.artist().map(|v| vec![v])
+        }
+    }
+
+    fn date(&self) -> Option<Timestamp> {
+        self.year().map(|year| Timestamp {
+            year,
+            month: None,
+            day: None,
+            hour: None,
+            minute: None,
+            second: None,
+        })
+    }
+    fn set_date(&mut self, date: Timestamp) {
+        self.set_year(date.year);
+    }
+    fn remove_date(&mut self) {
+        self.remove_year();
+    }
+
+    fn year(&self) -> Option<i32> {
+        self.inner.year.trim().parse().ok()
+    }
+    fn set_year(&mut self, year: i32) {
+        self.inner.year = year.clamp(0, 9999).to_string();
+    }
+    fn remove_year(&mut self) {
+        self.inner.year.clear();
+    }
+
+    fn duration(&self) -> Option<f64> {
+        None
+    }
+
+    fn album_title(&self) -> Option<&str> {
+        non_empty(&self.inner.album)
+    }
+    fn set_album_title(&mut self, v: &str) {
+        self.inner.album = v.to_owned();
+    }
+    fn remove_album_title(&mut self) {
+        self.inner.album.clear();
+    }
+
+    fn album_artist(&self) -> Option<&str> {
+        None
+    }
+    fn set_album_artist(&mut self, _v: &str) {}
+    fn remove_album_artist(&mut self) {}
+
+    fn album_cover(&self) -> Option<Picture<'_>> {
+        None
+    }
+    fn set_album_cover(&mut self, _cover: Picture) {}
+    fn remove_album_cover(&mut self) {}
+    fn try_set_album_cover(&mut self, cover: Picture) -> crate::Result<()> {
+        Err(Error::UnsupportedPictureFormat(cover.mime_type))
+    }
+
+    fn composer(&self) -> Option<&str> {
+        None
+    }
+    fn set_composer(&mut self, _composer: String) {}
+    fn remove_composer(&mut self) {}
+
+    fn track_number(&self) -> Option<u16> {
+        self.inner.track.map(u16::from)
+    }
+    fn set_track_number(&mut self, track: u16) {
+        self.inner.track = u8::try_from(track).ok().filter(|&t| t != 0);
+    }
+    fn remove_track_number(&mut self) {
+        self.inner.track = None;
+    }
+
+    fn total_tracks(&self) -> Option<u16> {
+        None
+    }
+    fn set_total_tracks(&mut self, _total_track: u16) {}
+    fn remove_total_tracks(&mut self) {}
+
+    fn disc_number(&self) -> Option<u16> {
+        None
+    }
+    fn set_disc_number(&mut self, _disc_number: u16) {}
+    fn remove_disc_number(&mut self) {}
+
+    fn total_discs(&self) -> Option<u16> {
+        None
+    }
+    fn set_total_discs(&mut self, _total_discs: u16) {}
+    fn remove_total_discs(&mut self) {}
+
+    fn genre(&self) -> Option<&str> {
+        self.inner.genre()
+    }
+    /// Only the genres of the ID3v1 genre list can be stored; setting any other genre removes it.
+    fn set_genre(&mut self, genre: &str) {
+        let mut t = Id3v1InnerTag::new();
+        self.inner.genre_str = None;
+        self.inner.genre_id = (0..GENRE_UNKNOWN)
+            .find(|&id| {
+                t.genre_id = id;
+                t.genre().is_some_and(|g| g.eq_ignore_ascii_case(genre))
+            })
+            .unwrap_or(GENRE_UNKNOWN);
+    }
+    fn remove_genre(&mut self) {
+        self.inner.genre_str = None;
+        self.inner.genre_id = GENRE_UNKNOWN;
+    }
+
+    fn comment(&self) -> Option<&str> {
+        non_empty(&self.inner.comment)
+    }
+    fn set_comment(&mut self, comment: String) {
+        self.inner.comment = comment;
+    }
+    fn remove_comment(&mut self) {
+        self.inner.comment.clear();
+    }
+
+    fn lyrics(&self) -> Option<Lyrics<'_>> {
+        None
+    }
+    fn set_lyrics(&mut self, _lyrics: Lyrics) {}
+    fn remove_lyrics(&mut self) {}
+
+    fn replay_gain(&self) -> Option<ReplayGain> {
+        None
+    }
+    fn set_replay_gain(&mut self, _replay_gain: ReplayGain) {}
+    fn remove_replay_gain(&mut self) {}
+
+    fn sort_order(&self, _field: SortField) -> Option<&str> {
+        None
+    }
+    fn set_sort_order(&mut self, _field: SortField, _v: &str) {}
+    fn remove_sort_order(&mut self, _field: SortField) {}
+
+    fn text_field(&self, _field: TextField) -> Option<&str> {
+        None
+    }
+    fn set_text_field(&mut self, _field: TextField, _v: &str) {}
+    fn remove_text_field(&mut self, _field: TextField) {}
+
+    fn bpm(&self) -> Option<u16> {
+        None
+    }
+    fn set_bpm(&mut self, _bpm: u16) {}
+    fn remove_bpm(&mut self) {}
+
+    fn compilation(&self) -> bool {
+        false
+    }
+    fn set_compilation(&mut self, _compilation: bool) {}
+
+    fn original_date(&self) -> Option<Timestamp> {
+        None
+    }
+    fn set_original_date(&mut self, _date: Timestamp) {}
+    fn remove_original_date(&mut self) {}
+
+    fn rating(&self) -> Option<f64> {
+        None
+    }
+    fn set_rating(&mut self, _rating: f64) {}
+    fn remove_rating(&mut self) {}
+
+    fn play_count(&self) -> Option<u64> {
+        None
+    }
+    fn set_play_count(&mut self, _count: u64) {}
+    fn remove_play_count(&mut self) {}
+
+    fn get(&self, key: &TagKey) -> Vec<&str> {
+        let value = match key {
+            TagKey::Title => self.title(),
+            TagKey::Artist => self.artist(),
+            TagKey::Album => self.album_title(),
+            TagKey::Genre => self.genre(),
+            TagKey::Comment => self.comment(),
+            TagKey::Date => non_empty(&self.inner.year),
+            _ => None,
+        };
+        value.into_iter().collect()
+    }
+    /// Artists are joined with `Config::sep_artist`; other fields keep the first value.
+    fn set(&mut self, key: &TagKey, values: &[&str]) {
+        let Some(&first) = values.first() else {
+            match key {
+                TagKey::Title => self.remove_title(),
+                TagKey::Artist => self.remove_artist(),
+                TagKey::Album => self.remove_album_title(),
+                TagKey::Genre => self.remove_genre(),
+                TagKey::Comment => self.remove_comment(),
+                TagKey::Date => self.remove_year(),
+                _ => {}
+            }
+            return;
+        };
+        match key {
+            TagKey::Title => self.set_title(first),
+            TagKey::Artist => self.set_artist(&values.join(self.config.sep_artist)),
+            TagKey::Album => self.set_album_title(first),
+            TagKey::Genre => self.set_genre(first),
+            TagKey::Comment => self.set_comment(first.to_owned()),
+            TagKey::Date => {
+                if let Ok(date) = first.parse::<Timestamp>() {
+                    self.set_year(date.year)
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The track number is listed as `Native("track")`.
+    fn items(&self) -> Vec<(TagKey, Value<'_>)> {
+        let mut items: Vec<_> = [
+            TagKey::Title,
+            TagKey::Artist,
+            TagKey::Album,
+            TagKey::Date,
+            TagKey::Comment,
+            TagKey::Genre,
+        ]
+        .into_iter()
+        .flat_map(|key| {
+            self.get(&key)
+                .into_iter()
+                .map(move |v| (key.clone(), Value::Text(v.into())))
+        })
+        .collect();
+        if let Some(track) = self.track_number() {
+            items.push((
+                TagKey::Native("track".to_owned()),
+                Value::Number(i64::from(track)),
+            ));
+        }
+        items
+    }
+
+    fn custom_field(&self, _name: &str) -> Vec<&str> {
+        Vec::new()
+    }
+    fn set_custom_field(&mut self, _name: &str, _values: &[&str]) {}
+    fn custom_fields(&self) -> Vec<(&str, &str)> {
+        Vec::new()
+    }
+
+    fn musicbrainz_ids(&self, _id: MusicBrainzId) -> Vec<&str> {
+        Vec::new()
+    }
+    fn set_musicbrainz_ids(&mut self, _id: MusicBrainzId, _values: &[&str]) {}
+}
+
+/// Serialize `tag` as a 128-byte ID3v1.1 trailer. Text is encoded as ISO-8859-1 (characters outside
+/// of it become `?`) and truncated to the size of its field.
+fn to_bytes(tag: &Id3v1InnerTag) -> [u8; 128] {
+    fn put(field: &mut [u8], s: &str) {
+        let bytes = s.chars().map(|c| u8::try_from(c).unwrap_or(b'?'));
+        for (b, c) in field.iter_mut().zip(bytes) {
+            *b = c;
+        }
+    }
+    let mut bytes = [0u8; 128];
+    bytes[..3].copy_from_slice(b"TAG");
+    put(&mut bytes[3..33], &tag.title);
+    put(&mut bytes[33..63], &tag.artist);
+    put(&mut bytes[63..93], &tag.album);
+    put(&mut bytes[93..97], &tag.year);
+    match tag.track {
+        Some(track) => {
+            put(&mut bytes[97..125], &tag.comment);
+            bytes[126] = track;
+        }
+        None => put(&mut bytes[97..127], &tag.comment),
+    }
+    bytes[127] = tag.genre_id;
+    bytes
+}
+
+/// Remove the ID3v1 trailer of `file`, along with its extended `TAG+` block, if any.
+pub(crate) fn remove_id3v1(file: &mut dyn Storage) -> crate::Result<()> {
+    let len = file.seek(SeekFrom::End(0))?;
+    let has_magic = |file: &mut dyn Storage, size: u64, magic: &[u8]| -> crate::Result<bool> {
+        if len < size {
+            return Ok(false);
+        }
+        let mut buf = vec![0u8; magic.len()];
+        file.seek(SeekFrom::Start(len - size))?;
+        file.read_exact(&mut buf)?;
+        Ok(buf == magic)
+    };
+    if has_magic(file, 128, b"TAG")? {
+        let size = if has_magic(file, 355, b"TAG+")? {
+            355
+        } else {
+            128
+        };
+        file.set_len(len - size)?;
+    }
+    Ok(())
+}
+
+/// Replace the ID3v1 trailer of `file` (if any, along with its extended `TAG+` block) with `tag`.
+pub(crate) fn write_id3v1(file: &mut dyn Storage, tag: &Id3v1InnerTag) -> crate::Result<()> {
+    remove_id3v1(file)?;
+    file.seek(SeekFrom::End(0))?;
+    file.write_all(&to_bytes(tag))?;
+    Ok(())
+}
+
+impl AudioTagWrite for Id3v1Tag {
+    fn write_to(&mut self, file: &mut dyn Storage) -> crate::Result<()> {
+        write_id3v1(file, &self.inner)
+    }
+    fn write_to_path(&mut self, path: &str) -> crate::Result<()> {
+        self.write_to(&mut File::options().read(true).write(true).open(path)?)
+    }
+}
@@ -1,90 +1,221 @@
 use crate::*;
 use id3::Timestamp;
-use mp4ameta::{self, ImgFmt};
+use mp4ameta::{self, Data, DataIdent, Fourcc, FreeformIdent, ImgFmt};
 use std::str::FromStr;
 
 pub use mp4ameta::Tag as Mp4InnerTag;
 
 impl_tag!(Mp4Tag, Mp4InnerTag, TagType::Mp4);
 
+/// The mean of the freeform (`----`) atoms written by iTunes and most taggers.
+const ITUNES_MEAN: &str = "com.apple.iTunes";
+
 impl<'a> From<&'a Mp4Tag> for AnyTag<'a> {
     fn from(inp: &'a Mp4Tag) -> Self {
-        let title = inp.title();
-        let artists = inp.artists().map(|i| i.into_iter().collect::<Vec<_>>());
-        let date = inp.date();
-        let year = inp.year();
-        let duration = inp.duration();
-        let album_title = inp.album_title();
-        let album_artists = inp
-            .album_artists()
-            .map(|i| i.into_iter().collect::<Vec<_>>());
-        let album_cover = inp.album_cover();
-        let (a, b) = inp.track();
-        let track_number = a;
-        let total_tracks = b;
-        let (a, b) = inp.disc();
-        let disc_number = a;
-        let total_discs = b;
-        let genre = inp.genre();
-        let composer = inp.composer();
-        let comment = inp.comment();
-        Self {
-            config: inp.config,
-            title,
-            artists,
-            date,
-            year,
-            duration,
-            album_title,
-            album_cover,
-            album_artists,
-            track_number,
-            total_tracks,
-            disc_number,
-            total_discs,
-            genre,
-            composer,
-            comment,
-        }
+        crate::anytag::anytag_from(inp)
     }
 }
 
 impl<'a> From<AnyTag<'a>> for Mp4Tag {
     fn from(inp: AnyTag<'a>) -> Self {
-        Self {
+        let mut tag = Self {
             config: inp.config,
-            inner: {
-                let mut t = mp4ameta::Tag::default();
-                if let Some(v) = inp.title() {
-                    t.set_title(v)
-                }
-                if let Some(i) = inp.artists() {
-                    i.iter().for_each(|&a| t.add_artist(a))
-                }
-                if let Some(v) = inp.year {
-                    t.set_year(v.to_string())
-                }
-                if let Some(v) = inp.album_title() {
-                    t.set_album(v)
-                }
-                if let Some(i) = inp.album_artists() {
-                    i.iter().for_each(|&a| t.add_album_artist(a))
-                }
-                if let Some(v) = inp.track_number() {
-                    t.set_track_number(v)
-                }
-                if let Some(v) = inp.total_tracks() {
-                    t.set_total_tracks(v)
-                }
-                if let Some(v) = inp.disc_number() {
-                    t.set_disc_number(v)
+            inner: mp4ameta::Tag::default(),
+        };
+        crate::anytag::apply_anytag(&mut tag, inp);
+        tag
+    }
+}
+
+const TITLE_SORT: Fourcc = Fourcc(*b"sonm");
+const ARTIST_SORT: Fourcc = Fourcc(*b"soar");
+const ALBUM_SORT: Fourcc = Fourcc(*b"soal");
+const ALBUM_ARTIST_SORT: Fourcc = Fourcc(*b"soaa");
+const COMPOSER_SORT: Fourcc = Fourcc(*b"soco");
+
+fn sort_ident(field: SortField) -> &'static Fourcc {
+    match field {
+        SortField::Title => &TITLE_SORT,
+        SortField::Artist => &ARTIST_SORT,
+        SortField::Album => &ALBUM_SORT,
+        SortField::AlbumArtist => &ALBUM_ARTIST_SORT,
+        SortField::Composer => &COMPOSER_SORT,
+    }
+}
+
+/// The atom of a `TextField`: a standard one, or else a freeform atom named after the vorbis
+/// comment.
+fn text_ident(field: TextField) -> Option<&'static Fourcc> {
+    match field {
+        TextField::Copyright => Some(&mp4ameta::ident::COPYRIGHT),
+        TextField::EncodedBy => Some(&mp4ameta::ident::ENCODER),
+        TextField::Grouping => Some(&mp4ameta::ident::GROUPING),
+        _ => None,
+    }
+}
+
+/// Where the values of a `TagKey` are stored.
+enum Mp4Field<'a> {
+    Atom(Fourcc),
+    Freeform {
+        mean: &'a str,
+        name: &'a str,
+    },
+    /// A `TagKey::Native` that is no atom name
+    None,
+}
+
+/// `custom_mean` is the mean of the `Custom` freeform atoms.
+fn mp4_field<'a>(key: &'a TagKey, custom_mean: &'a str) -> Mp4Field<'a> {
+    let itunes = |name| Mp4Field::Freeform {
+        mean: ITUNES_MEAN,
+        name,
+    };
+    Mp4Field::Atom(match key {
+        TagKey::Title => mp4ameta::ident::TITLE,
+        TagKey::Artist => mp4ameta::ident::ARTIST,
+        TagKey::Album => mp4ameta::ident::ALBUM,
+        TagKey::AlbumArtist => mp4ameta::ident::ALBUM_ARTIST,
+        TagKey::Genre => mp4ameta::ident::CUSTOM_GENRE,
+        TagKey::Composer => mp4ameta::ident::COMPOSER,
+        TagKey::Comment => mp4ameta::ident::COMMENT,
+        TagKey::Date => mp4ameta::ident::YEAR,
+        TagKey::Lyrics => mp4ameta::ident::LYRICS,
+        TagKey::Text(field) => match text_ident(*field) {
+            Some(ident) => *ident,
+            None => return itunes(field.vorbis_key()),
+        },
+        TagKey::Sort(field) => *sort_ident(*field),
+        TagKey::MusicBrainz(id) => return itunes(id.description()),
+        TagKey::Custom(name) => {
+            return Mp4Field::Freeform {
+                mean: custom_mean,
+                name,
+            }
+        }
+        TagKey::Native(name) => return native_field(name),
+    })
+}
+
+/// A `----:mean:name` freeform atom, or else an atom of 4 (Latin-1) characters such as `©nam`.
+fn native_field(name: &str) -> Mp4Field<'_> {
+    if let Some((mean, name)) = name.strip_prefix("----:").and_then(|n| n.split_once(':')) {
+        return Mp4Field::Freeform { mean, name };
+    }
+    let bytes: Option<Vec<u8>> = name.chars().map(|c| u8::try_from(c).ok()).collect();
+    match bytes.and_then(|b| <[u8; 4]>::try_from(b).ok()) {
+        Some(fourcc) => Mp4Field::Atom(Fourcc(fourcc)),
+        None => Mp4Field::None,
+    }
+}
+
+/// The key of an atom: a standard key, `Custom` for the other freeform atoms with `custom_mean`
+/// (but those read by other accessors), or else `Native`.
+fn atom_key(ident: &DataIdent, custom_mean: &str) -> TagKey {
+    let standard = TagKey::standard().find(|k| match (mp4_field(k, custom_mean), ident) {
+        (Mp4Field::Atom(a), DataIdent::Fourcc(b)) => a == *b,
+        (Mp4Field::Freeform { mean, name }, DataIdent::Freeform { mean: m, name: n }) => {
+            mean == m && name.eq_ignore_ascii_case(n)
+        }
+        _ => false,
+    });
+    match (standard, ident) {
+        (Some(key), _) => key,
+        (None, DataIdent::Freeform { mean, name })
+            if mean == custom_mean
+                && !(mean == ITUNES_MEAN
+                    && ACCESSOR_FREEFORM_NAMES
+                        .iter()
+                        .chain(&ReplayGain::FIELDS)
+                        .any(|n| n.eq_ignore_ascii_case(name))) =>
+        {
+            TagKey::Custom(name.clone())
+        }
+        (None, ident) => TagKey::Native(ident.to_string()),
+    }
+}
+
+/// The name of the freeform atom holding the original date.
+const ORIGINAL_DATE: &str = "ORIGINALDATE";
+/// The name of the freeform atom holding the play count.
+const PLAY_COUNT: &str = "FMPS_PLAYCOUNT";
+/// The name of the freeform atom holding the rating, from 0.0 to 1.0 as in vorbis comments.
+const RATING: &str = "FMPS_RATING";
+/// The iTunes freeform atoms read by accessors other than `get` (besides the ReplayGain ones),
+/// which are no custom fields.
+const ACCESSOR_FREEFORM_NAMES: [&str; 3] = [ORIGINAL_DATE, PLAY_COUNT, RATING];
+
+impl Mp4Tag {
+    /// The strings of the `----:<mean>:<name>` atoms, with `name` in any case.
+    fn freeform(&self, mean: &str, name: &str) -> Vec<&str> {
+        self.inner
+            .data()
+            .filter_map(|(ident, data)| match ident {
+                DataIdent::Freeform { mean: m, name: n }
+                    if m == mean && n.eq_ignore_ascii_case(name) =>
+                {
+                    data.string()
                 }
-                if let Some(v) = inp.total_discs() {
-                    t.set_total_discs(v)
+                _ => None,
+            })
+            .collect()
+    }
+    fn set_freeform(&mut self, mean: &str, name: &str, values: &[&str]) {
+        self.remove_freeform(mean, name);
+        if !values.is_empty() {
+            self.inner.set_all_data(
+                FreeformIdent::new(mean, name),
+                values.iter().map(|&v| Data::Utf8(v.to_owned())),
+            );
+        }
+    }
+    fn remove_freeform(&mut self, mean: &str, name: &str) {
+        let idents: Vec<_> = self
+            .inner
+            .data()
+            .filter_map(|(ident, _)| match ident {
+                DataIdent::Freeform { mean: m, name: n }
+                    if m == mean && n.eq_ignore_ascii_case(name) =>
+                {
+                    Some(ident.clone())
                 }
-                t
-            },
+                _ => None,
+            })
+            .collect();
+        for ident in idents {
+            self.inner.remove_data_of(&ident);
+        }
+    }
+}
+
+/// Convert a picture to a `covr` artwork. MP4 only stores jpeg, png and bmp images, so other
+/// formats are converted to png with `Config::cover_transcoder`, if any.
+fn to_artwork(config: &Config, picture: Picture) -> crate::Result<mp4ameta::Img<Vec<u8>>> {
+    let fmt = match &picture.mime_type {
+        MimeType::Png => ImgFmt::Png,
+        MimeType::Jpeg => ImgFmt::Jpeg,
+        MimeType::Bmp => ImgFmt::Bmp,
+        mime_type => {
+            let transcode = config
+                .cover_transcoder
+                .ok_or_else(|| Error::UnsupportedPictureFormat(mime_type.clone()))?;
+            return Ok(mp4ameta::Img {
+                fmt: ImgFmt::Png,
+                data: transcode(picture)?,
+            });
         }
+    };
+    Ok(mp4ameta::Img {
+        fmt,
+        data: picture.data.to_owned(),
+    })
+}
+
+fn mime_type(fmt: &ImgFmt) -> MimeType {
+    match fmt {
+        ImgFmt::Jpeg => MimeType::Jpeg,
+        ImgFmt::Png => MimeType::Png,
+        ImgFmt::Bmp => MimeType::Bmp,
     }
 }
 
@@ -100,6 +231,10 @@ impl<'a> std::convert::TryFrom<&'a mp4ameta::Data> for Picture<'a> {
                 data,
                 mime_type: MimeType::Jpeg,
             },
+            mp4ameta::Data::Bmp(ref data) => Self {
+                data,
+                mime_type: MimeType::Bmp,
+            },
             _ => return Err(crate::Error::NotAPicture),
         })
     }
@@ -126,21 +261,6 @@ impl AudioTagEdit for Mp4Tag {
         self.inner.remove_artists();
     }
 
-    fn artists(&self) -> Option<Vec<&str>> {
-        let v = self.inner.artists().fold(Vec::new(), |mut v, a| {
-            v.push(a);
-            v
-        });
-        if !v.is_empty() {
-            Some(v)
-        } else {
-            None
-        }
-    }
-    fn add_artist(&mut self, v: &str) {
-        self.inner.add_artist(v);
-    }
-
     fn date(&self) -> Option<Timestamp> {
         if let Some(Ok(date)) = self.inner.year().map(Timestamp::from_str) {
             Some(date)
@@ -156,7 +276,9 @@ impl AudioTagEdit for Mp4Tag {
     }
 
     fn year(&self) -> Option<i32> {
-        self.inner.year().and_then(|x| str::parse(x).ok())
+        self.date()
+            .map(|date| date.year)
+            .or_else(|| self.inner.year().and_then(|x| str::parse(x).ok()))
     }
     fn set_year(&mut self, year: i32) {
         self.inner.set_year(year.to_string())
@@ -190,52 +312,48 @@ impl AudioTagEdit for Mp4Tag {
         self.inner.remove_album_artists();
     }
 
-    fn album_artists(&self) -> Option<Vec<&str>> {
-        let v = self.inner.album_artists().fold(Vec::new(), |mut v, a| {
-            v.push(a);
-            v
-        });
-        if !v.is_empty() {
-            Some(v)
-        } else {
-            None
-        }
-    }
-    fn add_album_artist(&mut self, v: &str) {
-        self.inner.add_album_artist(v);
-    }
-
-    fn album_cover(&self) -> Option<Picture> {
-        self.inner.artwork().and_then(|data| match data.fmt {
-            ImgFmt::Jpeg => Some(Picture {
-                data: data.data,
-                mime_type: MimeType::Jpeg,
-            }),
-            ImgFmt::Png => Some(Picture {
-                data: data.data,
-                mime_type: MimeType::Png,
-            }),
-            _ => None,
+    fn album_cover(&self) -> Option<Picture<'_>> {
+        self.inner.artwork().map(|img| Picture {
+            data: img.data,
+            mime_type: mime_type(&img.fmt),
         })
     }
+    /// Covers that cannot be stored (see `try_set_album_cover`) are ignored.
     fn set_album_cover(&mut self, cover: Picture) {
+        let _ = self.try_set_album_cover(cover);
+    }
+    /// Set the cover, converting it with `Config::cover_transcoder` if it is neither a jpeg, png
+    /// nor bmp image.
+    fn try_set_album_cover(&mut self, cover: Picture) -> crate::Result<()> {
+        let artwork = to_artwork(&self.config, cover)?;
         self.remove_album_cover();
-        self.inner.add_artwork(match cover.mime_type {
-            MimeType::Png => mp4ameta::Img {
-                fmt: ImgFmt::Png,
-                data: cover.data.to_owned(),
-            },
-            MimeType::Jpeg => mp4ameta::Img {
-                fmt: ImgFmt::Jpeg,
-                data: cover.data.to_owned(),
-            },
-            _ => panic!("Only png and jpeg are supported in m4a"),
-        });
+        self.inner.add_artwork(artwork);
+        Ok(())
     }
     fn remove_album_cover(&mut self) {
         self.inner.remove_artworks();
     }
 
+    fn pictures(&self) -> Vec<EmbeddedPicture<'_>> {
+        self.inner
+            .artworks()
+            .map(|img| EmbeddedPicture::new(PictureType::CoverFront, mime_type(&img.fmt), img.data))
+            .collect()
+    }
+    /// Append the picture to the artworks, regardless of its type. Pictures that cannot be
+    /// stored (see `try_set_album_cover`) are ignored.
+    fn add_picture(&mut self, picture: EmbeddedPicture) {
+        if let Ok(artwork) = to_artwork(&self.config, picture.as_picture()) {
+            self.inner.add_artwork(artwork);
+        }
+    }
+    /// Every artwork is a front cover, so this removes them all for `PictureType::CoverFront`.
+    fn remove_pictures_by_type(&mut self, picture_type: PictureType) {
+        if picture_type == PictureType::CoverFront {
+            self.remove_album_cover();
+        }
+    }
+
     fn remove_track(&mut self) {
         self.inner.remove_track(); // faster than removing separately
     }
@@ -303,6 +421,14 @@ impl AudioTagEdit for Mp4Tag {
     fn remove_genre(&mut self) {
         self.inner.remove_genres();
     }
+    /// The standard (`gnre`) genres, then the custom (`©gen`) ones; `set_genres` only writes
+    /// custom genres.
+    fn genres(&self) -> Option<Vec<&str>> {
+        Some(self.inner.genres().collect::<Vec<_>>()).filter(|v| !v.is_empty())
+    }
+    fn set_genres(&mut self, genres: &[&str]) {
+        self.inner.set_genres(genres.iter().map(|&g| g.to_owned()));
+    }
 
     fn comment(&self) -> Option<&str> {
         self.inner.comment()
@@ -313,12 +439,226 @@ impl AudioTagEdit for Mp4Tag {
     fn remove_comment(&mut self) {
         self.inner.remove_comments();
     }
+
+    /// `©lyr`, which also holds synchronised lyrics as LRC text
+    fn lyrics(&self) -> Option<Lyrics<'_>> {
+        self.inner.lyrics().map(Lyrics::new)
+    }
+    fn set_lyrics(&mut self, lyrics: Lyrics) {
+        self.inner.set_lyrics(lyrics.text);
+    }
+    fn remove_lyrics(&mut self) {
+        self.inner.remove_lyrics();
+    }
+
+    /// The `----:com.apple.iTunes:replaygain_*` atoms
+    fn replay_gain(&self) -> Option<ReplayGain> {
+        ReplayGain::from_fields(|field| self.freeform(ITUNES_MEAN, field).first().copied())
+    }
+    fn set_replay_gain(&mut self, replay_gain: ReplayGain) {
+        for (field, value) in replay_gain.to_fields() {
+            match value {
+                Some(value) => {
+                    self.set_freeform(ITUNES_MEAN, &field.to_ascii_lowercase(), &[&value])
+                }
+                None => self.remove_freeform(ITUNES_MEAN, field),
+            }
+        }
+    }
+    fn remove_replay_gain(&mut self) {
+        for field in ReplayGain::FIELDS {
+            self.remove_freeform(ITUNES_MEAN, field);
+        }
+    }
+
+    fn sort_order(&self, field: SortField) -> Option<&str> {
+        self.inner.strings_of(sort_ident(field)).next()
+    }
+    fn set_sort_order(&mut self, field: SortField, v: &str) {
+        self.inner
+            .set_data(*sort_ident(field), Data::Utf8(v.to_owned()));
+    }
+    fn remove_sort_order(&mut self, field: SortField) {
+        self.inner.remove_data_of(sort_ident(field));
+    }
+
+    fn text_field(&self, field: TextField) -> Option<&str> {
+        match text_ident(field) {
+            Some(ident) => self.inner.strings_of(ident).next(),
+            None => self
+                .freeform(ITUNES_MEAN, field.vorbis_key())
+                .first()
+                .copied(),
+        }
+    }
+    fn set_text_field(&mut self, field: TextField, v: &str) {
+        match text_ident(field) {
+            Some(ident) => self.inner.set_data(*ident, Data::Utf8(v.to_owned())),
+            None => self.set_freeform(ITUNES_MEAN, field.vorbis_key(), &[v]),
+        }
+    }
+    fn remove_text_field(&mut self, field: TextField) {
+        match text_ident(field) {
+            Some(ident) => self.inner.remove_data_of(ident),
+            None => self.remove_freeform(ITUNES_MEAN, field.vorbis_key()),
+        }
+    }
+
+    fn bpm(&self) -> Option<u16> {
+        self.inner.bpm()
+    }
+    fn set_bpm(&mut self, bpm: u16) {
+        self.inner.set_bpm(bpm);
+    }
+    fn remove_bpm(&mut self) {
+        self.inner.remove_bpm();
+    }
+
+    fn compilation(&self) -> bool {
+        self.inner.compilation()
+    }
+    fn set_compilation(&mut self, compilation: bool) {
+        if compilation {
+            self.inner.set_compilation();
+        } else {
+            self.inner.remove_compilation();
+        }
+    }
+
+    fn original_date(&self) -> Option<Timestamp> {
+        self.freeform(ITUNES_MEAN, ORIGINAL_DATE)
+            .first()
+            .and_then(|v| Timestamp::from_str(v).ok())
+    }
+    fn set_original_date(&mut self, date: Timestamp) {
+        self.set_freeform(ITUNES_MEAN, ORIGINAL_DATE, &[&date.to_string()]);
+    }
+    fn remove_original_date(&mut self) {
+        self.remove_freeform(ITUNES_MEAN, ORIGINAL_DATE);
+    }
+
+    /// The `FMPS_RATING` freeform atom, or else the `rtng` atom as a percentage. The values of
+    /// `rtng` used for the iTunes content advisory (0, 1, 2 and 4) are no rating.
+    fn rating(&self) -> Option<f64> {
+        self.freeform(ITUNES_MEAN, RATING)
+            .first()
+            .and_then(|v| parse_rating(v, 1.0))
+            .or_else(|| {
+                let rating = *self
+                    .inner
+                    .bytes_of(&mp4ameta::ident::ADVISORY_RATING)
+                    .next()?
+                    .first()?;
+                (!matches!(rating, 0 | 1 | 2 | 4) && rating <= 100)
+                    .then(|| f64::from(rating) / 100.0)
+            })
+    }
+    /// Set the `FMPS_RATING` freeform atom; `rtng` is left to the content advisory.
+    fn set_rating(&mut self, rating: f64) {
+        let rating = rating.clamp(0.0, 1.0);
+        self.set_freeform(ITUNES_MEAN, RATING, &[&rating.to_string()]);
+    }
+    fn remove_rating(&mut self) {
+        self.remove_freeform(ITUNES_MEAN, RATING);
+    }
+
+    fn play_count(&self) -> Option<u64> {
+        self.freeform(ITUNES_MEAN, PLAY_COUNT)
+            .first()
+            .copied()
+            .and_then(parse_count)
+    }
+    fn set_play_count(&mut self, count: u64) {
+        self.set_freeform(ITUNES_MEAN, PLAY_COUNT, &[&count.to_string()]);
+    }
+    fn remove_play_count(&mut self) {
+        self.remove_freeform(ITUNES_MEAN, PLAY_COUNT);
+    }
+
+    fn get(&self, key: &TagKey) -> Vec<&str> {
+        match mp4_field(key, self.config.mp4_freeform_mean) {
+            Mp4Field::Atom(ident) => self
+                .inner
+                .data()
+                .filter(|(i, _)| matches!(i, DataIdent::Fourcc(f) if *f == ident))
+                .filter_map(|(_, data)| data.string())
+                .collect(),
+            Mp4Field::Freeform { mean, name } => self.freeform(mean, name),
+            Mp4Field::None => Vec::new(),
+        }
+    }
+    fn set(&mut self, key: &TagKey, values: &[&str]) {
+        match mp4_field(key, self.config.mp4_freeform_mean) {
+            Mp4Field::Atom(ident) if values.is_empty() => self.inner.remove_data_of(&ident),
+            Mp4Field::Atom(ident) => self
+                .inner
+                .set_all_data(ident, values.iter().map(|&v| Data::Utf8(v.to_owned()))),
+            Mp4Field::Freeform { mean, name } => self.set_freeform(mean, name, values),
+            Mp4Field::None => {}
+        }
+    }
+
+    /// `trkn` and `disk` are listed as `n/total` text, the artworks as pictures.
+    fn items(&self) -> Vec<(TagKey, Value<'_>)> {
+        let mut items = Vec::new();
+        for (ident, data) in self.inner.data() {
+            let is_pair = |i: &DataIdent| {
+                matches!(i, DataIdent::Fourcc(f)
+                    if *f == mp4ameta::ident::TRACK_NUMBER || *f == mp4ameta::ident::DISC_NUMBER)
+            };
+            let value = match data {
+                Data::Utf8(v) | Data::Utf16(v) => Value::Text(v.as_str().into()),
+                Data::Jpeg(_) | Data::Png(_) | Data::Bmp(_) => match Picture::try_from(data) {
+                    Ok(p) => Value::Picture(p.into()),
+                    Err(_) => continue,
+                },
+                Data::Reserved(v) if is_pair(ident) && v.len() >= 6 => {
+                    let number = u16::from_be_bytes([v[2], v[3]]);
+                    match u16::from_be_bytes([v[4], v[5]]) {
+                        0 => Value::Text(number.to_string().into()),
+                        total => Value::Text(format!("{}/{}", number, total).into()),
+                    }
+                }
+                Data::BeSigned(v) if matches!(v.len(), 1 | 2 | 4 | 8) => {
+                    let extend = if v[0] & 0x80 != 0 { 0xff } else { 0 };
+                    let mut bytes = [extend; 8];
+                    bytes[8 - v.len()..].copy_from_slice(v);
+                    Value::Number(i64::from_be_bytes(bytes))
+                }
+                Data::Reserved(v) | Data::BeSigned(v) => Value::Binary(v),
+            };
+            items.push((atom_key(ident, self.config.mp4_freeform_mean), value));
+        }
+        items
+    }
+
+    fn custom_fields(&self) -> Vec<(&str, &str)> {
+        let mean = self.config.mp4_freeform_mean;
+        self.inner
+            .data()
+            .filter_map(|(ident, data)| match ident {
+                DataIdent::Freeform { name, .. }
+                    if matches!(atom_key(ident, mean), TagKey::Custom(_)) =>
+                {
+                    Some((name.as_str(), data.string()?))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The `----:com.apple.iTunes:MusicBrainz *` atoms
+    fn musicbrainz_ids(&self, id: MusicBrainzId) -> Vec<&str> {
+        self.freeform(ITUNES_MEAN, id.description())
+    }
+    fn set_musicbrainz_ids(&mut self, id: MusicBrainzId, values: &[&str]) {
+        self.set_freeform(ITUNES_MEAN, id.description(), values);
+    }
 }
 
 impl AudioTagWrite for Mp4Tag {
-    fn write_to(&mut self, file: &mut File) -> crate::Result<()> {
-        self.inner.write_to(file)?;
-        Ok(())
+    fn write_to(&mut self, file: &mut dyn Storage) -> crate::Result<()> {
+        write_via_file(file, |f| Ok(self.inner.write_to(f)?))
     }
     fn write_to_path(&mut self, path: &str) -> crate::Result<()> {
         self.inner.write_to_path(path)?;
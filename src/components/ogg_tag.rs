@@ -0,0 +1,108 @@
+use super::vorbis_comment::CommentHeader;
+use crate::*;
+use id3::Timestamp;
+use metaflac::block::{Picture as FlacPicture, PictureType as FlacPictureType, VorbisComment};
+use std::io::{Read, Seek};
+use std::str::FromStr;
+
+const IDENTIFICATION_HEADER: &[u8; 7] = b"\x01vorbis";
+const COMMENT_HEADER: &[u8; 7] = b"\x03vorbis";
+
+/// The comment header of an Ogg Vorbis stream, along with the stream properties needed for
+/// `duration`. Its API mirrors the vorbis comment and picture methods of `metaflac::Tag`.
+///
+/// Pictures are stored in the comments as base64-encoded `METADATA_BLOCK_PICTURE`s.
+#[derive(Clone, Debug, Default)]
+pub struct OggInnerTag {
+    header: CommentHeader,
+    sample_rate: u32,
+    total_samples: u64,
+}
+
+impl OggInnerTag {
+    pub fn read_from_path(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::read_from(&mut File::open(path)?)
+    }
+    pub fn read_from<R: Read + Seek + ?Sized>(reader: &mut R) -> crate::Result<Self> {
+        let headers = crate::ogg::read_headers(reader, 2)?;
+        let id = &headers.packets[0];
+        if !id.starts_with(IDENTIFICATION_HEADER) || id.len() < 16 {
+            return Err(Error::OggError("not a vorbis stream".to_owned()));
+        }
+        let comment = headers.packets[1]
+            .strip_prefix(COMMENT_HEADER)
+            .ok_or_else(|| Error::OggError("missing vorbis comment header".to_owned()))?;
+        Ok(Self {
+            header: CommentHeader::from_bytes(comment)?,
+            sample_rate: u32::from_le_bytes(id[12..16].try_into().unwrap()),
+            total_samples: headers.last_granule_position,
+        })
+    }
+    /// Replace the comment header of the Ogg Vorbis stream in `file`.
+    pub fn write_to(&self, file: &mut dyn Storage) -> crate::Result<()> {
+        let mut packets = crate::ogg::read_headers(file, 3)?.packets;
+        let mut comment = COMMENT_HEADER.to_vec();
+        comment.extend(self.header.to_bytes());
+        comment.push(1); // framing bit
+        packets[1] = comment;
+        crate::ogg::write_headers(file, &packets)
+    }
+    pub fn write_to_path(&self, path: impl AsRef<Path>) -> crate::Result<()> {
+        let mut file = File::options().read(true).write(true).open(path)?;
+        self.write_to(&mut file)
+    }
+
+    pub fn vorbis_comments(&self) -> Option<&VorbisComment> {
+        Some(&self.header.comments)
+    }
+    pub fn vorbis_comments_mut(&mut self) -> &mut VorbisComment {
+        &mut self.header.comments
+    }
+
+    pub fn pictures(&self) -> impl Iterator<Item = &FlacPicture> {
+        self.header.pictures.iter()
+    }
+    pub fn add_picture<T: Into<String>>(
+        &mut self,
+        mime_type: T,
+        picture_type: FlacPictureType,
+        data: Vec<u8>,
+    ) {
+        self.header
+            .add_picture(mime_type.into(), picture_type, data);
+    }
+    pub fn remove_picture_type(&mut self, picture_type: FlacPictureType) {
+        self.header.remove_picture_type(picture_type);
+    }
+    pub fn push_block(&mut self, block: metaflac::Block) {
+        self.header.push_block(block);
+    }
+
+    /// Duration in seconds, computed from the granule position of the last page.
+    pub fn duration(&self) -> Option<f64> {
+        if self.sample_rate == 0 {
+            None
+        } else {
+            Some(self.total_samples as f64 / f64::from(self.sample_rate))
+        }
+    }
+}
+
+impl_tag!(OggTag, OggInnerTag, TagType::Vorbis);
+
+impl_vorbis_comment_tag!(OggTag, {
+    fn duration(&self) -> Option<f64> {
+        self.inner.duration()
+    }
+
+    impl_vorbis_replay_gain!();
+});
+
+impl AudioTagWrite for OggTag {
+    fn write_to(&mut self, file: &mut dyn Storage) -> crate::Result<()> {
+        self.inner.write_to(file)
+    }
+    fn write_to_path(&mut self, path: &str) -> crate::Result<()> {
+        self.inner.write_to_path(path)
+    }
+}
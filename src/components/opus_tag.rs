@@ -0,0 +1,209 @@
+use super::vorbis_comment::CommentHeader;
+use crate::*;
+use id3::Timestamp;
+use metaflac::block::{Picture as FlacPicture, PictureType as FlacPictureType, VorbisComment};
+use std::io::{Read, Seek};
+use std::str::FromStr;
+
+const IDENTIFICATION_HEADER: &[u8; 8] = b"OpusHead";
+const COMMENT_HEADER: &[u8; 8] = b"OpusTags";
+
+/// The `OpusTags` header of an Ogg Opus stream, along with the output gain and the stream
+/// properties needed for `duration` from the `OpusHead` header. Its API mirrors the vorbis comment
+/// and picture methods of `metaflac::Tag`.
+///
+/// ## References
+///
+/// - <https://www.rfc-editor.org/rfc/rfc7845#section-5>
+#[derive(Clone, Debug, Default)]
+pub struct OpusInnerTag {
+    header: CommentHeader,
+    /// `None` leaves the output gain of the file written to untouched.
+    output_gain: Option<i16>,
+    /// `None` unless the tag was read from a file.
+    stream: Option<StreamInfo>,
+}
+
+/// The properties of the stream the tag was read from.
+#[derive(Clone, Copy, Debug)]
+struct StreamInfo {
+    pre_skip: u16,
+    granule_position: u64,
+}
+
+impl OpusInnerTag {
+    pub fn read_from_path(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::read_from(&mut File::open(path)?)
+    }
+    pub fn read_from<R: Read + Seek + ?Sized>(reader: &mut R) -> crate::Result<Self> {
+        let headers = crate::ogg::read_headers(reader, 2)?;
+        let head = &headers.packets[0];
+        if !head.starts_with(IDENTIFICATION_HEADER) || head.len() < 19 {
+            return Err(Error::OggError("not an opus stream".to_owned()));
+        }
+        let comment = headers.packets[1]
+            .strip_prefix(COMMENT_HEADER)
+            .ok_or_else(|| Error::OggError("missing OpusTags header".to_owned()))?;
+        Ok(Self {
+            header: CommentHeader::from_bytes(comment)?,
+            output_gain: Some(i16::from_le_bytes(head[16..18].try_into().unwrap())),
+            stream: Some(StreamInfo {
+                pre_skip: u16::from_le_bytes(head[10..12].try_into().unwrap()),
+                granule_position: headers.last_granule_position,
+            }),
+        })
+    }
+    /// Replace the `OpusTags` header (and the output gain in `OpusHead`) of the Ogg Opus stream in
+    /// `file`.
+    pub fn write_to(&self, file: &mut dyn Storage) -> crate::Result<()> {
+        let mut packets = crate::ogg::read_headers(file, 2)?.packets;
+        if !packets[0].starts_with(IDENTIFICATION_HEADER) || packets[0].len() < 19 {
+            return Err(Error::OggError("not an opus stream".to_owned()));
+        }
+        if let Some(gain) = self.output_gain {
+            packets[0][16..18].copy_from_slice(&gain.to_le_bytes());
+        }
+        let mut comment = COMMENT_HEADER.to_vec();
+        comment.extend(self.header.to_bytes());
+        packets[1] = comment;
+        crate::ogg::write_headers(file, &packets)
+    }
+    pub fn write_to_path(&self, path: impl AsRef<Path>) -> crate::Result<()> {
+        let mut file = File::options().read(true).write(true).open(path)?;
+        self.write_to(&mut file)
+    }
+
+    pub fn vorbis_comments(&self) -> Option<&VorbisComment> {
+        Some(&self.header.comments)
+    }
+    pub fn vorbis_comments_mut(&mut self) -> &mut VorbisComment {
+        &mut self.header.comments
+    }
+
+    pub fn pictures(&self) -> impl Iterator<Item = &FlacPicture> {
+        self.header.pictures.iter()
+    }
+    pub fn add_picture<T: Into<String>>(
+        &mut self,
+        mime_type: T,
+        picture_type: FlacPictureType,
+        data: Vec<u8>,
+    ) {
+        self.header
+            .add_picture(mime_type.into(), picture_type, data);
+    }
+    pub fn remove_picture_type(&mut self, picture_type: FlacPictureType) {
+        self.header.remove_picture_type(picture_type);
+    }
+    pub fn push_block(&mut self, block: metaflac::Block) {
+        self.header.push_block(block);
+    }
+
+    /// The output gain in Q7.8 dB, as stored in `OpusHead`.
+    pub fn output_gain(&self) -> Option<i16> {
+        self.output_gain
+    }
+    pub fn set_output_gain(&mut self, gain: i16) {
+        self.output_gain = Some(gain);
+    }
+
+    /// Duration in seconds. Opus always runs at 48 kHz, minus the pre-skip at the start.
+    pub fn duration(&self) -> Option<f64> {
+        self.stream
+            .map(|s| s.granule_position.saturating_sub(u64::from(s.pre_skip)) as f64 / 48000.0)
+    }
+}
+
+impl_tag!(OpusTag, OpusInnerTag, TagType::Opus);
+
+/// The ReplayGain reference level (-18 LUFS) is 5 dB louder than the R128 one.
+const R128_TO_REPLAY_GAIN: f64 = 5.0;
+
+impl_vorbis_comment_tag!(OpusTag, {
+    fn duration(&self) -> Option<f64> {
+        self.inner.duration()
+    }
+
+    /// Opus files must not use `REPLAYGAIN_*` comments: the gains are converted from and to the
+    /// R128 gains, and peaks are not stored.
+    fn replay_gain(&self) -> Option<ReplayGain> {
+        let replay_gain = ReplayGain {
+            track_gain: self.r128_track_gain().map(|g| g + R128_TO_REPLAY_GAIN),
+            album_gain: self.r128_album_gain().map(|g| g + R128_TO_REPLAY_GAIN),
+            ..ReplayGain::default()
+        };
+        Some(replay_gain).filter(|g| !g.is_empty())
+    }
+    fn set_replay_gain(&mut self, replay_gain: ReplayGain) {
+        self.remove_replay_gain();
+        if let Some(gain) = replay_gain.track_gain {
+            self.set_r128_track_gain(gain - R128_TO_REPLAY_GAIN);
+        }
+        if let Some(gain) = replay_gain.album_gain {
+            self.set_r128_album_gain(gain - R128_TO_REPLAY_GAIN);
+        }
+    }
+    fn remove_replay_gain(&mut self) {
+        self.remove_r128_track_gain();
+        self.remove_r128_album_gain();
+    }
+});
+
+/// Opus-specific gains. All values are in dB; they are stored as Q7.8 fixed point numbers, so they
+/// are rounded to the nearest 1/256 dB.
+impl OpusTag {
+    /// The gain decoders apply to every sample, from the `OpusHead` header.
+    pub fn output_gain(&self) -> Option<f64> {
+        self.inner.output_gain().map(from_q78)
+    }
+    pub fn set_output_gain(&mut self, gain: f64) {
+        self.inner.set_output_gain(to_q78(gain));
+    }
+
+    /// `R128_TRACK_GAIN`: the gain to apply on top of the output gain to normalize this track to
+    /// -23 LUFS.
+    pub fn r128_track_gain(&self) -> Option<f64> {
+        self.get_first("R128_TRACK_GAIN")
+            .and_then(|v| v.trim().parse::<i16>().ok())
+            .map(from_q78)
+    }
+    pub fn set_r128_track_gain(&mut self, gain: f64) {
+        self.set_first("R128_TRACK_GAIN", &to_q78(gain).to_string());
+    }
+    pub fn remove_r128_track_gain(&mut self) {
+        self.remove("R128_TRACK_GAIN");
+    }
+
+    /// `R128_ALBUM_GAIN`: the gain to apply on top of the output gain to normalize this album to
+    /// -23 LUFS.
+    pub fn r128_album_gain(&self) -> Option<f64> {
+        self.get_first("R128_ALBUM_GAIN")
+            .and_then(|v| v.trim().parse::<i16>().ok())
+            .map(from_q78)
+    }
+    pub fn set_r128_album_gain(&mut self, gain: f64) {
+        self.set_first("R128_ALBUM_GAIN", &to_q78(gain).to_string());
+    }
+    pub fn remove_r128_album_gain(&mut self) {
+        self.remove("R128_ALBUM_GAIN");
+    }
+}
+
+fn from_q78(v: i16) -> f64 {
+    f64::from(v) / 256.0
+}
+
+fn to_q78(db: f64) -> i16 {
+    (db * 256.0)
+        .round()
+        .clamp(f64::from(i16::MIN), f64::from(i16::MAX)) as i16
+}
+
+impl AudioTagWrite for OpusTag {
+    fn write_to(&mut self, file: &mut dyn Storage) -> crate::Result<()> {
+        self.inner.write_to(file)
+    }
+    fn write_to_path(&mut self, path: &str) -> crate::Result<()> {
+        self.inner.write_to_path(path)
+    }
+}
@@ -0,0 +1,608 @@
+//! Vorbis comments are shared by FLAC, Ogg Vorbis and Opus, so the field mapping (`TITLE`,
+//! `ALBUMARTIST`, `TRACKNUMBER`...) is implemented once here for every tag type built on them.
+//!
+//! ## References
+//!
+//! - <https://www.xiph.org/vorbis/doc/v-comment.html>
+
+use crate::*;
+use base64::Engine;
+use metaflac::block::{Picture as FlacPicture, PictureType as FlacPictureType, VorbisComment};
+
+/// The comment used to embed a base64-encoded FLAC picture block in Ogg streams.
+pub(crate) const PICTURE_KEY: &str = "METADATA_BLOCK_PICTURE";
+
+/// The content of a comment header in an Ogg stream, with the embedded pictures decoded.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CommentHeader {
+    pub comments: VorbisComment,
+    pub pictures: Vec<FlacPicture>,
+}
+
+impl CommentHeader {
+    /// Parse a comment header (without any codec-specific prefix or framing bit), moving
+    /// embedded `METADATA_BLOCK_PICTURE`s out of the comments.
+    pub fn from_bytes(bytes: &[u8]) -> crate::Result<Self> {
+        let malformed = || Error::OggError("malformed comment header".to_owned());
+        let mut reader = LengthPrefixed(bytes);
+        let mut comments = VorbisComment::new();
+        comments.vendor_string =
+            String::from_utf8_lossy(reader.next().ok_or_else(malformed)?).into();
+        let count = reader.u32().ok_or_else(malformed)?;
+        let mut pictures = Vec::new();
+        for _ in 0..count {
+            let comment = String::from_utf8_lossy(reader.next().ok_or_else(malformed)?);
+            let Some((key, value)) = comment.split_once('=') else {
+                continue;
+            };
+            let key = key.to_ascii_uppercase();
+            if key == PICTURE_KEY {
+                if let Some(picture) = base64::engine::general_purpose::STANDARD
+                    .decode(value)
+                    .ok()
+                    .and_then(|data| picture_from_bytes(&data))
+                {
+                    pictures.push(picture);
+                }
+                continue;
+            }
+            comments
+                .comments
+                .entry(key)
+                .or_default()
+                .push(value.to_owned());
+        }
+        Ok(Self { comments, pictures })
+    }
+
+    /// Serialize the comment header (without any codec-specific prefix or framing bit),
+    /// embedding the pictures as `METADATA_BLOCK_PICTURE`s.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut comments = self.comments.clone();
+        if !self.pictures.is_empty() {
+            comments.set(
+                PICTURE_KEY,
+                self.pictures
+                    .iter()
+                    .map(|p| base64::engine::general_purpose::STANDARD.encode(p.to_bytes()))
+                    .collect(),
+            );
+        }
+        comments.to_bytes()
+    }
+
+    pub fn add_picture(&mut self, mime_type: String, picture_type: FlacPictureType, data: Vec<u8>) {
+        self.remove_picture_type(picture_type);
+        self.pictures.push(FlacPicture {
+            picture_type,
+            mime_type,
+            data,
+            ..FlacPicture::default()
+        });
+    }
+    pub fn remove_picture_type(&mut self, picture_type: FlacPictureType) {
+        self.pictures.retain(|p| p.picture_type != picture_type);
+    }
+    /// Like `metaflac::Tag::push_block`, but only pictures and vorbis comments can be stored in
+    /// a comment header: other blocks are ignored.
+    pub fn push_block(&mut self, block: metaflac::Block) {
+        match block {
+            metaflac::Block::Picture(picture) => self.pictures.push(picture),
+            metaflac::Block::VorbisComment(comments) => self.comments = comments,
+            _ => {}
+        }
+    }
+}
+
+impl<'a> std::convert::TryFrom<&'a FlacPicture> for EmbeddedPicture<'a> {
+    type Error = crate::Error;
+    fn try_from(inp: &'a FlacPicture) -> crate::Result<Self> {
+        let mime_type = MimeType::guess(&inp.mime_type, &inp.data)
+            .ok_or_else(|| Error::UnsupportedMimeType(inp.mime_type.to_owned()))?;
+        Ok(
+            Self::new(inp.picture_type.into(), mime_type, inp.data.as_slice())
+                .with_description(inp.description.as_str())
+                .with_dimensions(inp.width, inp.height, inp.depth),
+        )
+    }
+}
+
+/// Unknown dimensions are read from the image header, if possible.
+impl From<EmbeddedPicture<'_>> for FlacPicture {
+    fn from(inp: EmbeddedPicture<'_>) -> Self {
+        let (width, height) = match (inp.width, inp.height) {
+            (0, 0) => inp.dimensions().unwrap_or_default(),
+            dimensions => dimensions,
+        };
+        Self {
+            picture_type: inp.picture_type.into(),
+            mime_type: String::from(inp.mime_type),
+            description: inp.description.into_owned(),
+            width,
+            height,
+            depth: inp.depth,
+            num_colors: 0,
+            data: inp.data.into_owned(),
+        }
+    }
+}
+
+/// `metaflac` panics on truncated picture blocks, so check every length before handing it over.
+fn picture_from_bytes(bytes: &[u8]) -> Option<FlacPicture> {
+    let mut i = 4; // picture type
+    for skip in [0, 0, 16] {
+        // mime type, description, then width, height, depth and colors before the data
+        i += skip;
+        let len = u32::from_be_bytes(bytes.get(i..i + 4)?.try_into().ok()?) as usize;
+        i += 4 + len;
+    }
+    bytes.get(..i)?;
+    FlacPicture::from_bytes(bytes).ok()
+}
+
+/// A cursor over a sequence of length-prefixed fields.
+struct LengthPrefixed<'a>(&'a [u8]);
+
+impl<'a> LengthPrefixed<'a> {
+    fn u32(&mut self) -> Option<u32> {
+        let (n, rest) = self.0.split_first_chunk::<4>()?;
+        self.0 = rest;
+        Some(u32::from_le_bytes(*n))
+    }
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()? as usize;
+        let field = self.0.get(..len)?;
+        self.0 = &self.0[len..];
+        Some(field)
+    }
+}
+
+/// Implement the ReplayGain methods of `AudioTagEdit` with the `REPLAYGAIN_*` comments, for
+/// `impl_vorbis_comment_tag!`. Opus uses the `R128_*` comments instead.
+macro_rules! impl_vorbis_replay_gain {
+    () => {
+        fn replay_gain(&self) -> Option<ReplayGain> {
+            ReplayGain::from_fields(|field| self.get_first(field))
+        }
+        fn set_replay_gain(&mut self, replay_gain: ReplayGain) {
+            for (field, value) in replay_gain.to_fields() {
+                match value {
+                    Some(value) => self.set_first(field, &value),
+                    None => self.remove(field),
+                }
+            }
+        }
+        fn remove_replay_gain(&mut self) {
+            for field in ReplayGain::FIELDS {
+                self.remove(field);
+            }
+        }
+    };
+}
+
+/// Implement `AudioTagEdit` and the `AnyTag` conversions for a tag whose inner type exposes the
+/// vorbis comment and picture API of `metaflac::Tag`. Format-specific methods (i.e. `duration`)
+/// are passed in the braces.
+macro_rules! impl_vorbis_comment_tag {
+    ($tag:ident, { $($extra:tt)* }) => {
+        impl<'a> From<AnyTag<'a>> for $tag {
+            fn from(inp: AnyTag<'a>) -> Self {
+                let mut t = $tag {
+                    config: inp.config,
+                    ..$tag::default()
+                };
+                // `YEAR` is a field of its own, kept along with `DATE`
+                let year = inp.date.and(inp.year);
+                crate::anytag::apply_anytag(&mut t, inp);
+                if let Some(v) = year {
+                    t.set_year(v)
+                }
+                t
+            }
+        }
+
+        impl<'a> From<&'a $tag> for AnyTag<'a> {
+            fn from(inp: &'a $tag) -> Self {
+                crate::anytag::anytag_from(inp)
+            }
+        }
+
+        impl $tag {
+            pub fn get_first(&self, key: &str) -> Option<&str> {
+                if let Some(Some(v)) = self.inner.vorbis_comments().map(|c| c.get(key)) {
+                    if !v.is_empty() {
+                        Some(v[0].as_str())
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                }
+            }
+            pub fn set_first(&mut self, key: &str, val: &str) {
+                self.inner.vorbis_comments_mut().set(key, vec![val]);
+            }
+            pub fn remove(&mut self, k: &str) {
+                self.inner.vorbis_comments_mut().comments.remove(k);
+            }
+        }
+
+        impl AudioTagEdit for $tag {
+            fn title(&self) -> Option<&str> {
+                self.get_first("TITLE")
+            }
+            fn set_title(&mut self, title: &str) {
+                self.set_first("TITLE", title);
+            }
+            fn remove_title(&mut self) {
+                self.remove("TITLE");
+            }
+
+            fn artist(&self) -> Option<&str> {
+                self.get_first("ARTIST")
+            }
+            fn set_artist(&mut self, artist: &str) {
+                self.set_first("ARTIST", artist)
+            }
+            fn remove_artist(&mut self) {
+                self.remove("ARTIST");
+            }
+
+            fn date(&self) -> Option<Timestamp> {
+                if let Some(Ok(timestamp)) = self.get_first("DATE").map(Timestamp::from_str) {
+                    Some(timestamp)
+                } else {
+                    None
+                }
+            }
+            fn set_date(&mut self, date: Timestamp) {
+                self.set_first("DATE", &date.to_string());
+            }
+            fn remove_date(&mut self) {
+                self.remove("DATE");
+            }
+
+            fn year(&self) -> Option<i32> {
+                if let Some(Ok(y)) = self.get_first("YEAR").map(|s| s.parse::<i32>()) {
+                    Some(y)
+                } else if let Some(Ok(y)) = self
+                    .get_first("DATE")
+                    .map(|s| s.chars().take(4).collect::<String>().parse::<i32>())
+                {
+                    Some(y)
+                } else {
+                    None
+                }
+            }
+            fn set_year(&mut self, year: i32) {
+                self.set_first("YEAR", &year.to_string());
+            }
+            fn remove_year(&mut self) {
+                self.remove("YEAR");
+                self.remove("DATE");
+            }
+
+            fn album_title(&self) -> Option<&str> {
+                self.get_first("ALBUM")
+            }
+            fn set_album_title(&mut self, title: &str) {
+                self.set_first("ALBUM", title)
+            }
+            fn remove_album_title(&mut self) {
+                self.remove("ALBUM");
+            }
+
+            fn album_artist(&self) -> Option<&str> {
+                self.get_first("ALBUMARTIST")
+            }
+            fn set_album_artist(&mut self, v: &str) {
+                self.set_first("ALBUMARTIST", v)
+            }
+            fn remove_album_artist(&mut self) {
+                self.remove("ALBUMARTIST");
+            }
+
+            fn album_cover(&self) -> Option<Picture<'_>> {
+                self.inner
+                    .pictures()
+                    .find(|&pic| matches!(pic.picture_type, metaflac::block::PictureType::CoverFront))
+                    .and_then(|pic| {
+                        Some(Picture {
+                            data: &pic.data,
+                            mime_type: MimeType::guess(&pic.mime_type, &pic.data)?,
+                        })
+                    })
+            }
+            fn set_album_cover(&mut self, cover: Picture) {
+                self.remove_album_cover();
+                let mime = String::from(cover.mime_type);
+                let picture_type = metaflac::block::PictureType::CoverFront;
+                self.inner
+                    .add_picture(mime, picture_type, (cover.data).to_owned());
+            }
+            fn remove_album_cover(&mut self) {
+                self.inner
+                    .remove_picture_type(metaflac::block::PictureType::CoverFront)
+            }
+
+            fn pictures(&self) -> Vec<EmbeddedPicture<'_>> {
+                self.inner
+                    .pictures()
+                    .filter_map(|pic| pic.try_into().ok())
+                    .collect()
+            }
+            fn add_picture(&mut self, picture: EmbeddedPicture) {
+                let picture = metaflac::block::Picture::from(picture);
+                self.inner.remove_picture_type(picture.picture_type);
+                self.inner.push_block(metaflac::Block::Picture(picture));
+            }
+            fn remove_pictures_by_type(&mut self, picture_type: PictureType) {
+                self.inner.remove_picture_type(picture_type.into())
+            }
+
+            fn composer(&self) -> Option<&str> {
+                self.get_first("COMPOSER")
+            }
+            fn set_composer(&mut self, composer: String) {
+                self.set_first("COMPOSER", &composer);
+            }
+            fn remove_composer(&mut self) {
+                self.remove("COMPOSER")
+            }
+
+            fn track_number(&self) -> Option<u16> {
+                if let Some(Ok(n)) = self.get_first("TRACKNUMBER").map(|x| x.parse::<u16>()) {
+                    Some(n)
+                } else {
+                    None
+                }
+            }
+            fn set_track_number(&mut self, v: u16) {
+                self.set_first("TRACKNUMBER", &v.to_string())
+            }
+            fn remove_track_number(&mut self) {
+                self.remove("TRACKNUMBER");
+            }
+
+            // ! not standard
+            fn total_tracks(&self) -> Option<u16> {
+                if let Some(Ok(n)) = self.get_first("TOTALTRACKS").map(|x| x.parse::<u16>()) {
+                    Some(n)
+                } else {
+                    None
+                }
+            }
+            fn set_total_tracks(&mut self, v: u16) {
+                self.set_first("TOTALTRACKS", &v.to_string())
+            }
+            fn remove_total_tracks(&mut self) {
+                self.remove("TOTALTRACKS");
+            }
+
+            fn disc_number(&self) -> Option<u16> {
+                if let Some(Ok(n)) = self.get_first("DISCNUMBER").map(|x| x.parse::<u16>()) {
+                    Some(n)
+                } else {
+                    None
+                }
+            }
+            fn set_disc_number(&mut self, v: u16) {
+                self.set_first("DISCNUMBER", &v.to_string())
+            }
+            fn remove_disc_number(&mut self) {
+                self.remove("DISCNUMBER");
+            }
+
+            // ! not standard
+            fn total_discs(&self) -> Option<u16> {
+                if let Some(Ok(n)) = self.get_first("TOTALDISCS").map(|x| x.parse::<u16>()) {
+                    Some(n)
+                } else {
+                    None
+                }
+            }
+            fn set_total_discs(&mut self, v: u16) {
+                self.set_first("TOTALDISCS", &v.to_string())
+            }
+            fn remove_total_discs(&mut self) {
+                self.remove("TOTALDISCS");
+            }
+
+            fn genre(&self) -> Option<&str> {
+                self.get_first("GENRE")
+            }
+            fn set_genre(&mut self, v: &str) {
+                self.set_first("GENRE", v);
+            }
+            fn remove_genre(&mut self) {
+                self.remove("GENRE");
+            }
+
+            fn comment(&self) -> Option<&str> {
+                self.get_first("COMMENT")
+            }
+            fn set_comment(&mut self, v: String) {
+                self.set_first("COMMENT", &v);
+            }
+            fn remove_comment(&mut self) {
+                self.remove("COMMENT");
+            }
+
+            /// `LYRICS`, or else `UNSYNCEDLYRICS`
+            fn lyrics(&self) -> Option<Lyrics<'_>> {
+                self.get_first("LYRICS")
+                    .or_else(|| self.get_first("UNSYNCEDLYRICS"))
+                    .map(Lyrics::new)
+            }
+            fn set_lyrics(&mut self, lyrics: Lyrics) {
+                self.remove_lyrics();
+                self.set_first("LYRICS", &lyrics.text);
+            }
+            fn remove_lyrics(&mut self) {
+                self.remove("LYRICS");
+                self.remove("UNSYNCEDLYRICS");
+            }
+
+            /// The `SYNCEDLYRICS` comment, in the LRC format
+            fn synced_lyrics(&self) -> Option<SyncedLyrics> {
+                SyncedLyrics::from_lrc(self.get_first("SYNCEDLYRICS")?)
+            }
+            fn set_synced_lyrics(&mut self, lyrics: &SyncedLyrics) {
+                self.set_first("SYNCEDLYRICS", &lyrics.to_lrc());
+            }
+            fn remove_synced_lyrics(&mut self) {
+                self.remove("SYNCEDLYRICS");
+            }
+
+            fn sort_order(&self, field: SortField) -> Option<&str> {
+                self.get_first(field.vorbis_key())
+            }
+            fn set_sort_order(&mut self, field: SortField, v: &str) {
+                self.set_first(field.vorbis_key(), v);
+            }
+            fn remove_sort_order(&mut self, field: SortField) {
+                self.remove(field.vorbis_key());
+            }
+
+            fn text_field(&self, field: TextField) -> Option<&str> {
+                self.get_first(field.vorbis_key())
+            }
+            fn set_text_field(&mut self, field: TextField, v: &str) {
+                self.set_first(field.vorbis_key(), v);
+            }
+            fn remove_text_field(&mut self, field: TextField) {
+                self.remove(field.vorbis_key());
+            }
+
+            fn bpm(&self) -> Option<u16> {
+                self.get_first("BPM").and_then(parse_bpm)
+            }
+            fn set_bpm(&mut self, bpm: u16) {
+                self.set_first("BPM", &bpm.to_string());
+            }
+            fn remove_bpm(&mut self) {
+                self.remove("BPM");
+            }
+
+            fn compilation(&self) -> bool {
+                self.get_first("COMPILATION").is_some_and(parse_flag)
+            }
+            fn set_compilation(&mut self, compilation: bool) {
+                if compilation {
+                    self.set_first("COMPILATION", "1");
+                } else {
+                    self.remove("COMPILATION");
+                }
+            }
+
+            fn original_date(&self) -> Option<Timestamp> {
+                self.get_first("ORIGINALDATE")
+                    .and_then(|v| Timestamp::from_str(v).ok())
+            }
+            fn set_original_date(&mut self, date: Timestamp) {
+                self.set_first("ORIGINALDATE", &date.to_string());
+            }
+            fn remove_original_date(&mut self) {
+                self.remove("ORIGINALDATE");
+            }
+
+            /// `FMPS_RATING`, or else `RATING` as a percentage
+            fn rating(&self) -> Option<f64> {
+                self.get_first("FMPS_RATING")
+                    .and_then(|v| parse_rating(v, 1.0))
+                    .or_else(|| self.get_first("RATING").and_then(|v| parse_rating(v, 100.0)))
+            }
+            fn set_rating(&mut self, rating: f64) {
+                let rating = rating.clamp(0.0, 1.0);
+                self.set_first("FMPS_RATING", &rating.to_string());
+                self.set_first("RATING", &(rating * 100.0).round().to_string());
+            }
+            fn remove_rating(&mut self) {
+                self.remove("FMPS_RATING");
+                self.remove("RATING");
+            }
+
+            fn play_count(&self) -> Option<u64> {
+                self.get_first("FMPS_PLAYCOUNT").and_then(parse_count)
+            }
+            fn set_play_count(&mut self, count: u64) {
+                self.set_first("FMPS_PLAYCOUNT", &count.to_string());
+            }
+            fn remove_play_count(&mut self) {
+                self.remove("FMPS_PLAYCOUNT");
+            }
+
+            /// Names are upper-cased, as they are when the comments are read.
+            fn get(&self, key: &TagKey) -> Vec<&str> {
+                self.inner
+                    .vorbis_comments()
+                    .and_then(|c| c.get(&key.vorbis_key().to_ascii_uppercase()))
+                    .map(|v| v.iter().map(String::as_str).collect())
+                    .unwrap_or_default()
+            }
+            fn set(&mut self, key: &TagKey, values: &[&str]) {
+                let name = key.vorbis_key().to_ascii_uppercase();
+                if values.is_empty() {
+                    self.remove(&name);
+                } else {
+                    self.inner.vorbis_comments_mut().set(name, values.to_vec());
+                }
+            }
+
+            /// The comments are sorted by name, and the pictures listed as `METADATA_BLOCK_PICTURE`.
+            fn items(&self) -> Vec<(TagKey, Value<'_>)> {
+                let mut comments: Vec<_> = self
+                    .inner
+                    .vorbis_comments()
+                    .map(|c| c.comments.iter().collect())
+                    .unwrap_or_default();
+                comments.sort_by_key(|&(name, _)| name);
+                let mut items = Vec::new();
+                for (name, values) in comments {
+                    let key = TagKey::from_vorbis_key(name);
+                    items.extend(values.iter().map(|v| (key.clone(), Value::Text(v.as_str().into()))));
+                }
+                let picture_key = TagKey::Native(crate::components::vorbis_comment::PICTURE_KEY.to_owned());
+                items.extend(
+                    self.pictures()
+                        .into_iter()
+                        .map(|p| (picture_key.clone(), Value::Picture(p))),
+                );
+                items
+            }
+
+            /// Sorted by name.
+            fn custom_fields(&self) -> Vec<(&str, &str)> {
+                let mut fields: Vec<_> = self
+                    .inner
+                    .vorbis_comments()
+                    .into_iter()
+                    .flat_map(|c| &c.comments)
+                    .filter(|(name, _)| matches!(TagKey::from_vorbis_key(name), TagKey::Custom(_)))
+                    .flat_map(|(name, values)| values.iter().map(move |v| (name.as_str(), v.as_str())))
+                    .collect();
+                fields.sort_by_key(|&(name, _)| name);
+                fields
+            }
+
+            fn musicbrainz_ids(&self, id: MusicBrainzId) -> Vec<&str> {
+                self.inner
+                    .vorbis_comments()
+                    .and_then(|c| c.get(id.vorbis_key()))
+                    .map(|v| v.iter().map(String::as_str).collect())
+                    .unwrap_or_default()
+            }
+            fn set_musicbrainz_ids(&mut self, id: MusicBrainzId, values: &[&str]) {
+                if values.is_empty() {
+                    self.remove(id.vorbis_key());
+                } else {
+                    self.inner
+                        .vorbis_comments_mut()
+                        .set(id.vorbis_key(), values.to_vec());
+                }
+            }
+
+            $($extra)*
+        }
+    };
+}
@@ -0,0 +1,252 @@
+use crate::riff::{Container, Endianness};
+use crate::*;
+use id3::{self, Frame, TagLike, Timestamp};
+use std::io::{Cursor, Read, Seek};
+use std::str::FromStr;
+
+/// The `LIST`/`INFO` fields kept in sync with their ID3 counterparts.
+const INFO_FIELDS: [&[u8; 4]; 7] = [
+    b"INAM", b"IART", b"IPRD", b"ICRD", b"ITRK", b"IGNR", b"ICMT",
+];
+
+/// The metadata of a WAV file: an embedded `id3 ` chunk and the RIFF `LIST`/`INFO` chunk.
+///
+/// ID3 takes precedence: on read, `INFO` fields only fill in what the ID3 tag lacks; on write,
+/// the `INFO` fields `INAM`, `IART`, `IPRD`, `ICRD`, `ITRK`, `IGNR` and `ICMT` are regenerated from
+/// the ID3 tag so both stay in sync. Other `INFO` fields are preserved.
+#[derive(Clone, Debug, Default)]
+pub struct WavInnerTag {
+    id3: id3::Tag,
+    info: Vec<([u8; 4], String)>,
+    duration: Option<f64>,
+}
+
+impl WavInnerTag {
+    pub fn read_from_path(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::read_from(&mut File::open(path)?)
+    }
+    pub fn read_from<R: Read + Seek + ?Sized>(reader: &mut R) -> crate::Result<Self> {
+        let riff = Container::read_from(reader, b"RIFF", Endianness::Little)?;
+        if &riff.form_type != b"WAVE" {
+            return Err(Error::ChunkError("not a WAVE file".to_owned()));
+        }
+        let mut tag = Self::default();
+        for chunk in &riff.chunks {
+            match &chunk.id {
+                b"id3 " | b"ID3 " => {
+                    let data = Container::read_chunk(reader, chunk)?;
+                    tag.id3 = id3::Tag::read_from2(Cursor::new(data))?;
+                }
+                b"LIST" => {
+                    let data = Container::read_chunk(reader, chunk)?;
+                    if let Some(info) = data.strip_prefix(b"INFO") {
+                        tag.info = parse_info(info);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let (Some(fmt), Some(data)) = (riff.find(b"fmt "), riff.find(b"data")) {
+            let fmt = Container::read_chunk(reader, fmt)?;
+            if let Some(byte_rate) = fmt.get(8..12) {
+                let byte_rate = u32::from_le_bytes(byte_rate.try_into().unwrap());
+                if byte_rate > 0 {
+                    tag.duration = Some(f64::from(data.size) / f64::from(byte_rate));
+                }
+            }
+        }
+        tag.merge_info();
+        Ok(tag)
+    }
+    /// Replace the `id3 ` and `LIST`/`INFO` chunks of `file`. The ID3 tag keeps the version it was
+    /// read with.
+    pub fn write_to(&mut self, file: &mut dyn Storage) -> crate::Result<()> {
+        self.write_with_config(file, &Config::default())
+    }
+    pub fn write_to_path(&mut self, path: impl AsRef<Path>) -> crate::Result<()> {
+        let mut file = File::options().read(true).write(true).open(path)?;
+        self.write_to(&mut file)
+    }
+    /// Like `write_to`, with the ID3 tag written in `config.id3v2_version` if it is set.
+    pub(crate) fn write_with_config(
+        &mut self,
+        file: &mut dyn Storage,
+        config: &Config,
+    ) -> crate::Result<()> {
+        self.sync_info(config.sep_artist);
+        let riff = Container::read_from(file, b"RIFF", Endianness::Little)?;
+        let mut removed = Vec::new();
+        for (i, chunk) in riff.chunks.iter().enumerate() {
+            let is_info = chunk.id == *b"LIST"
+                && chunk.size >= 4
+                && Container::read_chunk(file, chunk)?.starts_with(b"INFO");
+            if is_info || chunk.id == *b"id3 " || chunk.id == *b"ID3 " {
+                removed.push(i);
+            }
+        }
+        let mut added = Vec::new();
+        if !self.info.is_empty() {
+            added.push((*b"LIST", info_to_bytes(&self.info)));
+        }
+        if self.id3.frames().next().is_some() {
+            let (id3, version) =
+                crate::components::id3_tag::to_write_version(&self.id3, config.id3v2_version);
+            let mut data = Vec::new();
+            id3.write_to(&mut data, version)?;
+            added.push((*b"id3 ", data));
+        }
+        riff.rewrite(file, &removed, &added)
+    }
+
+    pub fn id3(&self) -> &id3::Tag {
+        &self.id3
+    }
+    pub fn id3_mut(&mut self) -> &mut id3::Tag {
+        &mut self.id3
+    }
+
+    /// The `INFO` fields, e.g. `("ISFT", "Lavf58.29.100")`.
+    pub fn info(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.info
+            .iter()
+            .map(|(id, v)| (std::str::from_utf8(id).unwrap_or_default(), v.as_str()))
+    }
+    pub fn set_info(&mut self, id: [u8; 4], value: impl Into<String>) {
+        self.remove_info(id);
+        self.info.push((id, value.into()));
+    }
+    pub fn remove_info(&mut self, id: [u8; 4]) {
+        self.info.retain(|(i, _)| *i != id);
+    }
+
+    pub fn duration(&self) -> Option<f64> {
+        self.duration
+    }
+
+    /// Fill in the fields missing from the ID3 tag with those of `INFO`.
+    fn merge_info(&mut self) {
+        if let (None, Some(v)) = (self.id3.title(), get_info(&self.info, b"INAM")) {
+            self.id3.set_title(v);
+        }
+        if let (None, Some(v)) = (self.id3.artist(), get_info(&self.info, b"IART")) {
+            self.id3.set_artist(v);
+        }
+        if let (None, Some(v)) = (self.id3.album(), get_info(&self.info, b"IPRD")) {
+            self.id3.set_album(v);
+        }
+        if self.id3.date_recorded().is_none() && self.id3.year().is_none() {
+            if let Some(Ok(v)) =
+                get_info(&self.info, b"ICRD").map(|v| Timestamp::from_str(v.trim()))
+            {
+                self.id3.set_date_recorded(v);
+            }
+        }
+        if self.id3.track().is_none() {
+            if let Some(Ok(v)) = get_info(&self.info, b"ITRK").map(|v| v.trim().parse()) {
+                self.id3.set_track(v);
+            }
+        }
+        if let (None, Some(v)) = (self.id3.genre(), get_info(&self.info, b"IGNR")) {
+            self.id3.set_genre(v);
+        }
+        if self.id3.comments().next().is_none() {
+            if let Some(v) = get_info(&self.info, b"ICMT") {
+                self.id3.add_frame(id3::frame::Comment {
+                    lang: "XXX".to_string(),
+                    description: "".to_string(),
+                    text: v.to_owned(),
+                });
+            }
+        }
+    }
+
+    /// Regenerate the `INFO` fields that have an ID3 counterpart, with multiple values joined with
+    /// `sep`.
+    fn sync_info(&mut self, sep: &str) {
+        let text = |id| {
+            self.id3
+                .get(id)
+                .and_then(|f| f.content().text())
+                .map(|t| t.replace('\0', sep))
+        };
+        self.info.retain(|(id, _)| !INFO_FIELDS.contains(&id));
+        let date = self
+            .id3
+            .date_recorded()
+            .map(|d| d.to_string())
+            .or_else(|| self.id3.year().map(|y| y.to_string()));
+        let comment = self
+            .id3
+            .comments()
+            .find(|c| c.description.is_empty())
+            .map(|c| c.text.clone());
+        let fields = [
+            (b"INAM", text("TIT2")),
+            (b"IART", text("TPE1")),
+            (b"IPRD", text("TALB")),
+            (b"ICRD", date),
+            (b"ITRK", self.id3.track().map(|t| t.to_string())),
+            (b"IGNR", text("TCON")),
+            (b"ICMT", comment),
+        ];
+        for (id, value) in fields {
+            if let Some(value) = value {
+                self.info.push((*id, value));
+            }
+        }
+    }
+}
+
+fn get_info<'a>(info: &'a [([u8; 4], String)], id: &[u8; 4]) -> Option<&'a str> {
+    info.iter().find(|(i, _)| i == id).map(|(_, v)| v.as_str())
+}
+
+fn parse_info(mut bytes: &[u8]) -> Vec<([u8; 4], String)> {
+    let mut info = Vec::new();
+    while let Some((id, rest)) = bytes.split_first_chunk::<4>() {
+        let Some((size, rest)) = rest.split_first_chunk::<4>() else {
+            break;
+        };
+        let size = u32::from_le_bytes(*size) as usize;
+        let Some(value) = rest.get(..size) else {
+            break;
+        };
+        let value = String::from_utf8_lossy(value);
+        info.push((*id, value.trim_end_matches('\0').to_owned()));
+        bytes = rest.get(size + size % 2..).unwrap_or_default();
+    }
+    info
+}
+
+fn info_to_bytes(info: &[([u8; 4], String)]) -> Vec<u8> {
+    let mut bytes = b"INFO".to_vec();
+    for (id, value) in info {
+        let size = value.len() + 1; // null-terminated
+        bytes.extend_from_slice(id);
+        bytes.extend_from_slice(&(size as u32).to_le_bytes());
+        bytes.extend_from_slice(value.as_bytes());
+        bytes.push(0);
+        if size % 2 == 1 {
+            bytes.push(0);
+        }
+    }
+    bytes
+}
+
+impl_tag!(WavTag, WavInnerTag, TagType::Wav);
+
+impl_id3v2_tag!(WavTag, inner.id3, {
+    fn duration(&self) -> Option<f64> {
+        self.inner.duration()
+    }
+});
+
+impl AudioTagWrite for WavTag {
+    fn write_to(&mut self, file: &mut dyn Storage) -> crate::Result<()> {
+        self.inner.write_with_config(file, &self.config)
+    }
+    fn write_to_path(&mut self, path: &str) -> crate::Result<()> {
+        let mut file = File::options().read(true).write(true).open(path)?;
+        self.write_to(&mut file)
+    }
+}
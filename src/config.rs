@@ -1,10 +1,47 @@
-#[derive(Clone, Copy)]
+use crate::Picture;
+
+#[derive(Clone, Copy, Debug)]
 pub struct Config {
     /// The separator used when parsing and formatting multiple artists in metadata formats that does not explicitly support
-    /// multiple artists (i.e. artist is a single string separated by the separator)
+    /// multiple artists (i.e. artist is a single string separated by the separator), that is ID3v1. The other formats store
+    /// each artist as a separate value.
     pub sep_artist: &'static str,
     /// Parse multiple artists from a single string using the separator specified above
     pub parse_multiple_artists: bool,
+    /// When reading an mp3 without ID3v2 tag with `Tag::read_from_path`, read its ID3v1 trailer
+    /// (as an `Id3v1Tag`) instead of failing
+    pub id3v1_fallback: bool,
+    /// What to do with the ID3v1 trailer when writing an `Id3v2Tag`
+    pub id3v1_write: Id3v1Write,
+    /// The ID3v2 version used when writing an `Id3v2Tag`. If `None`, the version the tag was read
+    /// with is kept (new tags are written as ID3v2.4). ID3v2.3 separates multiple values with `/`,
+    /// so values containing one, e.g. the artist "AC/DC", are split when read back
+    pub id3v2_version: Option<Id3v2Version>,
+    /// Used to convert covers to png when the format cannot store them as they are (e.g. a tiff
+    /// or gif cover in an `Mp4Tag`). If `None`, such covers are rejected.
+    pub cover_transcoder: Option<CoverTranscoder>,
+    /// The user of the ID3 `POPM` frame holding the rating and play count of an `Id3v2Tag`
+    pub popm_email: &'static str,
+    /// The mean of the MP4 freeform (`----:mean:name`) atoms holding the custom fields of an
+    /// `Mp4Tag`
+    pub mp4_freeform_mean: &'static str,
+}
+
+/// A function converting a picture to png data, e.g. with the `image` crate.
+pub type CoverTranscoder = fn(Picture<'_>) -> crate::Result<Vec<u8>>;
+
+pub use id3::Version as Id3v2Version;
+
+/// What to do with the ID3v1 trailer of a file when writing an ID3v2 tag to it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Id3v1Write {
+    /// Leave the trailer as it is, or absent
+    #[default]
+    Keep,
+    /// Write the trailer from the fields of the ID3v2 tag, replacing any existing one
+    Update,
+    /// Remove the trailer
+    Strip,
 }
 
 impl Default for Config {
@@ -12,9 +49,31 @@ impl Default for Config {
         Self {
             sep_artist: ";",
             parse_multiple_artists: true,
+            id3v1_fallback: false,
+            id3v1_write: Id3v1Write::Keep,
+            id3v2_version: None,
+            cover_transcoder: None,
+            popm_email: "no@email",
+            mp4_freeform_mean: "com.apple.iTunes",
         }
     }
 }
+/// The `cover_transcoder`s are compared by address.
+impl PartialEq for Config {
+    fn eq(&self, other: &Self) -> bool {
+        let transcoder = |c: &Self| c.cover_transcoder.map(|f| f as usize);
+        self.sep_artist == other.sep_artist
+            && self.parse_multiple_artists == other.parse_multiple_artists
+            && self.id3v1_fallback == other.id3v1_fallback
+            && self.id3v1_write == other.id3v1_write
+            && self.id3v2_version == other.id3v2_version
+            && transcoder(self) == transcoder(other)
+            && self.popm_email == other.popm_email
+            && self.mp4_freeform_mean == other.mp4_freeform_mean
+    }
+}
+impl Eq for Config {}
+
 impl Config {
     pub fn sep_artist(mut self, sep: &'static str) -> Self {
         self.sep_artist = sep;
@@ -24,4 +83,28 @@ impl Config {
         self.parse_multiple_artists = parse_multiple_artists;
         self
     }
+    pub fn id3v1_fallback(mut self, id3v1_fallback: bool) -> Self {
+        self.id3v1_fallback = id3v1_fallback;
+        self
+    }
+    pub fn id3v1_write(mut self, id3v1_write: Id3v1Write) -> Self {
+        self.id3v1_write = id3v1_write;
+        self
+    }
+    pub fn id3v2_version(mut self, id3v2_version: Id3v2Version) -> Self {
+        self.id3v2_version = Some(id3v2_version);
+        self
+    }
+    pub fn cover_transcoder(mut self, cover_transcoder: CoverTranscoder) -> Self {
+        self.cover_transcoder = Some(cover_transcoder);
+        self
+    }
+    pub fn popm_email(mut self, popm_email: &'static str) -> Self {
+        self.popm_email = popm_email;
+        self
+    }
+    pub fn mp4_freeform_mean(mut self, mp4_freeform_mean: &'static str) -> Self {
+        self.mp4_freeform_mean = mp4_freeform_mean;
+        self
+    }
 }
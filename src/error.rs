@@ -19,6 +19,8 @@ pub enum Error {
     UnsupportedMimeType(String),
     #[error("")]
     NotAPicture,
+    #[error("{0:?} pictures cannot be stored in this format")]
+    UnsupportedPictureFormat(crate::MimeType),
 
     #[error(transparent)]
     FlacTagError(#[from] metaflac::Error),
@@ -28,6 +30,18 @@ pub enum Error {
 
     #[error(transparent)]
     Id3TagError(#[from] id3::Error),
+
+    #[error("Ogg error: {0}")]
+    OggError(String),
+
+    #[error("APE error: {0}")]
+    ApeError(String),
+
+    #[error("Malformed chunk structure: {0}")]
+    ChunkError(String),
+
+    #[error("LRC error: {0}")]
+    LrcError(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,143 @@
+//! Image format detection and dimensions from the header of the image data, so that pictures
+//! with a missing or wrong mime type can still be read and broken ones rejected.
+
+use crate::MimeType;
+
+/// The format of the image, from its magic bytes.
+pub(crate) fn detect(data: &[u8]) -> Option<MimeType> {
+    Some(match data {
+        [0xFF, 0xD8, 0xFF, ..] => MimeType::Jpeg,
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => MimeType::Png,
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => MimeType::Gif,
+        [b'B', b'M', ..] => MimeType::Bmp,
+        [b'I', b'I', 0x2A, 0x00, ..] | [b'M', b'M', 0x00, 0x2A, ..] => MimeType::Tiff,
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => MimeType::Webp,
+        [_, _, _, _, b'f', b't', b'y', b'p', b'a', b'v', b'i', b'f' | b's', ..] => MimeType::Avif,
+        _ => return None,
+    })
+}
+
+/// The width and height of the image, or `None` if its format is unknown or the header is
+/// truncated.
+pub(crate) fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let dimensions = match detect(data)? {
+        MimeType::Jpeg => jpeg_dimensions(data)?,
+        MimeType::Png => (be32(data, 16)?, be32(data, 20)?),
+        MimeType::Gif => (le16(data, 6)?.into(), le16(data, 8)?.into()),
+        MimeType::Bmp => bmp_dimensions(data)?,
+        MimeType::Tiff => tiff_dimensions(data)?,
+        MimeType::Webp => webp_dimensions(data)?,
+        MimeType::Avif => {
+            // the `ispe` property: size, type, version and flags, width, height
+            let i = data.windows(4).position(|w| w == b"ispe")?;
+            (be32(data, i + 8)?, be32(data, i + 12)?)
+        }
+        MimeType::Other(_) => return None,
+    };
+    Some(dimensions)
+}
+
+fn be16(data: &[u8], i: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(i..i + 2)?.try_into().ok()?))
+}
+fn le16(data: &[u8], i: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(i..i + 2)?.try_into().ok()?))
+}
+fn be32(data: &[u8], i: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(i..i + 4)?.try_into().ok()?))
+}
+fn le32(data: &[u8], i: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(i..i + 4)?.try_into().ok()?))
+}
+
+/// Walk the markers to the first start of frame.
+fn jpeg_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let mut i = 2;
+    loop {
+        while *data.get(i)? != 0xFF {
+            i += 1;
+        }
+        while *data.get(i)? == 0xFF {
+            i += 1;
+        }
+        match data[i] {
+            // SOF0-SOF15, except DHT, JPG and DAC
+            0xC0..=0xCF if !matches!(data[i], 0xC4 | 0xC8 | 0xCC) => {
+                // length, precision, height, width
+                return Some((be16(data, i + 6)?.into(), be16(data, i + 4)?.into()));
+            }
+            // markers without a length
+            0x01 | 0xD0..=0xD8 => i += 1,
+            _ => i += 1 + usize::from(be16(data, i + 1)?),
+        }
+    }
+}
+
+fn bmp_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if le32(data, 14)? == 12 {
+        // BITMAPCOREHEADER
+        Some((le16(data, 18)?.into(), le16(data, 20)?.into()))
+    } else {
+        // negative heights are top-down bitmaps
+        let width = le32(data, 18)? as i32;
+        let height = le32(data, 22)? as i32;
+        Some((width.unsigned_abs(), height.unsigned_abs()))
+    }
+}
+
+/// Read the `ImageWidth` and `ImageLength` fields of the first IFD.
+fn tiff_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let little_endian = data[0] == b'I';
+    let u16_at = |i| {
+        if little_endian {
+            le16(data, i)
+        } else {
+            be16(data, i)
+        }
+    };
+    let u32_at = |i| {
+        if little_endian {
+            le32(data, i)
+        } else {
+            be32(data, i)
+        }
+    };
+    let ifd = u32_at(4)? as usize;
+    let (mut width, mut height) = (None, None);
+    for entry in 0..usize::from(u16_at(ifd)?) {
+        let i = ifd + 2 + entry * 12;
+        let value = match u16_at(i + 2)? {
+            3 => u32::from(u16_at(i + 8)?), // SHORT
+            4 => u32_at(i + 8)?,            // LONG
+            _ => continue,
+        };
+        match u16_at(i)? {
+            256 => width = Some(value),
+            257 => height = Some(value),
+            _ => {}
+        }
+    }
+    Some((width?, height?))
+}
+
+fn webp_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    match data.get(12..16)? {
+        b"VP8 " => Some((
+            u32::from(le16(data, 26)? & 0x3FFF),
+            u32::from(le16(data, 28)? & 0x3FFF),
+        )),
+        b"VP8L" => {
+            let bits = le32(data, 21)?;
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+        }
+        b"VP8X" => {
+            // 24-bit canvas width and height minus one
+            let canvas = |i: usize| {
+                let b = data.get(i..i + 3)?;
+                Some(u32::from_le_bytes([b[0], b[1], b[2], 0]) + 1)
+            };
+            Some((canvas(24)?, canvas(27)?))
+        }
+        _ => None,
+    }
+}
@@ -25,9 +25,14 @@
 //!
 //! | File Format   | Metadata Format       | backend                                                     |
 //! |---------------|-----------------------|-------------------------------------------------------------|
-//! | `mp3`         | id3v2.4               | [**id3**](https://github.com/polyfloyd/rust-id3)            |
+//! | `mp3`         | id3v2.3/4, id3v1.1    | [**id3**](https://github.com/polyfloyd/rust-id3)            |
 //! | `m4a/mp4/...` | MPEG-4 audio metadata | [**mp4ameta**](https://github.com/Saecki/rust-mp4ameta)     |
 //! | `flac`        | Vorbis comment        | [**metaflac**](https://github.com/jameshurst/rust-metaflac) |
+//! | `ogg/oga`     | Vorbis comment        | native                                                      |
+//! | `opus`        | Vorbis comment        | native                                                      |
+//! | `ape/wv/mpc`  | APEv2                 | native                                                      |
+//! | `wav`         | id3v2.4, RIFF INFO    | [**id3**](https://github.com/polyfloyd/rust-id3), native    |
+//! | `aif/aiff/aifc` | id3v2.4, text chunks | [**id3**](https://github.com/polyfloyd/rust-id3), native    |
 //!
 //! ## Examples
 //!
@@ -78,11 +83,22 @@ pub use traits::*;
 pub mod types;
 pub use types::*;
 
+pub mod owned;
+pub use owned::*;
+
+pub mod lyrics;
+pub use lyrics::*;
+
 pub mod config;
-pub use config::Config;
+pub use config::{Config, CoverTranscoder, Id3v1Write, Id3v2Version};
+
+mod image;
+mod ogg;
+mod riff;
 
 use std::convert::From;
 use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
 
 pub use std::convert::{TryFrom, TryInto};
@@ -113,12 +129,15 @@ pub struct Tag {
     tag_type: Option<TagType>,
     /// The config which can be specified with `.with_config()` before parsing.
     config: Config,
+    /// Whether to guess the tag type from the file content before the file extension, which can be
+    /// specified with `.with_content_detection()`.
+    detect_content: bool,
 }
 
 impl Tag {
     /// Initiate a new Tag (a builder for `Box<dyn AudioTag>`) with default configurations.
-    /// You can then optionally chain `with_tag_type` and/or `with_config`.
-    /// Finally, you `read_from_path`
+    /// You can then optionally chain `with_tag_type`, `with_config` and/or `with_content_detection`.
+    /// Finally, you `read_from_path` or `read_from`
     pub fn new() -> Self {
         Self::default()
     }
@@ -126,41 +145,114 @@ impl Tag {
     pub fn with_tag_type(self, tag_type: TagType) -> Self {
         Self {
             tag_type: Some(tag_type),
-            config: self.config,
+            ..self
         }
     }
     /// Specify configuration, if you do not want to use the default
     pub fn with_config(self, config: Config) -> Self {
+        Self { config, ..self }
+    }
+    /// Guess the tag type from the magic bytes of the file (see `TagType::detect`), and only fall
+    /// back to the file extension if they are not recognized. Ignored if the tag type is specified.
+    pub fn with_content_detection(self, detect_content: bool) -> Self {
         Self {
-            tag_type: self.tag_type,
-            config,
+            detect_content,
+            ..self
         }
     }
     pub fn read_from_path(
         &self,
         path: impl AsRef<Path>,
     ) -> crate::Result<Box<dyn AudioTag + Send + Sync>> {
-        match self.tag_type.unwrap_or(TagType::try_from_ext(
-            path.as_ref()
-                .extension()
-                .ok_or(Error::UnknownFileExtension(String::new()))?
-                .to_string_lossy()
-                .to_string()
-                .to_lowercase()
-                .as_str(),
-        )?) {
-            TagType::Id3v2 => Ok(Box::new({
-                let mut t = Id3v2Tag::read_from_path(path)?;
+        let mut reader = BufReader::new(File::open(path.as_ref())?);
+        let detected = match self.tag_type {
+            Some(tag_type) => Some(tag_type),
+            None if self.detect_content => TagType::detect(&mut reader).ok(),
+            None => None,
+        };
+        let tag_type = match detected {
+            Some(tag_type) => tag_type,
+            None => TagType::try_from_ext(
+                path.as_ref()
+                    .extension()
+                    .ok_or(Error::UnknownFileExtension(String::new()))?
+                    .to_string_lossy()
+                    .to_string()
+                    .to_lowercase()
+                    .as_str(),
+            )?,
+        };
+        self.read_as(tag_type, &mut reader)
+    }
+    /// Read from the start of `reader`, e.g. a `Cursor` over an in-memory file. Unless specified,
+    /// the tag type is guessed from the content (see `TagType::detect`).
+    pub fn read_from(
+        &self,
+        mut reader: impl Read + Seek,
+    ) -> crate::Result<Box<dyn AudioTag + Send + Sync>> {
+        let tag_type = match self.tag_type {
+            Some(tag_type) => tag_type,
+            None => TagType::detect(&mut reader)?,
+        };
+        self.read_as(tag_type, &mut reader)
+    }
+    fn read_as(
+        &self,
+        tag_type: TagType,
+        reader: &mut (impl Read + Seek),
+    ) -> crate::Result<Box<dyn AudioTag + Send + Sync>> {
+        match tag_type {
+            TagType::Id3v2 => match Id3v2Tag::read_from(reader) {
+                Err(Error::Id3TagError(e))
+                    if self.config.id3v1_fallback && matches!(e.kind, id3::ErrorKind::NoTag) =>
+                {
+                    let mut t = Id3v1Tag::read_from(reader).map_err(|_| e)?;
+                    t.set_config(self.config);
+                    Ok(Box::new(t))
+                }
+                t => Ok(Box::new({
+                    let mut t = t?;
+                    t.set_config(self.config);
+                    t
+                })),
+            },
+            TagType::Id3v1 => Ok(Box::new({
+                let mut t = Id3v1Tag::read_from(reader)?;
                 t.set_config(self.config);
                 t
             })),
             TagType::Mp4 => Ok(Box::new({
-                let mut t = Mp4Tag::read_from_path(path)?;
+                let mut t = Mp4Tag::read_from(reader)?;
                 t.set_config(self.config);
                 t
             })),
             TagType::Flac => Ok(Box::new({
-                let mut t = FlacTag::read_from_path(path)?;
+                let mut t = FlacTag::read_from(reader)?;
+                t.set_config(self.config);
+                t
+            })),
+            TagType::Vorbis => Ok(Box::new({
+                let mut t = OggTag::read_from(reader)?;
+                t.set_config(self.config);
+                t
+            })),
+            TagType::Opus => Ok(Box::new({
+                let mut t = OpusTag::read_from(reader)?;
+                t.set_config(self.config);
+                t
+            })),
+            TagType::Ape => Ok(Box::new({
+                let mut t = ApeTag::read_from(reader)?;
+                t.set_config(self.config);
+                t
+            })),
+            TagType::Wav => Ok(Box::new({
+                let mut t = WavTag::read_from(reader)?;
+                t.set_config(self.config);
+                t
+            })),
+            TagType::Aiff => Ok(Box::new({
+                let mut t = AiffTag::read_from(reader)?;
                 t.set_config(self.config);
                 t
             })),
@@ -168,7 +260,7 @@ impl Tag {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TagType {
     /// ## Common file extensions
     ///
@@ -178,6 +270,13 @@ pub enum TagType {
     ///
     /// - <https://www.wikiwand.com/en/ID3>
     Id3v2,
+    /// The legacy 128-byte trailer of mp3 files. Never guessed from the file extension: see
+    /// `Config::id3v1_fallback`.
+    ///
+    /// ## References
+    ///
+    /// - <https://id3.org/ID3v1>
+    Id3v1,
     Flac,
     /// ## Common file extensions
     ///
@@ -187,6 +286,46 @@ pub enum TagType {
     ///
     /// - <https://www.wikiwand.com/en/MPEG-4_Part_14>
     Mp4,
+    /// ## Common file extensions
+    ///
+    /// `.ogg, .oga`
+    ///
+    /// ## References
+    ///
+    /// - <https://www.wikiwand.com/en/Vorbis>
+    Vorbis,
+    /// ## Common file extensions
+    ///
+    /// `.opus`
+    ///
+    /// ## References
+    ///
+    /// - <https://www.rfc-editor.org/rfc/rfc7845>
+    Opus,
+    /// ## Common file extensions
+    ///
+    /// `.ape, .wv, .mpc`
+    ///
+    /// ## References
+    ///
+    /// - <https://wiki.hydrogenaud.io/index.php?title=APEv2_specification>
+    Ape,
+    /// ## Common file extensions
+    ///
+    /// `.wav, .wave`
+    ///
+    /// ## References
+    ///
+    /// - <https://www.wikiwand.com/en/WAV>
+    Wav,
+    /// ## Common file extensions
+    ///
+    /// `.aif, .aiff, .aifc`
+    ///
+    /// ## References
+    ///
+    /// - <https://www.wikiwand.com/en/Audio_Interchange_File_Format>
+    Aiff,
 }
 
 #[rustfmt::skip]
@@ -196,11 +335,109 @@ impl TagType {
                                                      "mp3" => Ok(Self::Id3v2),
             "m4a" | "m4b" | "m4p" | "m4v" | "isom" | "mp4" => Ok(Self::Mp4),
                                                     "flac" => Ok(Self::Flac),
+                                           "ogg" | "oga" => Ok(Self::Vorbis),
+                                                    "opus" => Ok(Self::Opus),
+                                     "ape" | "wv" | "mpc" => Ok(Self::Ape),
+                                          "wav" | "wave" => Ok(Self::Wav),
+                                   "aif" | "aiff" | "aifc" => Ok(Self::Aiff),
             p => Err(crate::Error::UnsupportedFormat(p.to_owned())),
         }
     }
 }
 
+impl TagType {
+    /// Guess the tag type from the magic bytes at the start of `reader`, whose position is restored
+    /// afterwards.
+    ///
+    /// Recognizes `ID3` (or an MPEG frame sync), `fLaC`, `ftyp`, `OggS` (Vorbis or Opus), `RIFF`
+    /// (WAVE), `FORM` (AIFF/AIFC), `MAC `, `wvpk`, `MPCK`/`MP+` and `APETAGEX`. A FLAC or APE file
+    /// preceded by an ID3v2 tag is recognized as such.
+    pub fn detect<R: Read + Seek + ?Sized>(reader: &mut R) -> crate::Result<Self> {
+        let position = reader.stream_position()?;
+        reader.seek(SeekFrom::Start(0))?;
+        let mut header = [0u8; 64];
+        let mut len = 0;
+        // read as much of the header as possible (short files, short reads)
+        while len < header.len() {
+            match reader.read(&mut header[len..])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        let mut detected = Self::detect_magic(&header[..len]);
+        if let (Some(Self::Id3v2), Some(id3v2_len)) = (detected, id3v2_len(&header[..len])) {
+            // look past the ID3v2 tag
+            reader.seek(SeekFrom::Start(id3v2_len))?;
+            let mut magic = [0u8; 4];
+            if reader.read_exact(&mut magic).is_ok() {
+                detected = match &magic {
+                    b"fLaC" => Some(Self::Flac),
+                    b"MAC " | b"wvpk" | b"MPCK" => Some(Self::Ape),
+                    _ => detected,
+                };
+            }
+        }
+        reader.seek(SeekFrom::Start(position))?;
+        detected.ok_or_else(|| Error::UnsupportedFormat("unrecognized file content".to_owned()))
+    }
+
+    fn detect_magic(header: &[u8]) -> Option<Self> {
+        let at =
+            |offset: usize, magic: &[u8]| header.get(offset..offset + magic.len()) == Some(magic);
+        if at(0, b"ID3") {
+            Some(Self::Id3v2)
+        } else if at(0, b"fLaC") {
+            Some(Self::Flac)
+        } else if at(4, b"ftyp") {
+            Some(Self::Mp4)
+        } else if at(0, b"OggS") {
+            // the first packet starts after the 27-byte page header and the segment table
+            let data = 27 + usize::from(*header.get(26)?);
+            if at(data, b"\x01vorbis") {
+                Some(Self::Vorbis)
+            } else if at(data, b"OpusHead") {
+                Some(Self::Opus)
+            } else {
+                None
+            }
+        } else if at(0, b"RIFF") && at(8, b"WAVE") {
+            Some(Self::Wav)
+        } else if at(0, b"FORM") && (at(8, b"AIFF") || at(8, b"AIFC")) {
+            Some(Self::Aiff)
+        } else if at(0, b"MAC ")
+            || at(0, b"wvpk")
+            || at(0, b"MPCK")
+            || at(0, b"MP+")
+            || at(0, b"APETAGEX")
+        {
+            Some(Self::Ape)
+        } else if header.len() >= 2
+            && header[0] == 0xff
+            && header[1] & 0xe0 == 0xe0
+            && header[1] & 0x06 != 0
+        {
+            // MPEG audio frame sync, i.e. an mp3 without ID3v2 tag; the layer bits of ADTS (AAC)
+            // are zero
+            Some(Self::Id3v2)
+        } else {
+            None
+        }
+    }
+}
+
+/// The length of the ID3v2 tag at the start of `header`, including its header and footer.
+pub(crate) fn id3v2_len(header: &[u8]) -> Option<u64> {
+    if header.len() < 10 || &header[..3] != b"ID3" {
+        return None;
+    }
+    // the size is a syncsafe integer
+    let size = header[6..10]
+        .iter()
+        .fold(0u64, |size, &b| (size << 7) | u64::from(b & 0x7f));
+    let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+    Some(10 + size + footer)
+}
+
 /// Convert a concrete tag type into another
 #[macro_export]
 macro_rules! convert {
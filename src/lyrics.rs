@@ -0,0 +1,157 @@
+//! Unsynchronised and synchronised lyrics, and their conversion from and to the LRC format.
+//!
+//! ## References
+//!
+//! - <https://en.wikipedia.org/wiki/LRC_(file_format)>
+
+use crate::*;
+use std::borrow::Cow;
+use std::fmt::Write;
+
+/// Unsynchronised lyrics, e.g. an ID3 `USLT` frame.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lyrics<'a> {
+    /// The ISO 639-2 language code, e.g. `"eng"`; empty if unknown or if the format does not
+    /// store it. Only ID3 stores the language and the description.
+    pub lang: Cow<'a, str>,
+    pub description: Cow<'a, str>,
+    pub text: Cow<'a, str>,
+}
+
+impl<'a> Lyrics<'a> {
+    pub fn new(text: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+    pub fn with_lang(mut self, lang: impl Into<Cow<'a, str>>) -> Self {
+        self.lang = lang.into();
+        self
+    }
+    pub fn with_description(mut self, description: impl Into<Cow<'a, str>>) -> Self {
+        self.description = description.into();
+        self
+    }
+    pub fn as_borrowed(&self) -> Lyrics<'_> {
+        Lyrics {
+            lang: Cow::Borrowed(&self.lang),
+            description: Cow::Borrowed(&self.description),
+            text: Cow::Borrowed(&self.text),
+        }
+    }
+    pub fn into_owned(self) -> Lyrics<'static> {
+        Lyrics {
+            lang: Cow::Owned(self.lang.into_owned()),
+            description: Cow::Owned(self.description.into_owned()),
+            text: Cow::Owned(self.text.into_owned()),
+        }
+    }
+}
+
+/// Synchronised lyrics, e.g. an ID3 `SYLT` frame or an LRC file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncedLyrics {
+    /// The ISO 639-2 language code, e.g. `"eng"`; empty if unknown
+    pub lang: String,
+    pub description: String,
+    /// The lines and the time they start at in milliseconds, in chronological order
+    pub lines: Vec<(u32, String)>,
+}
+
+impl SyncedLyrics {
+    /// Parse LRC text, e.g. `"[00:12.00]Line one\n[00:15.30]Line two"`. Lines may have several
+    /// timestamps, and the `[la:...]` and `[offset:...]` tags are applied.
+    ///
+    /// Returns `None` if there is no timestamped line, e.g. for unsynchronised lyrics.
+    pub fn from_lrc(lrc: &str) -> Option<Self> {
+        let mut lyrics = Self::default();
+        let mut offset = 0i64;
+        for line in lrc.lines() {
+            let mut rest = line.trim_start();
+            let mut times = Vec::new();
+            while let Some((tag, after)) = rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+                if let Some(time) = parse_lrc_time(tag) {
+                    times.push(time);
+                } else if let Some((key, value)) = tag.split_once(':') {
+                    match key.trim().to_ascii_lowercase().as_str() {
+                        "la" => lyrics.lang = value.trim().to_owned(),
+                        "offset" => offset = value.trim().parse().unwrap_or(0),
+                        _ => {}
+                    }
+                }
+                rest = after;
+            }
+            for time in times {
+                lyrics.lines.push((time, rest.trim().to_owned()));
+            }
+        }
+        if lyrics.lines.is_empty() {
+            return None;
+        }
+        // a positive offset shifts the lyrics earlier
+        for (time, _) in lyrics.lines.iter_mut() {
+            *time = (i64::from(*time) - offset).clamp(0, i64::from(u32::MAX)) as u32;
+        }
+        lyrics.lines.sort_by_key(|(time, _)| *time);
+        Some(lyrics)
+    }
+
+    /// Format the lyrics as LRC text, with a `[la:...]` tag if the language is known.
+    pub fn to_lrc(&self) -> String {
+        let mut lrc = String::new();
+        if !self.lang.is_empty() {
+            let _ = writeln!(lrc, "[la:{}]", self.lang);
+        }
+        for (time, text) in &self.lines {
+            let _ = writeln!(
+                lrc,
+                "[{:02}:{:02}.{:02}]{}",
+                time / 60_000,
+                time / 1000 % 60,
+                time % 1000 / 10,
+                text
+            );
+        }
+        lrc
+    }
+
+    /// Read an `.lrc` file.
+    pub fn read_lrc(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::from_lrc(&std::fs::read_to_string(path)?)
+            .ok_or_else(|| Error::LrcError("no timestamped line".to_owned()))
+    }
+
+    /// Write the lyrics to an `.lrc` file.
+    pub fn write_lrc(&self, path: impl AsRef<Path>) -> crate::Result<()> {
+        std::fs::write(path, self.to_lrc())?;
+        Ok(())
+    }
+}
+
+/// Parse `mm:ss`, `mm:ss.xx` or `mm:ss.xxx` (also with `:` before the fraction) to milliseconds.
+fn parse_lrc_time(time: &str) -> Option<u32> {
+    let (minutes, rest) = time.trim().split_once(':')?;
+    let (seconds, fraction) = match rest.split_once(['.', ':']) {
+        Some((seconds, fraction)) => (seconds, fraction),
+        None => (rest, ""),
+    };
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !digits(minutes) || !digits(seconds) || !(fraction.is_empty() || digits(fraction)) {
+        return None;
+    }
+    let fraction_ms = match fraction.len() {
+        0 => 0,
+        1 => fraction.parse::<u32>().ok()? * 100,
+        2 => fraction.parse::<u32>().ok()? * 10,
+        _ => fraction[..3].parse::<u32>().ok()?,
+    };
+    minutes
+        .parse::<u32>()
+        .ok()?
+        .checked_mul(60_000)?
+        .checked_add(seconds.parse::<u32>().ok()?.checked_mul(1000)?)?
+        .checked_add(fraction_ms)
+}
@@ -0,0 +1,262 @@
+//! Minimal Ogg bitstream handling: just enough to pull the header packets out of a logical stream and
+//! to rewrite them (re-paginating and re-checksumming the pages that follow) when a comment header
+//! changes size.
+//!
+//! ## References
+//!
+//! - <https://xiph.org/ogg/doc/framing.html>
+
+use crate::{rewrite_via_tempfile, Error, Result, Storage};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+const CAPTURE_PATTERN: &[u8; 4] = b"OggS";
+const HEADER_LEN: usize = 27;
+const FLAG_CONTINUED: u8 = 0x01;
+const FLAG_FIRST: u8 = 0x02;
+const MAX_SEGMENTS: usize = 255;
+
+/// A single Ogg page.
+#[derive(Clone, Debug)]
+pub(crate) struct Page {
+    pub header_type: u8,
+    pub granule_position: u64,
+    pub serial: u32,
+    pub sequence: u32,
+    pub segments: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+impl Page {
+    /// Read the next page, or `None` at the end of the stream.
+    pub fn read_from(reader: &mut (impl Read + ?Sized)) -> Result<Option<Self>> {
+        let mut header = [0u8; HEADER_LEN];
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        if &header[..4] != CAPTURE_PATTERN {
+            return Err(Error::OggError("missing capture pattern".to_owned()));
+        }
+        let mut segments = vec![0u8; header[26] as usize];
+        reader.read_exact(&mut segments)?;
+        let mut data = vec![0u8; segments.iter().map(|&s| s as usize).sum()];
+        reader.read_exact(&mut data)?;
+        Ok(Some(Self {
+            header_type: header[5],
+            granule_position: u64::from_le_bytes(header[6..14].try_into().unwrap()),
+            serial: u32::from_le_bytes(header[14..18].try_into().unwrap()),
+            sequence: u32::from_le_bytes(header[18..22].try_into().unwrap()),
+            segments,
+            data,
+        }))
+    }
+
+    /// Serialize the page, computing its checksum.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.segments.len() + self.data.len());
+        bytes.extend_from_slice(CAPTURE_PATTERN);
+        bytes.push(0); // stream structure version
+        bytes.push(self.header_type);
+        bytes.extend_from_slice(&self.granule_position.to_le_bytes());
+        bytes.extend_from_slice(&self.serial.to_le_bytes());
+        bytes.extend_from_slice(&self.sequence.to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]); // checksum placeholder
+        bytes.push(self.segments.len() as u8);
+        bytes.extend_from_slice(&self.segments);
+        bytes.extend_from_slice(&self.data);
+        let crc = crc32(&bytes);
+        bytes[22..26].copy_from_slice(&crc.to_le_bytes());
+        bytes
+    }
+}
+
+/// The header packets of the first logical stream in an Ogg file.
+pub(crate) struct Headers {
+    pub packets: Vec<Vec<u8>>,
+    /// The granule position of the last page of the stream.
+    pub last_granule_position: u64,
+}
+
+/// Read the first `count` packets of the first logical stream, and find its last granule position.
+pub(crate) fn read_headers<R: Read + Seek + ?Sized>(
+    reader: &mut R,
+    count: usize,
+) -> Result<Headers> {
+    reader.seek(SeekFrom::Start(0))?;
+    let mut serial = None;
+    let mut packets = Vec::with_capacity(count);
+    let mut partial = Vec::new();
+    let mut last_granule_position = 0;
+    while let Some(page) = Page::read_from(reader)? {
+        match serial {
+            None => serial = Some(page.serial),
+            Some(s) if s != page.serial => continue,
+            _ => {}
+        }
+        if page.granule_position != u64::MAX {
+            last_granule_position = page.granule_position;
+        }
+        if packets.len() < count {
+            let mut offset = 0;
+            for &len in &page.segments {
+                partial.extend_from_slice(&page.data[offset..offset + len as usize]);
+                offset += len as usize;
+                if len < 255 && packets.len() < count {
+                    packets.push(std::mem::take(&mut partial));
+                }
+            }
+        }
+    }
+    if packets.len() < count {
+        return Err(Error::OggError("stream ends before its headers".to_owned()));
+    }
+    Ok(Headers {
+        packets,
+        last_granule_position,
+    })
+}
+
+/// Replace the header packets of the first logical stream of `file` with `packets`.
+///
+/// The identification header (`packets[0]`) gets the first page to itself. When the new header
+/// pages take the same room as the old ones, only they are overwritten; otherwise the file is
+/// rewritten through a temporary file, the pages after the headers being copied verbatim unless
+/// the number of header pages changed, in which case their sequence numbers and checksums are
+/// updated.
+pub(crate) fn write_headers(file: &mut dyn Storage, packets: &[Vec<u8>]) -> Result<()> {
+    file.seek(SeekFrom::Start(0))?;
+    let first = Page::read_from(file)?.ok_or_else(|| Error::OggError("empty stream".to_owned()))?;
+    if first.header_type & FLAG_FIRST == 0 || first.segments.last() == Some(&255) {
+        return Err(Error::OggError(
+            "identification header must be alone on the first page".to_owned(),
+        ));
+    }
+    let serial = first.serial;
+
+    // skip the old header pages of this stream, keeping interleaved pages of other streams
+    let mut others = Vec::new();
+    let mut old_pages = 1u32;
+    let mut remaining = packets.len() - 1;
+    while remaining > 0 {
+        let page = Page::read_from(file)?
+            .ok_or_else(|| Error::OggError("stream ends before its headers".to_owned()))?;
+        if page.serial != serial {
+            others.push(page);
+            continue;
+        }
+        old_pages += 1;
+        for (i, &len) in page.segments.iter().enumerate() {
+            if len < 255 {
+                remaining -= 1;
+                if remaining == 0 && i + 1 != page.segments.len() {
+                    return Err(Error::OggError(
+                        "audio data shares a page with the headers".to_owned(),
+                    ));
+                }
+            }
+        }
+    }
+    let headers_end = file.stream_position()?;
+
+    let mut new_pages = paginate(&packets[..1], serial, 0);
+    new_pages[0].header_type |= FLAG_FIRST;
+    new_pages.extend(paginate(&packets[1..], serial, 1));
+    let delta = new_pages.len() as i64 - i64::from(old_pages);
+    let mut head = Vec::new();
+    for page in new_pages.iter().chain(others.iter()) {
+        head.extend(page.to_bytes());
+    }
+
+    if delta == 0 && head.len() as u64 == headers_end {
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&head)?;
+        return Ok(());
+    }
+    rewrite_via_tempfile(file, |file, tmp| {
+        tmp.write_all(&head)?;
+        file.seek(SeekFrom::Start(headers_end))?;
+        if delta == 0 {
+            std::io::copy(file, tmp)?;
+        } else {
+            while let Some(mut page) = Page::read_from(file)? {
+                if page.serial == serial {
+                    page.sequence = (i64::from(page.sequence) + delta) as u32;
+                }
+                tmp.write_all(&page.to_bytes())?;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Lay out `packets` on as many pages as needed, each packet starting right after the previous one.
+fn paginate(packets: &[Vec<u8>], serial: u32, first_sequence: u32) -> Vec<Page> {
+    let mut pages = Vec::new();
+    let mut page = new_page(serial, first_sequence, 0);
+    let mut packet_ended = false;
+    for packet in packets {
+        let mut lens = vec![255u8; packet.len() / 255];
+        lens.push((packet.len() % 255) as u8);
+        let mut offset = 0;
+        for (i, len) in lens.iter().enumerate() {
+            if page.segments.len() == MAX_SEGMENTS {
+                let continued = i > 0;
+                finish_page(&mut page, packet_ended);
+                let sequence = page.sequence + 1;
+                pages.push(std::mem::replace(
+                    &mut page,
+                    new_page(serial, sequence, if continued { FLAG_CONTINUED } else { 0 }),
+                ));
+                packet_ended = false;
+            }
+            page.segments.push(*len);
+            page.data
+                .extend_from_slice(&packet[offset..offset + *len as usize]);
+            offset += *len as usize;
+        }
+        packet_ended = true;
+    }
+    finish_page(&mut page, packet_ended);
+    pages.push(page);
+    pages
+}
+
+fn new_page(serial: u32, sequence: u32, header_type: u8) -> Page {
+    Page {
+        header_type,
+        granule_position: 0,
+        serial,
+        sequence,
+        segments: Vec::new(),
+        data: Vec::new(),
+    }
+}
+
+/// Header packets have a granule position of zero; a page on which no packet ends gets -1.
+fn finish_page(page: &mut Page, packet_ended: bool) {
+    page.granule_position = if packet_ended { 0 } else { u64::MAX };
+}
+
+/// The CRC used by Ogg: polynomial 0x04c11db7, no reflection, zero initial value and no final xor.
+fn crc32(bytes: &[u8]) -> u32 {
+    static TABLE: std::sync::OnceLock<[u32; 256]> = std::sync::OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let mut table = [0u32; 256];
+        for (i, entry) in table.iter_mut().enumerate() {
+            let mut r = (i as u32) << 24;
+            for _ in 0..8 {
+                r = if r & 0x8000_0000 != 0 {
+                    (r << 1) ^ 0x04c1_1db7
+                } else {
+                    r << 1
+                };
+            }
+            *entry = r;
+        }
+        table
+    });
+    bytes.iter().fold(0u32, |crc, &b| {
+        (crc << 8) ^ table[((crc >> 24) as u8 ^ b) as usize]
+    })
+}
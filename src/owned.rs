@@ -0,0 +1,286 @@
+use crate::*;
+use id3::Timestamp;
+
+/// An owned `Picture`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedPicture {
+    pub data: Vec<u8>,
+    pub mime_type: MimeType,
+}
+
+impl OwnedPicture {
+    pub fn as_picture(&self) -> Picture<'_> {
+        Picture {
+            data: &self.data,
+            mime_type: self.mime_type.clone(),
+        }
+    }
+}
+
+impl From<Picture<'_>> for OwnedPicture {
+    fn from(inp: Picture<'_>) -> Self {
+        Self {
+            data: inp.data.to_owned(),
+            mime_type: inp.mime_type,
+        }
+    }
+}
+
+/// The owned counterpart of `AnyTag`, which can be stored, sent across threads and (with the
+/// `serde` feature) serialized.
+///
+/// # Examples
+///
+/// ```no_run
+/// use audiotags::{OwnedTag, Tag, TagType, ToAnyTag};
+///
+/// let tag = Tag::new().read_from_path("assets/a.mp3").unwrap();
+/// let owned = OwnedTag::from(&tag.to_anytag());
+/// std::thread::spawn(move || {
+///     let flac = owned.to_dyn_tag(TagType::Flac);
+///     assert_eq!(flac.title(), owned.title.as_deref());
+/// });
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct OwnedTag {
+    /// Not serialized: a deserialized tag has the default config
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub config: Config,
+    pub title: Option<String>,
+    pub artists: Option<Vec<String>>,
+    #[cfg_attr(feature = "serde", serde(with = "timestamp"))]
+    pub date: Option<Timestamp>,
+    pub year: Option<i32>,
+    pub duration: Option<f64>,
+    pub album_title: Option<String>,
+    pub album_artists: Option<Vec<String>>,
+    pub album_cover: Option<OwnedPicture>,
+    pub pictures: Vec<EmbeddedPicture<'static>>,
+    pub track_number: Option<u16>,
+    pub total_tracks: Option<u16>,
+    pub disc_number: Option<u16>,
+    pub total_discs: Option<u16>,
+    pub genres: Option<Vec<String>>,
+    pub composers: Option<Vec<String>>,
+    pub comment: Option<String>,
+    pub lyrics: Option<Lyrics<'static>>,
+    pub synced_lyrics: Option<SyncedLyrics>,
+    pub replay_gain: Option<ReplayGain>,
+    pub title_sort: Option<String>,
+    pub artist_sort: Option<String>,
+    pub album_sort: Option<String>,
+    pub album_artist_sort: Option<String>,
+    pub composer_sort: Option<String>,
+    pub isrc: Option<String>,
+    pub label: Option<String>,
+    pub catalog_number: Option<String>,
+    pub copyright: Option<String>,
+    pub encoded_by: Option<String>,
+    pub grouping: Option<String>,
+    pub mood: Option<String>,
+    pub language: Option<String>,
+    pub conductor: Option<String>,
+    pub lyricist: Option<String>,
+    pub bpm: Option<u16>,
+    pub compilation: bool,
+    #[cfg_attr(feature = "serde", serde(with = "timestamp"))]
+    pub original_date: Option<Timestamp>,
+    pub rating: Option<f64>,
+    pub play_count: Option<u64>,
+    pub musicbrainz_ids: Vec<(MusicBrainzId, String)>,
+    pub custom_fields: Vec<(String, String)>,
+}
+
+impl From<&AnyTag<'_>> for OwnedTag {
+    fn from(inp: &AnyTag<'_>) -> Self {
+        let owned_vec = |v: &Vec<&str>| v.iter().map(|&s| s.to_owned()).collect();
+        Self {
+            config: inp.config,
+            title: inp.title.map(str::to_owned),
+            artists: inp.artists.as_ref().map(owned_vec),
+            date: inp.date,
+            year: inp.year,
+            duration: inp.duration,
+            album_title: inp.album_title.map(str::to_owned),
+            album_artists: inp.album_artists.as_ref().map(owned_vec),
+            album_cover: inp.album_cover.clone().map(OwnedPicture::from),
+            pictures: inp
+                .pictures
+                .iter()
+                .map(|p| p.clone().into_owned())
+                .collect(),
+            track_number: inp.track_number,
+            total_tracks: inp.total_tracks,
+            disc_number: inp.disc_number,
+            total_discs: inp.total_discs,
+            genres: inp.genres.as_ref().map(owned_vec),
+            composers: inp.composers.as_ref().map(owned_vec),
+            comment: inp.comment.map(str::to_owned),
+            lyrics: inp.lyrics.clone().map(Lyrics::into_owned),
+            synced_lyrics: inp.synced_lyrics.clone(),
+            replay_gain: inp.replay_gain,
+            title_sort: inp.title_sort.map(str::to_owned),
+            artist_sort: inp.artist_sort.map(str::to_owned),
+            album_sort: inp.album_sort.map(str::to_owned),
+            album_artist_sort: inp.album_artist_sort.map(str::to_owned),
+            composer_sort: inp.composer_sort.map(str::to_owned),
+            isrc: inp.isrc.map(str::to_owned),
+            label: inp.label.map(str::to_owned),
+            catalog_number: inp.catalog_number.map(str::to_owned),
+            copyright: inp.copyright.map(str::to_owned),
+            encoded_by: inp.encoded_by.map(str::to_owned),
+            grouping: inp.grouping.map(str::to_owned),
+            mood: inp.mood.map(str::to_owned),
+            language: inp.language.map(str::to_owned),
+            conductor: inp.conductor.map(str::to_owned),
+            lyricist: inp.lyricist.map(str::to_owned),
+            bpm: inp.bpm,
+            compilation: inp.compilation,
+            original_date: inp.original_date,
+            rating: inp.rating,
+            play_count: inp.play_count,
+            musicbrainz_ids: inp
+                .musicbrainz_ids
+                .iter()
+                .map(|&(id, v)| (id, v.to_owned()))
+                .collect(),
+            custom_fields: inp
+                .custom_fields
+                .iter()
+                .map(|&(name, v)| (name.to_owned(), v.to_owned()))
+                .collect(),
+        }
+    }
+}
+
+impl From<AnyTag<'_>> for OwnedTag {
+    fn from(inp: AnyTag<'_>) -> Self {
+        Self::from(&inp)
+    }
+}
+
+impl OwnedTag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Borrow the tag as an `AnyTag`, e.g. to convert it into a tag type.
+    pub fn as_anytag(&self) -> AnyTag<'_> {
+        fn borrowed_vec(v: &[String]) -> Vec<&str> {
+            v.iter().map(String::as_str).collect()
+        }
+        AnyTag {
+            config: self.config,
+            title: self.title.as_deref(),
+            artists: self.artists.as_deref().map(borrowed_vec),
+            date: self.date,
+            year: self.year,
+            duration: self.duration,
+            album_title: self.album_title.as_deref(),
+            album_artists: self.album_artists.as_deref().map(borrowed_vec),
+            album_cover: self.album_cover.as_ref().map(OwnedPicture::as_picture),
+            pictures: self
+                .pictures
+                .iter()
+                .map(EmbeddedPicture::as_borrowed)
+                .collect(),
+            track_number: self.track_number,
+            total_tracks: self.total_tracks,
+            disc_number: self.disc_number,
+            total_discs: self.total_discs,
+            genres: self.genres.as_deref().map(borrowed_vec),
+            composers: self.composers.as_deref().map(borrowed_vec),
+            comment: self.comment.as_deref(),
+            lyrics: self.lyrics.as_ref().map(Lyrics::as_borrowed),
+            synced_lyrics: self.synced_lyrics.clone(),
+            replay_gain: self.replay_gain,
+            title_sort: self.title_sort.as_deref(),
+            artist_sort: self.artist_sort.as_deref(),
+            album_sort: self.album_sort.as_deref(),
+            album_artist_sort: self.album_artist_sort.as_deref(),
+            composer_sort: self.composer_sort.as_deref(),
+            isrc: self.isrc.as_deref(),
+            label: self.label.as_deref(),
+            catalog_number: self.catalog_number.as_deref(),
+            copyright: self.copyright.as_deref(),
+            encoded_by: self.encoded_by.as_deref(),
+            grouping: self.grouping.as_deref(),
+            mood: self.mood.as_deref(),
+            language: self.language.as_deref(),
+            conductor: self.conductor.as_deref(),
+            lyricist: self.lyricist.as_deref(),
+            bpm: self.bpm,
+            compilation: self.compilation,
+            original_date: self.original_date,
+            rating: self.rating,
+            play_count: self.play_count,
+            musicbrainz_ids: self
+                .musicbrainz_ids
+                .iter()
+                .map(|(id, v)| (*id, v.as_str()))
+                .collect(),
+            custom_fields: self
+                .custom_fields
+                .iter()
+                .map(|(name, v)| (name.as_str(), v.as_str()))
+                .collect(),
+        }
+    }
+}
+
+impl ToAnyTag for OwnedTag {
+    fn to_anytag(&self) -> AnyTag<'_> {
+        self.as_anytag()
+    }
+}
+
+impl ToAny for OwnedTag {
+    fn to_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn to_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+macro_rules! impl_from_owned {
+    ($($tag:ident),+) => {
+        $(
+            impl From<&OwnedTag> for $tag {
+                fn from(inp: &OwnedTag) -> Self {
+                    inp.as_anytag().into()
+                }
+            }
+            impl From<OwnedTag> for $tag {
+                fn from(inp: OwnedTag) -> Self {
+                    Self::from(&inp)
+                }
+            }
+        )+
+    };
+}
+
+impl_from_owned!(Id3v2Tag, Id3v1Tag, Mp4Tag, FlacTag, OggTag, OpusTag, ApeTag, WavTag, AiffTag);
+
+/// (De)serialize `Option<Timestamp>` as an ISO 8601 string, e.g. `"2020-05-22"`.
+#[cfg(feature = "serde")]
+mod timestamp {
+    use id3::Timestamp;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(date: &Option<Timestamp>, s: S) -> Result<S::Ok, S::Error> {
+        match date {
+            Some(date) => s.serialize_some(&date.to_string()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Timestamp>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|date| Timestamp::from_str(&date).map_err(D::Error::custom))
+            .transpose()
+    }
+}
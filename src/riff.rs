@@ -0,0 +1,161 @@
+//! Reading and rewriting the chunks of RIFF (WAV, little-endian) and IFF (AIFF, big-endian)
+//! containers.
+//!
+//! ## References
+//!
+//! - <https://www.mmsp.ece.mcgill.ca/Documents/AudioFormats/WAVE/WAVE.html>
+//! - <https://www.mmsp.ece.mcgill.ca/Documents/AudioFormats/AIFF/AIFF.html>
+
+use crate::{Error, Result, Storage};
+use std::io::{Read, Seek, SeekFrom};
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Endianness {
+    Little,
+    Big,
+}
+
+impl Endianness {
+    pub fn u32(self, bytes: [u8; 4]) -> u32 {
+        match self {
+            Self::Little => u32::from_le_bytes(bytes),
+            Self::Big => u32::from_be_bytes(bytes),
+        }
+    }
+    pub fn u32_bytes(self, n: u32) -> [u8; 4] {
+        match self {
+            Self::Little => n.to_le_bytes(),
+            Self::Big => n.to_be_bytes(),
+        }
+    }
+}
+
+/// A chunk header: its id, where it starts and the size of its data (without padding).
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ChunkInfo {
+    pub id: [u8; 4],
+    pub offset: u64,
+    pub size: u32,
+}
+
+impl ChunkInfo {
+    pub fn data_offset(&self) -> u64 {
+        self.offset + 8
+    }
+    /// The length of the whole chunk, including header and padding byte.
+    fn len(&self) -> u64 {
+        8 + u64::from(self.size) + u64::from(self.size & 1)
+    }
+}
+
+/// The top-level chunks of a RIFF or IFF file.
+pub(crate) struct Container {
+    /// The form type, e.g. `WAVE` or `AIFF`.
+    pub form_type: [u8; 4],
+    pub chunks: Vec<ChunkInfo>,
+    endianness: Endianness,
+}
+
+impl Container {
+    /// List the chunks of a container whose magic is `magic` (`RIFF` or `FORM`).
+    pub fn read_from<R: Read + Seek + ?Sized>(
+        reader: &mut R,
+        magic: &[u8; 4],
+        endianness: Endianness,
+    ) -> Result<Self> {
+        let malformed = |msg: &str| Error::ChunkError(msg.to_owned());
+        let file_len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
+        let mut header = [0u8; 12];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != magic {
+            return Err(malformed("unexpected file magic"));
+        }
+        let form_size = endianness.u32(header[4..8].try_into().unwrap());
+        let end = (8 + u64::from(form_size)).min(file_len);
+        let mut chunks = Vec::new();
+        let mut offset = 12;
+        while offset + 8 <= end {
+            let mut chunk_header = [0u8; 8];
+            reader.seek(SeekFrom::Start(offset))?;
+            reader.read_exact(&mut chunk_header)?;
+            let chunk = ChunkInfo {
+                id: chunk_header[..4].try_into().unwrap(),
+                offset,
+                size: endianness.u32(chunk_header[4..].try_into().unwrap()),
+            };
+            if chunk.data_offset() + u64::from(chunk.size) > file_len {
+                return Err(malformed("chunk extends past the end of the file"));
+            }
+            offset += chunk.len();
+            chunks.push(chunk);
+        }
+        Ok(Self {
+            form_type: header[8..12].try_into().unwrap(),
+            chunks,
+            endianness,
+        })
+    }
+
+    pub fn find(&self, id: &[u8; 4]) -> Option<&ChunkInfo> {
+        self.chunks.iter().find(|c| &c.id == id)
+    }
+
+    pub fn read_chunk<R: Read + Seek + ?Sized>(
+        reader: &mut R,
+        chunk: &ChunkInfo,
+    ) -> Result<Vec<u8>> {
+        let mut data = vec![0u8; chunk.size as usize];
+        reader.seek(SeekFrom::Start(chunk.data_offset()))?;
+        reader.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    /// Remove the chunks at the `removed` indices, then append `added` chunks and update the size
+    /// of the container.
+    ///
+    /// No chunk is moved, so the audio data is never read or rewritten: removed chunks followed by
+    /// a kept one are turned into `JUNK` padding in place, and the others, at the end of the file,
+    /// are overwritten by `added`. The file is only truncated once everything is written.
+    pub fn rewrite(
+        &self,
+        file: &mut dyn Storage,
+        removed: &[usize],
+        added: &[([u8; 4], Vec<u8>)],
+    ) -> Result<()> {
+        let end = self
+            .chunks
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !removed.contains(i))
+            .map(|(_, c)| c.offset + c.len())
+            .max()
+            .unwrap_or(12);
+
+        let mut tail = Vec::new();
+        for (id, data) in added {
+            tail.extend_from_slice(id);
+            tail.extend_from_slice(&self.endianness.u32_bytes(data.len() as u32));
+            tail.extend_from_slice(data);
+            if data.len() % 2 == 1 {
+                tail.push(0);
+            }
+        }
+        // the padding byte of the very last kept chunk is sometimes missing, seeking past the end
+        // of the file fills it in with zero
+        file.seek(SeekFrom::Start(end))?;
+        file.write_all(&tail)?;
+        let new_len = end + tail.len() as u64;
+        file.set_len(new_len)?;
+
+        for chunk in removed.iter().map(|&i| &self.chunks[i]) {
+            if chunk.offset < end {
+                file.seek(SeekFrom::Start(chunk.offset))?;
+                file.write_all(b"JUNK")?;
+            }
+        }
+        file.seek(SeekFrom::Start(4))?;
+        file.write_all(&self.endianness.u32_bytes((new_len - 8) as u32))?;
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use super::*;
 use id3::Timestamp;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 
 pub trait AudioTag: AudioTagEdit + AudioTagWrite + ToAnyTag {}
 
@@ -16,20 +17,22 @@ pub trait AudioTagEdit: AudioTagConfig {
     fn set_title(&mut self, title: &str);
     fn remove_title(&mut self);
 
+    /// The first artist (all of them in `Id3v1Tag`, see `artists`).
     fn artist(&self) -> Option<&str>;
+    /// Replace the artists with `artist`.
     fn set_artist(&mut self, artist: &str);
     fn remove_artist(&mut self);
 
+    /// The artists, stored as separate values (see `TagKey`) except in `Id3v1Tag`, where they are
+    /// separated by `Config::sep_artist`.
     fn artists(&self) -> Option<Vec<&str>> {
-        if self.config().parse_multiple_artists {
-            self.artist()
-                .map(|a| a.split(self.config().sep_artist).collect::<Vec<&str>>())
-        } else {
-            self.artist().map(|v| vec![v])
-        }
+        Some(self.get(&TagKey::Artist)).filter(|v| !v.is_empty())
+    }
+    fn set_artists(&mut self, artists: &[&str]) {
+        self.set(&TagKey::Artist, artists);
     }
     fn add_artist(&mut self, artist: &str) {
-        self.set_artist(artist);
+        self.push(&TagKey::Artist, artist);
     }
 
     fn date(&self) -> Option<Timestamp>;
@@ -77,25 +80,60 @@ pub trait AudioTagEdit: AudioTagConfig {
     fn remove_album_artist(&mut self);
 
     fn album_artists(&self) -> Option<Vec<&str>> {
-        if self.config().parse_multiple_artists {
-            self.album_artist()
-                .map(|a| a.split(self.config().sep_artist).collect::<Vec<&str>>())
-        } else {
-            self.album_artist().map(|v| vec![v])
-        }
+        Some(self.get(&TagKey::AlbumArtist)).filter(|v| !v.is_empty())
+    }
+    fn set_album_artists(&mut self, artists: &[&str]) {
+        self.set(&TagKey::AlbumArtist, artists);
     }
     fn add_album_artist(&mut self, artist: &str) {
-        self.set_album_artist(artist);
+        self.push(&TagKey::AlbumArtist, artist);
     }
 
-    fn album_cover(&self) -> Option<Picture>;
+    fn album_cover(&self) -> Option<Picture<'_>>;
     fn set_album_cover(&mut self, cover: Picture);
     fn remove_album_cover(&mut self);
+    /// Like `set_album_cover`, but return an error instead of ignoring a cover the format cannot
+    /// store.
+    fn try_set_album_cover(&mut self, cover: Picture) -> crate::Result<()> {
+        self.set_album_cover(cover);
+        Ok(())
+    }
+
+    /// All the pictures of the tag, including the front cover returned by `album_cover`.
+    ///
+    /// MP4 does not store picture types, so every `covr` artwork is returned as `CoverFront`.
+    fn pictures(&self) -> Vec<EmbeddedPicture<'_>> {
+        self.album_cover()
+            .map(|cover| vec![cover.into()])
+            .unwrap_or_default()
+    }
+    /// Add a picture, replacing the picture of the same type if there is one. In MP4, the
+    /// picture is appended to the `covr` artworks.
+    fn add_picture(&mut self, picture: EmbeddedPicture) {
+        if picture.picture_type == PictureType::CoverFront {
+            self.set_album_cover(picture.as_picture());
+        }
+    }
+    fn remove_pictures_by_type(&mut self, picture_type: PictureType) {
+        if picture_type == PictureType::CoverFront {
+            self.remove_album_cover();
+        }
+    }
 
     fn composer(&self) -> Option<&str>;
     fn set_composer(&mut self, composer: String);
     fn remove_composer(&mut self);
 
+    fn composers(&self) -> Option<Vec<&str>> {
+        Some(self.get(&TagKey::Composer)).filter(|v| !v.is_empty())
+    }
+    fn set_composers(&mut self, composers: &[&str]) {
+        self.set(&TagKey::Composer, composers);
+    }
+    fn add_composer(&mut self, composer: &str) {
+        self.push(&TagKey::Composer, composer);
+    }
+
     fn track(&self) -> (Option<u16>, Option<u16>) {
         (self.track_number(), self.total_tracks())
     }
@@ -140,15 +178,408 @@ pub trait AudioTagEdit: AudioTagConfig {
     fn set_genre(&mut self, genre: &str);
     fn remove_genre(&mut self);
 
+    fn genres(&self) -> Option<Vec<&str>> {
+        Some(self.get(&TagKey::Genre)).filter(|v| !v.is_empty())
+    }
+    fn set_genres(&mut self, genres: &[&str]) {
+        self.set(&TagKey::Genre, genres);
+    }
+    fn add_genre(&mut self, genre: &str) {
+        self.push(&TagKey::Genre, genre);
+    }
+
     fn comment(&self) -> Option<&str>;
     fn set_comment(&mut self, genre: String);
     fn remove_comment(&mut self);
+
+    fn lyrics(&self) -> Option<Lyrics<'_>>;
+    fn set_lyrics(&mut self, lyrics: Lyrics);
+    fn remove_lyrics(&mut self);
+
+    /// By default, the lyrics parsed as LRC text, for formats with no field for synchronised
+    /// lyrics (MP4, APE).
+    fn synced_lyrics(&self) -> Option<SyncedLyrics> {
+        let lyrics = self.lyrics()?;
+        let mut synced = SyncedLyrics::from_lrc(&lyrics.text)?;
+        if synced.lang.is_empty() {
+            synced.lang = lyrics.lang.into_owned();
+        }
+        synced.description = lyrics.description.into_owned();
+        Some(synced)
+    }
+    /// By default, replace the lyrics with the LRC text of `lyrics`.
+    fn set_synced_lyrics(&mut self, lyrics: &SyncedLyrics) {
+        let lrc = lyrics.to_lrc();
+        self.set_lyrics(
+            Lyrics::new(lrc)
+                .with_lang(lyrics.lang.as_str())
+                .with_description(lyrics.description.as_str()),
+        );
+    }
+    fn remove_synced_lyrics(&mut self) {
+        if self.synced_lyrics().is_some() {
+            self.remove_lyrics();
+        }
+    }
+
+    fn replay_gain(&self) -> Option<ReplayGain>;
+    /// Replace the ReplayGain values: the `None` ones are removed.
+    fn set_replay_gain(&mut self, replay_gain: ReplayGain);
+    fn remove_replay_gain(&mut self);
+
+    /// The sort order of a field, e.g. `"Beatles, The"` for the artist `"The Beatles"`.
+    fn sort_order(&self, field: SortField) -> Option<&str>;
+    fn set_sort_order(&mut self, field: SortField, v: &str);
+    fn remove_sort_order(&mut self, field: SortField);
+
+    fn title_sort(&self) -> Option<&str> {
+        self.sort_order(SortField::Title)
+    }
+    fn set_title_sort(&mut self, v: &str) {
+        self.set_sort_order(SortField::Title, v);
+    }
+    fn remove_title_sort(&mut self) {
+        self.remove_sort_order(SortField::Title);
+    }
+
+    fn artist_sort(&self) -> Option<&str> {
+        self.sort_order(SortField::Artist)
+    }
+    fn set_artist_sort(&mut self, v: &str) {
+        self.set_sort_order(SortField::Artist, v);
+    }
+    fn remove_artist_sort(&mut self) {
+        self.remove_sort_order(SortField::Artist);
+    }
+
+    fn album_sort(&self) -> Option<&str> {
+        self.sort_order(SortField::Album)
+    }
+    fn set_album_sort(&mut self, v: &str) {
+        self.set_sort_order(SortField::Album, v);
+    }
+    fn remove_album_sort(&mut self) {
+        self.remove_sort_order(SortField::Album);
+    }
+
+    fn album_artist_sort(&self) -> Option<&str> {
+        self.sort_order(SortField::AlbumArtist)
+    }
+    fn set_album_artist_sort(&mut self, v: &str) {
+        self.set_sort_order(SortField::AlbumArtist, v);
+    }
+    fn remove_album_artist_sort(&mut self) {
+        self.remove_sort_order(SortField::AlbumArtist);
+    }
+
+    fn composer_sort(&self) -> Option<&str> {
+        self.sort_order(SortField::Composer)
+    }
+    fn set_composer_sort(&mut self, v: &str) {
+        self.set_sort_order(SortField::Composer, v);
+    }
+    fn remove_composer_sort(&mut self) {
+        self.remove_sort_order(SortField::Composer);
+    }
+
+    /// One of the `TextField`s; see its documentation for the field of each format.
+    fn text_field(&self, field: TextField) -> Option<&str>;
+    fn set_text_field(&mut self, field: TextField, v: &str);
+    fn remove_text_field(&mut self, field: TextField);
+
+    fn isrc(&self) -> Option<&str> {
+        self.text_field(TextField::Isrc)
+    }
+    fn set_isrc(&mut self, v: &str) {
+        self.set_text_field(TextField::Isrc, v);
+    }
+    fn remove_isrc(&mut self) {
+        self.remove_text_field(TextField::Isrc);
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.text_field(TextField::Label)
+    }
+    fn set_label(&mut self, v: &str) {
+        self.set_text_field(TextField::Label, v);
+    }
+    fn remove_label(&mut self) {
+        self.remove_text_field(TextField::Label);
+    }
+
+    fn catalog_number(&self) -> Option<&str> {
+        self.text_field(TextField::CatalogNumber)
+    }
+    fn set_catalog_number(&mut self, v: &str) {
+        self.set_text_field(TextField::CatalogNumber, v);
+    }
+    fn remove_catalog_number(&mut self) {
+        self.remove_text_field(TextField::CatalogNumber);
+    }
+
+    fn copyright(&self) -> Option<&str> {
+        self.text_field(TextField::Copyright)
+    }
+    fn set_copyright(&mut self, v: &str) {
+        self.set_text_field(TextField::Copyright, v);
+    }
+    fn remove_copyright(&mut self) {
+        self.remove_text_field(TextField::Copyright);
+    }
+
+    fn encoded_by(&self) -> Option<&str> {
+        self.text_field(TextField::EncodedBy)
+    }
+    fn set_encoded_by(&mut self, v: &str) {
+        self.set_text_field(TextField::EncodedBy, v);
+    }
+    fn remove_encoded_by(&mut self) {
+        self.remove_text_field(TextField::EncodedBy);
+    }
+
+    fn grouping(&self) -> Option<&str> {
+        self.text_field(TextField::Grouping)
+    }
+    fn set_grouping(&mut self, v: &str) {
+        self.set_text_field(TextField::Grouping, v);
+    }
+    fn remove_grouping(&mut self) {
+        self.remove_text_field(TextField::Grouping);
+    }
+
+    fn mood(&self) -> Option<&str> {
+        self.text_field(TextField::Mood)
+    }
+    fn set_mood(&mut self, v: &str) {
+        self.set_text_field(TextField::Mood, v);
+    }
+    fn remove_mood(&mut self) {
+        self.remove_text_field(TextField::Mood);
+    }
+
+    fn language(&self) -> Option<&str> {
+        self.text_field(TextField::Language)
+    }
+    fn set_language(&mut self, v: &str) {
+        self.set_text_field(TextField::Language, v);
+    }
+    fn remove_language(&mut self) {
+        self.remove_text_field(TextField::Language);
+    }
+
+    fn conductor(&self) -> Option<&str> {
+        self.text_field(TextField::Conductor)
+    }
+    fn set_conductor(&mut self, v: &str) {
+        self.set_text_field(TextField::Conductor, v);
+    }
+    fn remove_conductor(&mut self) {
+        self.remove_text_field(TextField::Conductor);
+    }
+
+    fn lyricist(&self) -> Option<&str> {
+        self.text_field(TextField::Lyricist)
+    }
+    fn set_lyricist(&mut self, v: &str) {
+        self.set_text_field(TextField::Lyricist, v);
+    }
+    fn remove_lyricist(&mut self) {
+        self.remove_text_field(TextField::Lyricist);
+    }
+
+    fn bpm(&self) -> Option<u16>;
+    fn set_bpm(&mut self, bpm: u16);
+    fn remove_bpm(&mut self);
+
+    /// Whether the track is part of a compilation, e.g. of various artists.
+    fn compilation(&self) -> bool;
+    /// Setting `false` removes the flag.
+    fn set_compilation(&mut self, compilation: bool);
+
+    /// The release date of the original recording, e.g. for a remaster.
+    fn original_date(&self) -> Option<Timestamp>;
+    fn set_original_date(&mut self, date: Timestamp);
+    fn remove_original_date(&mut self);
+
+    /// The rating, from 0.0 (worst) to 1.0 (best), scaled from the field of each format:
+    ///
+    /// | Format      | Field                                                   | Range          |
+    /// |-------------|---------------------------------------------------------|----------------|
+    /// | ID3v2       | `POPM` of `Config::popm_email`, or else the first one   | 1–255          |
+    /// | Vorbis, APE | `FMPS_RATING`, or else `RATING`                         | 0.0–1.0, 0–100 |
+    /// | MP4         | `----:com.apple.iTunes:FMPS_RATING`, or else `rtng`     | 0.0–1.0, 1–100 |
+    ///
+    /// A `POPM` of 0 is no rating. iTunes uses `rtng` for its content advisory, so only the values
+    /// it doesn't use (neither 0, 1, 2 nor 4) are read as a rating, and it is never written.
+    fn rating(&self) -> Option<f64>;
+    /// Set the rating, clamped to 0.0–1.0.
+    fn set_rating(&mut self, rating: f64);
+    fn remove_rating(&mut self);
+
+    /// How many times the track was played: the ID3 `PCNT` frame (or else the `POPM` counter), the
+    /// `FMPS_PLAYCOUNT` vorbis comment or APE item, or the MP4
+    /// `----:com.apple.iTunes:FMPS_PLAYCOUNT` atom.
+    fn play_count(&self) -> Option<u64>;
+    fn set_play_count(&mut self, count: u64);
+    fn remove_play_count(&mut self);
+
+    /// The values of a field, following the mapping documented on `TagKey`.
+    fn get(&self, key: &TagKey) -> Vec<&str>;
+    /// Replace the values of a field; an empty slice removes it.
+    fn set(&mut self, key: &TagKey, values: &[&str]);
+    /// Add a value to a field.
+    fn push(&mut self, key: &TagKey, value: &str) {
+        let mut values: Vec<_> = self.get(key).into_iter().map(str::to_owned).collect();
+        values.push(value.to_owned());
+        self.set(key, &values.iter().map(String::as_str).collect::<Vec<_>>());
+    }
+    /// Remove a field.
+    fn remove_key(&mut self, key: &TagKey) {
+        self.set(key, &[]);
+    }
+
+    /// Every item of the tag, including fields without accessor: one per text value, number,
+    /// binary data or picture. Items are keyed with the standard `TagKey`s where they apply,
+    /// `Custom` for user-defined text fields, and `Native` for anything else, e.g. an unknown ID3
+    /// frame or the pictures.
+    fn items(&self) -> Vec<(TagKey, Value<'_>)>;
+
+    /// The values of a user-defined field: an ID3 `TXXX` frame, a vorbis comment or APE item, or
+    /// an MP4 `----:<mean>:name` atom with the mean of `Config::mp4_freeform_mean`. Names are
+    /// matched in any case.
+    fn custom_field(&self, name: &str) -> Vec<&str> {
+        self.get(&TagKey::Custom(name.to_owned()))
+    }
+    /// Replace the values of a user-defined field; an empty slice removes it.
+    fn set_custom_field(&mut self, name: &str, values: &[&str]) {
+        self.set(&TagKey::Custom(name.to_owned()), values);
+    }
+    fn remove_custom_field(&mut self, name: &str) {
+        self.set(&TagKey::Custom(name.to_owned()), &[]);
+    }
+    /// Every user-defined field as `(name, value)` pairs, one per value, i.e. the `Custom` items.
+    fn custom_fields(&self) -> Vec<(&str, &str)>;
+
+    /// The values of a MusicBrainz identifier: usually one, or one per artist.
+    fn musicbrainz_ids(&self, id: MusicBrainzId) -> Vec<&str>;
+    /// Replace the values of a MusicBrainz identifier; an empty slice removes it.
+    fn set_musicbrainz_ids(&mut self, id: MusicBrainzId, values: &[&str]);
+    fn musicbrainz_id(&self, id: MusicBrainzId) -> Option<&str> {
+        self.musicbrainz_ids(id).into_iter().next()
+    }
+    fn set_musicbrainz_id(&mut self, id: MusicBrainzId, value: &str) {
+        self.set_musicbrainz_ids(id, &[value]);
+    }
+    fn remove_musicbrainz_id(&mut self, id: MusicBrainzId) {
+        self.set_musicbrainz_ids(id, &[]);
+    }
+    /// Every MusicBrainz identifier of the tag, in the order of `MusicBrainzId::ALL`.
+    fn all_musicbrainz_ids(&self) -> Vec<(MusicBrainzId, &str)> {
+        MusicBrainzId::ALL
+            .into_iter()
+            .flat_map(|id| self.musicbrainz_ids(id).into_iter().map(move |v| (id, v)))
+            .collect()
+    }
 }
 
 pub trait AudioTagWrite {
-    fn write_to(&mut self, file: &mut File) -> crate::Result<()>;
+    /// Write the tag into the audio file held by `file`, e.g. a `File` or a `Cursor<Vec<u8>>`.
+    fn write_to(&mut self, file: &mut dyn Storage) -> crate::Result<()>;
     // cannot use impl AsRef<Path>
     fn write_to_path(&mut self, path: &str) -> crate::Result<()>;
+    /// Same as `write_to_path`, for paths that are not `&str`: call it with `path.as_ref()`.
+    fn write_to_file_path(&mut self, path: &Path) -> crate::Result<()> {
+        match path.to_str() {
+            Some(path) => self.write_to_path(path),
+            None => self.write_to(&mut File::options().read(true).write(true).open(path)?),
+        }
+    }
+    /// Return a copy of the audio file `input` with the tag written into it.
+    fn write_to_vec(&mut self, input: &[u8]) -> crate::Result<Vec<u8>> {
+        let mut cursor = Cursor::new(input.to_vec());
+        self.write_to(&mut cursor)?;
+        Ok(cursor.into_inner())
+    }
+}
+
+/// Where `AudioTagWrite::write_to` writes: a `Read + Write + Seek` audio file which can also be
+/// resized.
+pub trait Storage: Read + Write + Seek {
+    /// Truncate or extend the storage to `len` bytes, like `File::set_len`.
+    fn set_len(&mut self, len: u64) -> std::io::Result<()>;
+    /// The `File` behind the storage, if any. Some backends can only write to files.
+    fn as_file(&mut self) -> Option<&mut File> {
+        None
+    }
+}
+
+impl Storage for File {
+    fn set_len(&mut self, len: u64) -> std::io::Result<()> {
+        File::set_len(self, len)
+    }
+    fn as_file(&mut self) -> Option<&mut File> {
+        Some(self)
+    }
+}
+
+impl Storage for Cursor<Vec<u8>> {
+    fn set_len(&mut self, len: u64) -> std::io::Result<()> {
+        self.get_mut().resize(len as usize, 0);
+        Ok(())
+    }
+}
+
+impl Storage for Cursor<&mut Vec<u8>> {
+    fn set_len(&mut self, len: u64) -> std::io::Result<()> {
+        self.get_mut().resize(len as usize, 0);
+        Ok(())
+    }
+}
+
+impl<S: Storage + ?Sized> Storage for &mut S {
+    fn set_len(&mut self, len: u64) -> std::io::Result<()> {
+        (**self).set_len(len)
+    }
+    fn as_file(&mut self) -> Option<&mut File> {
+        (**self).as_file()
+    }
+}
+
+/// Call `write` with the `File` behind `storage`, or else with a temporary copy of `storage`, which
+/// is copied back afterwards.
+pub(crate) fn write_via_file(
+    storage: &mut dyn Storage,
+    write: impl FnOnce(&mut File) -> crate::Result<()>,
+) -> crate::Result<()> {
+    if let Some(file) = storage.as_file() {
+        return write(file);
+    }
+    let mut tmp = tempfile::tempfile()?;
+    storage.seek(SeekFrom::Start(0))?;
+    std::io::copy(storage, &mut tmp)?;
+    tmp.seek(SeekFrom::Start(0))?;
+    write(&mut tmp)?;
+    tmp.seek(SeekFrom::Start(0))?;
+    storage.seek(SeekFrom::Start(0))?;
+    let len = std::io::copy(&mut tmp, storage)?;
+    storage.set_len(len)?;
+    Ok(())
+}
+
+/// Replace the content of `storage` with what `write` produces from it in a temporary file. The
+/// content is streamed rather than held in memory, and `storage` is only overwritten once `write`
+/// succeeded.
+pub(crate) fn rewrite_via_tempfile(
+    storage: &mut dyn Storage,
+    write: impl FnOnce(&mut dyn Storage, &mut File) -> crate::Result<()>,
+) -> crate::Result<()> {
+    let mut tmp = tempfile::tempfile()?;
+    write(storage, &mut tmp)?;
+    tmp.seek(SeekFrom::Start(0))?;
+    storage.seek(SeekFrom::Start(0))?;
+    let len = std::io::copy(&mut tmp, storage)?;
+    storage.set_len(len)?;
+    Ok(())
 }
 
 pub trait AudioTagConfig {
@@ -165,10 +596,23 @@ pub trait ToAnyTag: ToAny {
         // TODO: target type is the same, just return self
         match tag_type {
             TagType::Id3v2 => Box::new(Id3v2Tag::from(self.to_anytag())),
+            TagType::Id3v1 => Box::new(Id3v1Tag::from(self.to_anytag())),
             TagType::Mp4 => Box::new(Mp4Tag::from(self.to_anytag())),
             TagType::Flac => Box::new(FlacTag::from(self.to_anytag())),
+            TagType::Vorbis => Box::new(OggTag::from(self.to_anytag())),
+            TagType::Opus => Box::new(OpusTag::from(self.to_anytag())),
+            TagType::Ape => Box::new(ApeTag::from(self.to_anytag())),
+            TagType::Wav => Box::new(WavTag::from(self.to_anytag())),
+            TagType::Aiff => Box::new(AiffTag::from(self.to_anytag())),
         }
     }
+
+    /// Convert the tag type like `to_dyn_tag`, also reporting the fields that were lost on the way.
+    fn to_dyn_tag_with_report(&self, tag_type: TagType) -> (Box<dyn AudioTag>, ConversionReport) {
+        let tag = self.to_dyn_tag(tag_type);
+        let report = ConversionReport::compare(&self.to_anytag(), &tag.to_anytag());
+        (tag, report)
+    }
 }
 
 pub trait ToAny {
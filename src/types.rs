@@ -1,43 +1,83 @@
 pub use super::*;
+use std::borrow::Cow;
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MimeType {
     Png,
     Jpeg,
     Tiff,
     Bmp,
     Gif,
+    Webp,
+    Avif,
+    /// Any other `image/...` mime type, in lowercase
+    Other(String),
+}
+
+impl MimeType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            MimeType::Jpeg => "image/jpeg",
+            MimeType::Png => "image/png",
+            MimeType::Tiff => "image/tiff",
+            MimeType::Bmp => "image/bmp",
+            MimeType::Gif => "image/gif",
+            MimeType::Webp => "image/webp",
+            MimeType::Avif => "image/avif",
+            MimeType::Other(mime) => mime,
+        }
+    }
+    /// Detect the format of an image from its magic bytes.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        crate::image::detect(data)
+    }
+    /// The format detected from `data`, or else parsed from `mime`. Tags often have a wrong or
+    /// missing mime type, so the data is trusted first.
+    pub fn guess(mime: &str, data: &[u8]) -> Option<Self> {
+        Self::detect(data).or_else(|| mime.try_into().ok())
+    }
 }
 
+/// Parse a mime type leniently: case and parameters are ignored and common aliases (`image/jpg`,
+/// `image/x-ms-bmp`, or bare extensions as in ID3v2.2) are accepted.
 impl TryFrom<&str> for MimeType {
     type Error = crate::Error;
     fn try_from(inp: &str) -> crate::Result<Self> {
-        Ok(match inp {
-            "image/jpeg" => MimeType::Jpeg,
-            "image/png" => MimeType::Png,
-            "image/tiff" => MimeType::Tiff,
-            "image/bmp" => MimeType::Bmp,
-            "image/gif" => MimeType::Gif,
-            _ => return Err(crate::Error::UnsupportedMimeType(inp.to_owned())),
+        let mime = inp
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        Ok(match mime.as_str() {
+            "image/jpeg" | "image/jpg" | "image/pjpeg" | "jpeg" | "jpg" => MimeType::Jpeg,
+            "image/png" | "image/x-png" | "png" => MimeType::Png,
+            "image/tiff" | "image/tif" | "tiff" | "tif" => MimeType::Tiff,
+            "image/bmp" | "image/x-bmp" | "image/x-ms-bmp" | "bmp" => MimeType::Bmp,
+            "image/gif" | "gif" => MimeType::Gif,
+            "image/webp" | "webp" => MimeType::Webp,
+            "image/avif" | "avif" => MimeType::Avif,
+            _ => match mime.strip_prefix("image/") {
+                Some(subtype) if !subtype.is_empty() => MimeType::Other(mime),
+                _ => return Err(crate::Error::UnsupportedMimeType(inp.to_owned())),
+            },
         })
     }
 }
 
-impl From<MimeType> for &'static str {
+impl From<MimeType> for String {
     fn from(mt: MimeType) -> Self {
         match mt {
-            MimeType::Jpeg => "image/jpeg",
-            MimeType::Png => "image/png",
-            MimeType::Tiff => "image/tiff",
-            MimeType::Bmp => "image/bmp",
-            MimeType::Gif => "image/gif",
+            MimeType::Other(mime) => mime,
+            mt => mt.as_str().to_owned(),
         }
     }
 }
 
-impl From<MimeType> for String {
-    fn from(mt: MimeType) -> Self {
-        <MimeType as Into<&'static str>>::into(mt).to_owned()
+impl std::fmt::Display for MimeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
     }
 }
 
@@ -51,6 +91,11 @@ impl<'a> Picture<'a> {
     pub fn new(data: &'a [u8], mime_type: MimeType) -> Self {
         Self { data, mime_type }
     }
+    /// The width and height of the image, read from its header. `None` if the format is not
+    /// recognized or the data is truncated, e.g. for a broken cover.
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        crate::image::dimensions(self.data)
+    }
 }
 
 /// A struct for representing an album for convenience.
@@ -86,28 +131,623 @@ impl<'a> Album<'a> {
     }
 }
 
-// #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
-// pub enum PictureType {
-//     Other,
-//     Icon,
-//     OtherIcon,
-//     CoverFront,
-//     CoverBack,
-//     Leaflet,
-//     Media,
-//     LeadArtist,
-//     Artist,
-//     Conductor,
-//     Band,
-//     Composer,
-//     Lyricist,
-//     RecordingLocation,
-//     DuringRecording,
-//     DuringPerformance,
-//     ScreenCapture,
-//     BrightFish,
-//     Illustration,
-//     BandLogo,
-//     PublisherLogo,
-//     Undefined(u8),
-// }
+/// The type of an embedded picture, as defined for ID3 `APIC` frames and FLAC `PICTURE` blocks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PictureType {
+    Other,
+    Icon,
+    OtherIcon,
+    CoverFront,
+    CoverBack,
+    Leaflet,
+    Media,
+    LeadArtist,
+    Artist,
+    Conductor,
+    Band,
+    Composer,
+    Lyricist,
+    RecordingLocation,
+    DuringRecording,
+    DuringPerformance,
+    ScreenCapture,
+    BrightFish,
+    Illustration,
+    BandLogo,
+    PublisherLogo,
+    Undefined(u8),
+}
+
+macro_rules! impl_picture_type_conversions {
+    ($($variant:ident),+) => {
+        impl From<id3::frame::PictureType> for PictureType {
+            fn from(inp: id3::frame::PictureType) -> Self {
+                match inp {
+                    $(id3::frame::PictureType::$variant => Self::$variant,)+
+                    id3::frame::PictureType::Undefined(n) => Self::Undefined(n),
+                }
+            }
+        }
+        impl From<PictureType> for id3::frame::PictureType {
+            fn from(inp: PictureType) -> Self {
+                match inp {
+                    $(PictureType::$variant => Self::$variant,)+
+                    PictureType::Undefined(n) => Self::Undefined(n),
+                }
+            }
+        }
+        impl From<metaflac::block::PictureType> for PictureType {
+            fn from(inp: metaflac::block::PictureType) -> Self {
+                match inp {
+                    $(metaflac::block::PictureType::$variant => Self::$variant,)+
+                }
+            }
+        }
+        /// FLAC has no undefined picture types, so `Undefined` becomes `Other`.
+        impl From<PictureType> for metaflac::block::PictureType {
+            fn from(inp: PictureType) -> Self {
+                match inp {
+                    $(PictureType::$variant => Self::$variant,)+
+                    PictureType::Undefined(_) => Self::Other,
+                }
+            }
+        }
+    };
+}
+
+impl_picture_type_conversions!(
+    Other,
+    Icon,
+    OtherIcon,
+    CoverFront,
+    CoverBack,
+    Leaflet,
+    Media,
+    LeadArtist,
+    Artist,
+    Conductor,
+    Band,
+    Composer,
+    Lyricist,
+    RecordingLocation,
+    DuringRecording,
+    DuringPerformance,
+    ScreenCapture,
+    BrightFish,
+    Illustration,
+    BandLogo,
+    PublisherLogo
+);
+
+/// A picture embedded in a tag, e.g. an ID3 `APIC` frame or a FLAC `PICTURE` block. Unlike
+/// `Picture`, it has a type and a description, and its data can be borrowed from the tag or owned.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EmbeddedPicture<'a> {
+    pub picture_type: PictureType,
+    pub mime_type: MimeType,
+    pub description: Cow<'a, str>,
+    pub data: Cow<'a, [u8]>,
+    /// The width in pixels, `0` if unknown. Only FLAC and vorbis comments store the dimensions.
+    pub width: u32,
+    /// The height in pixels, `0` if unknown
+    pub height: u32,
+    /// The color depth in bits per pixel, `0` if unknown
+    pub depth: u32,
+}
+
+impl<'a> EmbeddedPicture<'a> {
+    pub fn new(
+        picture_type: PictureType,
+        mime_type: MimeType,
+        data: impl Into<Cow<'a, [u8]>>,
+    ) -> Self {
+        Self {
+            picture_type,
+            mime_type,
+            description: Cow::Borrowed(""),
+            data: data.into(),
+            width: 0,
+            height: 0,
+            depth: 0,
+        }
+    }
+    pub fn with_description(mut self, description: impl Into<Cow<'a, str>>) -> Self {
+        self.description = description.into();
+        self
+    }
+    pub fn with_dimensions(mut self, width: u32, height: u32, depth: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self.depth = depth;
+        self
+    }
+    pub fn as_picture(&self) -> Picture<'_> {
+        Picture {
+            data: &self.data,
+            mime_type: self.mime_type.clone(),
+        }
+    }
+    /// The width and height of the image, read from its header (see `Picture::dimensions`).
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        crate::image::dimensions(&self.data)
+    }
+    /// Borrow the data and description of the picture.
+    pub fn as_borrowed(&self) -> EmbeddedPicture<'_> {
+        EmbeddedPicture {
+            picture_type: self.picture_type,
+            mime_type: self.mime_type.clone(),
+            description: Cow::Borrowed(&self.description),
+            data: Cow::Borrowed(&self.data),
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+        }
+    }
+    pub fn into_owned(self) -> EmbeddedPicture<'static> {
+        EmbeddedPicture {
+            description: Cow::Owned(self.description.into_owned()),
+            data: Cow::Owned(self.data.into_owned()),
+            ..self
+        }
+    }
+}
+
+/// The front cover.
+impl<'a> From<Picture<'a>> for EmbeddedPicture<'a> {
+    fn from(inp: Picture<'a>) -> Self {
+        Self::new(PictureType::CoverFront, inp.mime_type, inp.data)
+    }
+}
+
+/// ReplayGain loudness normalization values. Gains are in dB, relative to the ReplayGain
+/// reference level (-18 LUFS for ReplayGain 2.0); peaks are the largest absolute sample value,
+/// where 1.0 is full scale.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayGain {
+    pub track_gain: Option<f64>,
+    pub track_peak: Option<f64>,
+    pub album_gain: Option<f64>,
+    pub album_peak: Option<f64>,
+}
+
+impl ReplayGain {
+    /// The field names used by vorbis comments, APE tags and ID3 `TXXX` frames.
+    pub(crate) const FIELDS: [&'static str; 4] = [
+        "REPLAYGAIN_TRACK_GAIN",
+        "REPLAYGAIN_TRACK_PEAK",
+        "REPLAYGAIN_ALBUM_GAIN",
+        "REPLAYGAIN_ALBUM_PEAK",
+    ];
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Read the `FIELDS` with `get`, e.g. `"-6.50 dB"` and `"0.988831"`. `None` if none of them
+    /// is set (or valid).
+    pub(crate) fn from_fields<'a>(mut get: impl FnMut(&str) -> Option<&'a str>) -> Option<Self> {
+        let [track_gain, track_peak, album_gain, album_peak] = Self::FIELDS;
+        let gain = |v: &str| {
+            let v = v.trim();
+            let v = match v.len().checked_sub(2) {
+                Some(i) if v.is_char_boundary(i) && v[i..].eq_ignore_ascii_case("dB") => &v[..i],
+                _ => v,
+            };
+            v.trim().parse::<f64>().ok().filter(|v| v.is_finite())
+        };
+        let peak = |v: &str| v.trim().parse::<f64>().ok().filter(|v| v.is_finite());
+        let replay_gain = Self {
+            track_gain: get(track_gain).and_then(gain),
+            track_peak: get(track_peak).and_then(peak),
+            album_gain: get(album_gain).and_then(gain),
+            album_peak: get(album_peak).and_then(peak),
+        };
+        Some(replay_gain).filter(|g| !g.is_empty())
+    }
+
+    /// The `FIELDS` and their values, as written by common taggers; `None` for unknown values.
+    pub(crate) fn to_fields(self) -> [(&'static str, Option<String>); 4] {
+        let [track_gain, track_peak, album_gain, album_peak] = Self::FIELDS;
+        let gain = |v: f64| format!("{:.2} dB", v);
+        let peak = |v: f64| format!("{:.6}", v);
+        [
+            (track_gain, self.track_gain.map(gain)),
+            (track_peak, self.track_peak.map(peak)),
+            (album_gain, self.album_gain.map(gain)),
+            (album_peak, self.album_peak.map(peak)),
+        ]
+    }
+}
+
+/// The MusicBrainz identifiers written by MusicBrainz Picard. Artist IDs hold one ID per artist.
+///
+/// ## References
+///
+/// - <https://picard-docs.musicbrainz.org/en/appendices/tag_mapping.html>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MusicBrainzId {
+    /// The recording, called "track ID" in most formats
+    Recording,
+    /// The track on the release
+    Track,
+    /// The release, called "album ID" in most formats
+    Release,
+    ReleaseGroup,
+    Artist,
+    AlbumArtist,
+    Work,
+    /// The disc ID computed from the table of contents of a CD
+    Disc,
+}
+
+impl MusicBrainzId {
+    pub const ALL: [Self; 8] = [
+        Self::Recording,
+        Self::Track,
+        Self::Release,
+        Self::ReleaseGroup,
+        Self::Artist,
+        Self::AlbumArtist,
+        Self::Work,
+        Self::Disc,
+    ];
+
+    /// The vorbis comment name, also used for APE items.
+    pub(crate) fn vorbis_key(self) -> &'static str {
+        match self {
+            Self::Recording => "MUSICBRAINZ_TRACKID",
+            Self::Track => "MUSICBRAINZ_RELEASETRACKID",
+            Self::Release => "MUSICBRAINZ_ALBUMID",
+            Self::ReleaseGroup => "MUSICBRAINZ_RELEASEGROUPID",
+            Self::Artist => "MUSICBRAINZ_ARTISTID",
+            Self::AlbumArtist => "MUSICBRAINZ_ALBUMARTISTID",
+            Self::Work => "MUSICBRAINZ_WORKID",
+            Self::Disc => "MUSICBRAINZ_DISCID",
+        }
+    }
+
+    /// The ID3 `TXXX` description, also used for MP4 freeform atom names. The recording ID is
+    /// stored in an ID3 `UFID` frame instead.
+    pub(crate) fn description(self) -> &'static str {
+        match self {
+            Self::Recording => "MusicBrainz Track Id",
+            Self::Track => "MusicBrainz Release Track Id",
+            Self::Release => "MusicBrainz Album Id",
+            Self::ReleaseGroup => "MusicBrainz Release Group Id",
+            Self::Artist => "MusicBrainz Artist Id",
+            Self::AlbumArtist => "MusicBrainz Album Artist Id",
+            Self::Work => "MusicBrainz Work Id",
+            Self::Disc => "MusicBrainz Disc Id",
+        }
+    }
+}
+
+/// The fields with a sort order, e.g. `"Beatles, The"` for the artist `"The Beatles"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SortField {
+    Title,
+    Artist,
+    Album,
+    AlbumArtist,
+    Composer,
+}
+
+impl SortField {
+    pub const ALL: [Self; 5] = [
+        Self::Title,
+        Self::Artist,
+        Self::Album,
+        Self::AlbumArtist,
+        Self::Composer,
+    ];
+
+    /// The vorbis comment name, also used for APE items.
+    pub(crate) fn vorbis_key(self) -> &'static str {
+        match self {
+            Self::Title => "TITLESORT",
+            Self::Artist => "ARTISTSORT",
+            Self::Album => "ALBUMSORT",
+            Self::AlbumArtist => "ALBUMARTISTSORT",
+            Self::Composer => "COMPOSERSORT",
+        }
+    }
+}
+
+/// Text fields beyond the basic ones, read and written with `AudioTagEdit::text_field` or their
+/// typed accessors (`isrc`, `label`...).
+///
+/// | Field           | ID3v2                | Vorbis comment / APE | MP4                                   |
+/// |-----------------|----------------------|----------------------|---------------------------------------|
+/// | `Isrc`          | `TSRC`               | `ISRC`               | `----:com.apple.iTunes:ISRC`          |
+/// | `Label`         | `TPUB`               | `LABEL`              | `----:com.apple.iTunes:LABEL`         |
+/// | `CatalogNumber` | `TXXX:CATALOGNUMBER` | `CATALOGNUMBER`      | `----:com.apple.iTunes:CATALOGNUMBER` |
+/// | `Copyright`     | `TCOP`               | `COPYRIGHT`          | `cprt`                                |
+/// | `EncodedBy`     | `TENC`               | `ENCODEDBY`          | `©too`                                |
+/// | `Grouping`      | `TIT1`               | `GROUPING`           | `©grp`                                |
+/// | `Mood`          | `TMOO`               | `MOOD`               | `----:com.apple.iTunes:MOOD`          |
+/// | `Language`      | `TLAN`               | `LANGUAGE`           | `----:com.apple.iTunes:LANGUAGE`      |
+/// | `Conductor`     | `TPE3`               | `CONDUCTOR`          | `----:com.apple.iTunes:CONDUCTOR`     |
+/// | `Lyricist`      | `TEXT`               | `LYRICIST`           | `----:com.apple.iTunes:LYRICIST`      |
+///
+/// The typed fields that are not text in every format are mapped the same way:
+///
+/// | Field           | ID3v2                      | Vorbis comment / APE | MP4                                  |
+/// |-----------------|----------------------------|----------------------|--------------------------------------|
+/// | `bpm`           | `TBPM`                     | `BPM`                | `tmpo`                               |
+/// | `compilation`   | `TCMP`                     | `COMPILATION`        | `cpil`                               |
+/// | `original_date` | `TDOR` (`TORY` in ID3v2.3) | `ORIGINALDATE`       | `----:com.apple.iTunes:ORIGINALDATE` |
+///
+/// APE item keys are case-insensitive. ID3v1 has none of these fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextField {
+    /// International Standard Recording Code
+    Isrc,
+    /// The record label or publisher
+    Label,
+    CatalogNumber,
+    Copyright,
+    /// The person or organization that encoded the file
+    EncodedBy,
+    /// The content group, e.g. a work or a set of tracks
+    Grouping,
+    Mood,
+    /// The language of the lyrics, e.g. `"eng"`
+    Language,
+    Conductor,
+    Lyricist,
+}
+
+impl TextField {
+    pub const ALL: [Self; 10] = [
+        Self::Isrc,
+        Self::Label,
+        Self::CatalogNumber,
+        Self::Copyright,
+        Self::EncodedBy,
+        Self::Grouping,
+        Self::Mood,
+        Self::Language,
+        Self::Conductor,
+        Self::Lyricist,
+    ];
+
+    /// The vorbis comment name, also used for APE items and MP4 freeform atoms.
+    pub(crate) fn vorbis_key(self) -> &'static str {
+        match self {
+            Self::Isrc => "ISRC",
+            Self::Label => "LABEL",
+            Self::CatalogNumber => "CATALOGNUMBER",
+            Self::Copyright => "COPYRIGHT",
+            Self::EncodedBy => "ENCODEDBY",
+            Self::Grouping => "GROUPING",
+            Self::Mood => "MOOD",
+            Self::Language => "LANGUAGE",
+            Self::Conductor => "CONDUCTOR",
+            Self::Lyricist => "LYRICIST",
+        }
+    }
+}
+
+/// The vorbis comments read by the accessors of `AudioTagEdit` but not through a `TagKey`
+/// (besides the ReplayGain ones), which are no custom fields.
+const VORBIS_ACCESSOR_KEYS: [&str; 16] = [
+    "YEAR",
+    "TRACKNUMBER",
+    "TOTALTRACKS",
+    "DISCNUMBER",
+    "TOTALDISCS",
+    "UNSYNCEDLYRICS",
+    "SYNCEDLYRICS",
+    "BPM",
+    "COMPILATION",
+    "ORIGINALDATE",
+    "FMPS_RATING",
+    "RATING",
+    "FMPS_PLAYCOUNT",
+    "R128_TRACK_GAIN",
+    "R128_ALBUM_GAIN",
+    "METADATA_BLOCK_PICTURE",
+];
+/// The APE items read by the accessors of `AudioTagEdit` but not through a `TagKey` (besides the
+/// ReplayGain ones), which are no custom fields.
+const APE_ACCESSOR_KEYS: [&str; 8] = [
+    "Track",
+    "Disc",
+    "BPM",
+    "Compilation",
+    "OriginalDate",
+    "FMPS_RATING",
+    "RATING",
+    "FMPS_PLAYCOUNT",
+];
+
+/// A text field of any format, read and written with `AudioTagEdit::{get, set, push, remove}`.
+///
+/// | Key              | ID3v2                | Vorbis comment   | APE             | MP4                            |
+/// |------------------|----------------------|------------------|-----------------|--------------------------------|
+/// | `Title`          | `TIT2`               | `TITLE`          | `Title`         | `©nam`                         |
+/// | `Artist`         | `TPE1`               | `ARTIST`         | `Artist`        | `©ART`                         |
+/// | `Album`          | `TALB`               | `ALBUM`          | `Album`         | `©alb`                         |
+/// | `AlbumArtist`    | `TPE2`               | `ALBUMARTIST`    | `Album Artist`  | `aART`                         |
+/// | `Genre`          | `TCON`               | `GENRE`          | `Genre`         | `©gen`                         |
+/// | `Composer`       | `TCOM`               | `COMPOSER`       | `Composer`      | `©wrt`                         |
+/// | `Comment`        | `COMM`               | `COMMENT`        | `Comment`       | `©cmt`                         |
+/// | `Date`           | `TDRC`               | `DATE`           | `Year`          | `©day`                         |
+/// | `Lyrics`         | `USLT`               | `LYRICS`         | `Lyrics`        | `©lyr`                         |
+/// | `Text(field)`    | see `TextField`      | see `TextField`  | see `TextField` | see `TextField`                |
+/// | `Sort(field)`    | `TSOT`, `TSOP`...    | `TITLESORT`...   | `TITLESORT`...  | `sonm`, `soar`...              |
+/// | `MusicBrainz(id)`| `UFID`, `TXXX`       | `MUSICBRAINZ_*`  | `MUSICBRAINZ_*` | `----:com.apple.iTunes:*`      |
+/// | `Custom(name)`   | `TXXX:name`          | `name`           | `name`          | `----:<mean>:name`             |
+/// | `Native(name)`   | frame `name`         | `name`           | `name`          | atom `name`                    |
+///
+/// ID3 text frames hold multiple values separated by null bytes; the `COMM` and `USLT` frames hold
+/// a single value, so more values are joined with line breaks. ID3v1 only has the title, artist,
+/// album, genre, comment and year (as `Date`). Fields stored as numbers in some format (track
+/// and disc numbers, BPM, ratings...) are only available through their typed accessors. The
+/// `<mean>` of custom MP4 fields is `Config::mp4_freeform_mean`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TagKey {
+    Title,
+    Artist,
+    Album,
+    AlbumArtist,
+    Genre,
+    Composer,
+    Comment,
+    Date,
+    Lyrics,
+    Text(TextField),
+    Sort(SortField),
+    MusicBrainz(MusicBrainzId),
+    /// A user-defined field, see `AudioTagEdit::custom_field`
+    Custom(String),
+    /// A field by its name in the format: an ID3 frame ID (e.g. `TKEY`), a vorbis comment or APE
+    /// item name, or an MP4 atom (e.g. `©mvn` or `----:mean:name`). Only text can be set, i.e.
+    /// ID3 text frames and MP4 atoms; any field can be removed.
+    Native(String),
+}
+
+impl TagKey {
+    /// Every key but `Custom`.
+    pub fn standard() -> impl Iterator<Item = Self> {
+        [
+            Self::Title,
+            Self::Artist,
+            Self::Album,
+            Self::AlbumArtist,
+            Self::Genre,
+            Self::Composer,
+            Self::Comment,
+            Self::Date,
+            Self::Lyrics,
+        ]
+        .into_iter()
+        .chain(TextField::ALL.map(Self::Text))
+        .chain(SortField::ALL.map(Self::Sort))
+        .chain(MusicBrainzId::ALL.map(Self::MusicBrainz))
+    }
+
+    /// The vorbis comment name.
+    pub(crate) fn vorbis_key(&self) -> &str {
+        match self {
+            Self::Title => "TITLE",
+            Self::Artist => "ARTIST",
+            Self::Album => "ALBUM",
+            Self::AlbumArtist => "ALBUMARTIST",
+            Self::Genre => "GENRE",
+            Self::Composer => "COMPOSER",
+            Self::Comment => "COMMENT",
+            Self::Date => "DATE",
+            Self::Lyrics => "LYRICS",
+            Self::Text(field) => field.vorbis_key(),
+            Self::Sort(field) => field.vorbis_key(),
+            Self::MusicBrainz(id) => id.vorbis_key(),
+            Self::Custom(name) | Self::Native(name) => name,
+        }
+    }
+
+    /// The key of a vorbis comment: a standard key, `Native` if another accessor reads it, or
+    /// else `Custom`.
+    pub(crate) fn from_vorbis_key(name: &str) -> Self {
+        Self::from_name(name, Self::vorbis_key, &VORBIS_ACCESSOR_KEYS)
+    }
+
+    /// The key of an APE item: a standard key, `Native` if another accessor reads it, or else
+    /// `Custom`.
+    pub(crate) fn from_ape_key(name: &str) -> Self {
+        Self::from_name(name, Self::ape_key, &APE_ACCESSOR_KEYS)
+    }
+
+    fn from_name(name: &str, key: impl Fn(&Self) -> &str, accessor_keys: &[&str]) -> Self {
+        if let Some(standard) = Self::standard().find(|k| key(k).eq_ignore_ascii_case(name)) {
+            standard
+        } else if accessor_keys
+            .iter()
+            .chain(&ReplayGain::FIELDS)
+            .any(|k| k.eq_ignore_ascii_case(name))
+        {
+            Self::Native(name.to_owned())
+        } else {
+            Self::Custom(name.to_owned())
+        }
+    }
+
+    /// The APE item key, which differs from the vorbis comment name for some standard fields.
+    pub(crate) fn ape_key(&self) -> &str {
+        match self {
+            Self::Title => "Title",
+            Self::Artist => "Artist",
+            Self::Album => "Album",
+            Self::AlbumArtist => "Album Artist",
+            Self::Genre => "Genre",
+            Self::Composer => "Composer",
+            Self::Comment => "Comment",
+            Self::Date => "Year",
+            Self::Lyrics => "Lyrics",
+            _ => self.vorbis_key(),
+        }
+    }
+}
+
+impl From<TextField> for TagKey {
+    fn from(field: TextField) -> Self {
+        Self::Text(field)
+    }
+}
+
+impl From<SortField> for TagKey {
+    fn from(field: SortField) -> Self {
+        Self::Sort(field)
+    }
+}
+
+impl From<MusicBrainzId> for TagKey {
+    fn from(id: MusicBrainzId) -> Self {
+        Self::MusicBrainz(id)
+    }
+}
+
+/// The value of an item listed by `AudioTagEdit::items`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value<'a> {
+    Text(Cow<'a, str>),
+    /// An integer, e.g. the MP4 `tmpo` atom or the ID3 `PCNT` frame
+    Number(i64),
+    Binary(&'a [u8]),
+    Picture(EmbeddedPicture<'a>),
+}
+
+/// Parse a BPM stored as text, which some taggers write with decimals.
+pub(crate) fn parse_bpm(v: &str) -> Option<u16> {
+    let bpm = v.trim().parse::<f64>().ok()?.round();
+    (0.0..=f64::from(u16::MAX))
+        .contains(&bpm)
+        .then_some(bpm as u16)
+}
+
+/// Parse a flag stored as text, e.g. `COMPILATION=1`.
+pub(crate) fn parse_flag(v: &str) -> bool {
+    let v = v.trim();
+    v == "1" || v.eq_ignore_ascii_case("true")
+}
+
+/// Parse a rating stored as a fraction of `scale`, e.g. `RATING=80` with a scale of 100.
+pub(crate) fn parse_rating(v: &str, scale: f64) -> Option<f64> {
+    let rating = v.trim().parse::<f64>().ok()? / scale;
+    (0.0..=1.0).contains(&rating).then_some(rating)
+}
+
+/// Parse a play count, which `FMPS_PLAYCOUNT` allows to be fractional.
+pub(crate) fn parse_count(v: &str) -> Option<u64> {
+    let v = v.trim();
+    v.parse().ok().or_else(|| {
+        let count = v.parse::<f64>().ok()?;
+        (count >= 0.0).then_some(count.round() as u64)
+    })
+}
@@ -1,6 +1,7 @@
 use audiotags::*;
 use id3::TagLike;
 use std::fs;
+use std::str::FromStr;
 use tempfile::Builder;
 
 #[test]
@@ -56,3 +57,796 @@ fn test_inner() {
     assert_eq!(id3tag_reload.artist(), Some(artist));
     assert_eq!(id3tag_reload.album_artist(), Some(album_artist));
 }
+
+#[test]
+fn test_conversion_report() {
+    let mut tag = FlacTag::new();
+    tag.set_title("foo title");
+    tag.set_artist("foo artist");
+    tag.set_date(id3::Timestamp::from_str("2020-05-22").unwrap());
+    tag.set_genre("Rock");
+    tag.set_composer("foo composer".to_owned());
+    tag.set_comment("foo comment".to_owned());
+    let cover = Picture {
+        mime_type: MimeType::Png,
+        data: &[0u8; 10],
+    };
+    tag.set_album_cover(cover.clone());
+
+    for tag_type in [TagType::Id3v2, TagType::Ape, TagType::Vorbis] {
+        let (converted, report) = tag.to_dyn_tag_with_report(tag_type);
+        assert!(report.is_lossless(), "{:?}: {:?}", tag_type, report);
+        assert_eq!(converted.composer(), Some("foo composer"));
+        assert_eq!(converted.comment(), Some("foo comment"));
+        assert_eq!(converted.album_cover(), Some(cover.clone()));
+    }
+
+    let (converted, report) = tag.to_dyn_tag_with_report(TagType::Mp4);
+    assert!(report.is_lossless(), "{:?}", report);
+    assert_eq!(converted.genre(), Some("Rock"));
+
+    let (_, report) = tag.to_dyn_tag_with_report(TagType::Id3v1);
+    let lost = |field| {
+        report
+            .lost
+            .iter()
+            .find(|l| l.field == field)
+            .map(|l| &l.reason)
+    };
+    assert_eq!(lost("composers"), Some(&LossReason::Unsupported));
+    assert_eq!(lost("album_cover"), Some(&LossReason::Unsupported));
+    assert_eq!(
+        lost("date"),
+        Some(&LossReason::Altered {
+            source: "2020-05-22".to_owned(),
+            target: "2020".to_owned()
+        })
+    );
+    assert_eq!(lost("title"), None);
+    assert_eq!(lost("genres"), None);
+
+    tag.set_album_cover(Picture {
+        mime_type: MimeType::Bmp,
+        data: &[0u8; 10],
+    });
+    let (_, report) = tag.to_dyn_tag_with_report(TagType::Mp4);
+    assert!(report.is_lossless(), "{:?}", report);
+
+    tag.set_album_cover(Picture {
+        mime_type: MimeType::Tiff,
+        data: &[0u8; 10],
+    });
+    let (_, report) = tag.to_dyn_tag_with_report(TagType::Mp4);
+    assert_eq!(report.lost.len(), 1);
+    assert_eq!(report.lost[0].field, "album_cover");
+}
+
+#[test]
+fn test_owned_tag() {
+    let mut tag = Id3v2Tag::new();
+    tag.set_title("foo title");
+    tag.set_artist("foo artist");
+    tag.set_date(id3::Timestamp::from_str("2020-05-22").unwrap());
+    tag.set_album_cover(Picture {
+        mime_type: MimeType::Png,
+        data: &[1u8; 10],
+    });
+
+    let owned = std::thread::spawn(move || OwnedTag::from(tag.to_anytag()))
+        .join()
+        .unwrap();
+    assert_eq!(owned.title.as_deref(), Some("foo title"));
+    assert_eq!(owned.artists, Some(vec!["foo artist".to_owned()]));
+    assert_eq!(
+        owned.album_cover,
+        Some(OwnedPicture {
+            mime_type: MimeType::Png,
+            data: vec![1u8; 10],
+        })
+    );
+
+    let flac = FlacTag::from(&owned);
+    assert_eq!(flac.title(), Some("foo title"));
+    assert_eq!(flac.date(), owned.date);
+    let (mp4, report) = owned.to_dyn_tag_with_report(TagType::Mp4);
+    assert!(report.is_lossless(), "{:?}", report);
+    assert_eq!(mp4.artist(), Some("foo artist"));
+    assert_eq!(
+        OwnedTag::from(mp4.to_anytag()).album_cover,
+        owned.album_cover
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_owned_tag_serde() {
+    let mut owned = OwnedTag::new();
+    owned.title = Some("foo title".to_owned());
+    owned.date = Some(id3::Timestamp::from_str("2020-05-22").unwrap());
+    owned.album_cover = Some(OwnedPicture {
+        mime_type: MimeType::Jpeg,
+        data: vec![1, 2, 3],
+    });
+
+    let json = serde_json::to_string(&owned).unwrap();
+    assert!(json.contains(r#""date":"2020-05-22""#), "{}", json);
+    assert_eq!(serde_json::from_str::<OwnedTag>(&json).unwrap(), owned);
+    let partial: OwnedTag = serde_json::from_str(r#"{"title":"foo"}"#).unwrap();
+    assert_eq!(partial.title.as_deref(), Some("foo"));
+    assert!(serde_json::from_str::<OwnedTag>(r#"{"date":"not a date"}"#).is_err());
+}
+
+#[test]
+fn test_picture_conversion() {
+    let mut tag = FlacTag::new();
+    let front = EmbeddedPicture::new(PictureType::CoverFront, MimeType::Png, vec![1u8; 10]);
+    let back = EmbeddedPicture::new(PictureType::CoverBack, MimeType::Jpeg, vec![2u8; 10])
+        .with_description("foo back");
+    tag.add_picture(front.clone());
+    tag.add_picture(back.clone());
+
+    for tag_type in [TagType::Id3v2, TagType::Ape, TagType::Opus] {
+        let (converted, report) = tag.to_dyn_tag_with_report(tag_type);
+        assert!(report.is_lossless(), "{:?}: {:?}", tag_type, report);
+        assert_eq!(converted.pictures(), vec![front.clone(), back.clone()]);
+    }
+
+    let (converted, report) = tag.to_dyn_tag_with_report(TagType::Mp4);
+    assert_eq!(converted.pictures().len(), 2);
+    assert_eq!(
+        report.lost,
+        vec![LostField {
+            field: "pictures",
+            reason: LossReason::Altered {
+                source: "CoverBack: image/jpeg (10 bytes)".to_owned(),
+                target: "CoverFront: image/jpeg (10 bytes)".to_owned(),
+            }
+        }]
+    );
+
+    let owned = OwnedTag::from(tag.to_anytag());
+    assert_eq!(owned.pictures.len(), 2);
+    assert_eq!(Id3v2Tag::from(&owned).pictures()[1], back);
+}
+
+#[test]
+fn test_mime_type() {
+    assert_eq!(MimeType::try_from("image/JPG").unwrap(), MimeType::Jpeg);
+    assert_eq!(MimeType::try_from("PNG").unwrap(), MimeType::Png);
+    assert_eq!(MimeType::try_from("image/x-ms-bmp").unwrap(), MimeType::Bmp);
+    assert_eq!(MimeType::try_from("image/webp").unwrap(), MimeType::Webp);
+    assert_eq!(
+        MimeType::try_from("image/heic; foo=bar").unwrap(),
+        MimeType::Other("image/heic".to_owned())
+    );
+    assert_eq!(
+        MimeType::Other("image/heic".to_owned()).as_str(),
+        "image/heic"
+    );
+    assert!(MimeType::try_from("-->").is_err());
+    assert!(MimeType::try_from("image/").is_err());
+
+    let png = [
+        &b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR"[..],
+        &300u32.to_be_bytes(),
+        &200u32.to_be_bytes(),
+    ]
+    .concat();
+    let gif = b"GIF89a\x2c\x01\xc8\x00".to_vec();
+    let bmp = [
+        &b"BM"[..],
+        &[0; 12],
+        &40u32.to_le_bytes(),
+        &300i32.to_le_bytes(),
+        &(-200i32).to_le_bytes(),
+    ]
+    .concat();
+    let jpeg = [
+        &b"\xff\xd8\xff\xe0\x00\x10"[..],
+        &[0; 14],
+        &b"\xff\xc0\x00\x11\x08\x00\xc8\x01\x2c"[..],
+    ]
+    .concat();
+    let tiff = [
+        &b"II*\0\x08\0\0\0\x02\0"[..],
+        &[0, 1, 3, 0, 1, 0, 0, 0, 0x2c, 1, 0, 0],
+        &[1, 1, 4, 0, 1, 0, 0, 0, 0xc8, 0, 0, 0],
+    ]
+    .concat();
+    let webp = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\0\0\0\0\x2b\x01\0\xc7\0\0".to_vec();
+    let avif = [
+        &b"\0\0\0\x14ftypavif\0\0\0\0mif1\0\0\0\x14ispe\0\0\0\0"[..],
+        &300u32.to_be_bytes(),
+        &200u32.to_be_bytes(),
+    ]
+    .concat();
+    for (data, mime_type) in [
+        (png, MimeType::Png),
+        (gif, MimeType::Gif),
+        (bmp, MimeType::Bmp),
+        (jpeg, MimeType::Jpeg),
+        (tiff, MimeType::Tiff),
+        (webp, MimeType::Webp),
+        (avif, MimeType::Avif),
+    ] {
+        assert_eq!(MimeType::detect(&data), Some(mime_type.clone()));
+        let picture = Picture::new(&data, mime_type.clone());
+        assert_eq!(picture.dimensions(), Some((300, 200)), "{:?}", mime_type);
+        // truncated
+        let truncated = &data[..data.len() - 1];
+        assert_eq!(Picture::new(truncated, mime_type).dimensions(), None);
+    }
+    assert_eq!(MimeType::detect(&[0; 10]), None);
+    assert_eq!(
+        MimeType::guess("image/jpeg", b"GIF89a\x2c\x01\xc8\x00"),
+        Some(MimeType::Gif)
+    );
+    assert_eq!(MimeType::guess("image/jpg", &[0; 10]), Some(MimeType::Jpeg));
+
+    // covers with a lenient mime type, or none, are read
+    let mut inner = id3::Tag::new();
+    inner.add_frame(id3::frame::Picture {
+        mime_type: "image/jpg".to_owned(),
+        picture_type: id3::frame::PictureType::CoverFront,
+        description: String::new(),
+        data: vec![0; 10],
+    });
+    inner.add_frame(id3::frame::Picture {
+        mime_type: String::new(),
+        picture_type: id3::frame::PictureType::CoverBack,
+        description: String::new(),
+        data: b"GIF89a\x2c\x01\xc8\x00".to_vec(),
+    });
+    let tag = Id3v2Tag::from(inner);
+    assert_eq!(tag.album_cover().unwrap().mime_type, MimeType::Jpeg);
+    assert_eq!(tag.pictures()[1].mime_type, MimeType::Gif);
+
+    // FLAC pictures get the dimensions of the image
+    let mut flac = FlacTag::new();
+    flac.add_picture(tag.pictures()[1].clone());
+    assert_eq!(flac.pictures()[0].width, 300);
+    assert_eq!(flac.pictures()[0].height, 200);
+}
+
+#[test]
+fn test_lrc() {
+    let lrc = "[ar:foo]\n[la:eng]\n[offset:500]\n[00:12.00][01:00.5]Line one\n[00:15.30]Line two\nplain\n";
+    let lyrics = SyncedLyrics::from_lrc(lrc).unwrap();
+    assert_eq!(lyrics.lang, "eng");
+    assert_eq!(
+        lyrics.lines,
+        vec![
+            (11500, "Line one".to_owned()),
+            (14800, "Line two".to_owned()),
+            (60000, "Line one".to_owned()),
+        ]
+    );
+    assert_eq!(
+        lyrics.to_lrc(),
+        "[la:eng]\n[00:11.50]Line one\n[00:14.80]Line two\n[01:00.00]Line one\n"
+    );
+    assert_eq!(
+        SyncedLyrics::from_lrc(&lyrics.to_lrc()),
+        Some(lyrics.clone())
+    );
+    assert!(SyncedLyrics::from_lrc("foo\nbar").is_none());
+    assert_eq!(
+        SyncedLyrics::from_lrc("[00:01.234]foo").unwrap().lines,
+        vec![(1234, "foo".to_owned())]
+    );
+
+    let tmp = Builder::new().suffix(".lrc").tempfile().unwrap();
+    lyrics.write_lrc(tmp.path()).unwrap();
+    assert_eq!(SyncedLyrics::read_lrc(tmp.path()).unwrap(), lyrics);
+    fs::write(tmp.path(), "foo").unwrap();
+    assert!(matches!(
+        SyncedLyrics::read_lrc(tmp.path()),
+        Err(Error::LrcError(_))
+    ));
+}
+
+#[test]
+fn test_lyrics_conversion() {
+    let lyrics = Lyrics::new("foo\nbar")
+        .with_lang("eng")
+        .with_description("desc");
+    let synced = SyncedLyrics {
+        lang: "eng".to_owned(),
+        description: "desc".to_owned(),
+        lines: vec![(1000, "foo".to_owned()), (2000, "bar".to_owned())],
+    };
+    let mut id3tag = Id3v2Tag::new();
+    id3tag.set_lyrics(lyrics.clone());
+    id3tag.set_synced_lyrics(&synced);
+    assert_eq!(id3tag.lyrics(), Some(lyrics.clone()));
+    assert_eq!(id3tag.synced_lyrics(), Some(synced.clone()));
+
+    // FLAC keeps both, but neither the language nor the description
+    let flactag: FlacTag = id3tag.to_anytag().into();
+    assert_eq!(flactag.lyrics().unwrap().text, "foo\nbar");
+    assert_eq!(flactag.synced_lyrics().unwrap().lines, synced.lines);
+
+    // MP4 keeps only one of them
+    let (mp4tag, report) = flactag.to_dyn_tag_with_report(TagType::Mp4);
+    assert_eq!(mp4tag.synced_lyrics().unwrap().lines, synced.lines);
+    assert!(report.lost.iter().any(|l| l.field == "lyrics"));
+    assert!(!report.lost.iter().any(|l| l.field == "synced_lyrics"));
+
+    let id3tag = mp4tag.to_dyn_tag(TagType::Id3v2);
+    assert_eq!(id3tag.synced_lyrics().unwrap().lines, synced.lines);
+}
+
+#[test]
+fn test_replay_gain() {
+    let mut flactag = FlacTag::new();
+    flactag.set_first("REPLAYGAIN_TRACK_GAIN", "+1.5 DB");
+    flactag.set_first("REPLAYGAIN_TRACK_PEAK", " 0.5 ");
+    flactag.set_first("REPLAYGAIN_ALBUM_GAIN", "foo");
+    let replay_gain = ReplayGain {
+        track_gain: Some(1.5),
+        track_peak: Some(0.5),
+        ..ReplayGain::default()
+    };
+    assert_eq!(flactag.replay_gain(), Some(replay_gain));
+    flactag.set_replay_gain(replay_gain);
+    assert_eq!(flactag.get_first("REPLAYGAIN_TRACK_GAIN"), Some("1.50 dB"));
+    assert_eq!(flactag.get_first("REPLAYGAIN_TRACK_PEAK"), Some("0.500000"));
+    assert_eq!(flactag.get_first("REPLAYGAIN_ALBUM_GAIN"), None);
+
+    // RVA2: identification, then master volume channel, -6.5 dB, 16-bit peak of 0.5
+    let rva2 = |identification: &[u8], gain: i16| {
+        let mut data = identification.to_vec();
+        data.push(0);
+        data.push(1);
+        data.extend_from_slice(&gain.to_be_bytes());
+        data.extend_from_slice(&[16, 0x40, 0x00]);
+        id3::Frame::with_content(
+            "RVA2",
+            id3::Content::Unknown(id3::frame::Unknown {
+                data,
+                version: id3::Version::Id3v24,
+            }),
+        )
+    };
+    let mut inner = id3::Tag::new();
+    inner.add_frame(rva2(b"track", -6 * 512 - 256));
+    inner.add_frame(rva2(b"album", 512));
+    let mut id3tag = Id3v2Tag::from(inner);
+    let from_rva2 = ReplayGain {
+        track_gain: Some(-6.5),
+        track_peak: Some(0.5),
+        album_gain: Some(1.0),
+        album_peak: Some(0.5),
+    };
+    assert_eq!(id3tag.replay_gain(), Some(from_rva2));
+    id3tag.set_replay_gain(replay_gain);
+    assert_eq!(id3tag.replay_gain(), Some(replay_gain));
+
+    // Opus stores R128 gains, relative to -23 LUFS instead of -18 LUFS
+    let mut opustag: OpusTag = id3tag.to_anytag().into();
+    assert_eq!(opustag.r128_track_gain(), Some(-3.5));
+    // a tag not read from a file has no duration, whatever its output gain
+    opustag.set_output_gain(-1.0);
+    assert_eq!(opustag.duration(), None);
+    let (_, report) = id3tag.to_dyn_tag_with_report(TagType::Opus);
+    assert!(report.lost.iter().any(|l| l.field == "replay_gain"));
+    let (mp4tag, report) = id3tag.to_dyn_tag_with_report(TagType::Mp4);
+    assert!(report.is_lossless(), "{:?}", report);
+    assert_eq!(mp4tag.replay_gain(), Some(replay_gain));
+    assert!(id3::Tag::from(id3tag).get("RVA2").is_none());
+}
+
+#[test]
+fn test_musicbrainz_conversion() {
+    // as written by Picard to ID3v2.3
+    let mut inner = id3::Tag::new();
+    inner.add_frame(id3::frame::UniqueFileIdentifier {
+        owner_identifier: "http://musicbrainz.org".to_owned(),
+        identifier: b"d6118046-407d-4e06-a1ba-49c399a4c42f".to_vec(),
+    });
+    inner.add_frame(id3::frame::ExtendedText {
+        description: "MusicBrainz Artist Id".to_owned(),
+        value: "5b11f4ce-a62d-471e-81fc-a69a8278c7da/ba0d6274-db14-4ef5-b28d-657ebde1a396"
+            .to_owned(),
+    });
+    inner.add_frame(id3::frame::ExtendedText {
+        description: "MUSICBRAINZ ALBUM ID".to_owned(),
+        value: "f5093c06-23e3-404f-aeaa-40f72885ee3a".to_owned(),
+    });
+    let id3tag = Id3v2Tag::from(inner);
+    let artists = vec![
+        "5b11f4ce-a62d-471e-81fc-a69a8278c7da",
+        "ba0d6274-db14-4ef5-b28d-657ebde1a396",
+    ];
+    assert_eq!(id3tag.musicbrainz_ids(MusicBrainzId::Artist), artists);
+    assert_eq!(
+        id3tag.musicbrainz_id(MusicBrainzId::Release),
+        Some("f5093c06-23e3-404f-aeaa-40f72885ee3a")
+    );
+
+    for tag_type in [TagType::Flac, TagType::Mp4, TagType::Ape, TagType::Opus] {
+        let (converted, report) = id3tag.to_dyn_tag_with_report(tag_type);
+        assert!(report.is_lossless(), "{:?}: {:?}", tag_type, report);
+        assert_eq!(
+            converted.musicbrainz_id(MusicBrainzId::Recording),
+            Some("d6118046-407d-4e06-a1ba-49c399a4c42f")
+        );
+        assert_eq!(converted.musicbrainz_ids(MusicBrainzId::Artist), artists);
+        // and back
+        let back = converted.to_dyn_tag(TagType::Id3v2);
+        assert_eq!(back.all_musicbrainz_ids(), id3tag.all_musicbrainz_ids());
+    }
+
+    let flactag: FlacTag = id3tag.to_anytag().into();
+    assert_eq!(
+        flactag.get_first("MUSICBRAINZ_TRACKID"),
+        Some("d6118046-407d-4e06-a1ba-49c399a4c42f")
+    );
+    let (_, report) = flactag.to_dyn_tag_with_report(TagType::Id3v1);
+    assert!(report.lost.iter().any(|l| l.field == "musicbrainz_ids"));
+}
+
+#[test]
+fn test_sort_order_conversion() {
+    let mut inner = id3::Tag::new();
+    inner.set_text("TSOP", "Beatles, The");
+    inner.set_text("TSO2", "Beatles, The");
+    let id3tag = Id3v2Tag::from(inner);
+    assert_eq!(id3tag.artist_sort(), Some("Beatles, The"));
+
+    let flactag: FlacTag = id3tag.to_anytag().into();
+    assert_eq!(flactag.get_first("ARTISTSORT"), Some("Beatles, The"));
+    assert_eq!(flactag.get_first("ALBUMARTISTSORT"), Some("Beatles, The"));
+
+    for tag_type in [TagType::Mp4, TagType::Ape, TagType::Vorbis, TagType::Id3v2] {
+        let (converted, report) = flactag.to_dyn_tag_with_report(tag_type);
+        assert!(report.is_lossless(), "{:?}: {:?}", tag_type, report);
+        assert_eq!(converted.album_artist_sort(), Some("Beatles, The"));
+    }
+    let (_, report) = flactag.to_dyn_tag_with_report(TagType::Id3v1);
+    assert!(report.lost.iter().any(|l| l.field == "artist_sort"));
+}
+
+#[test]
+fn test_extended_fields_conversion() {
+    let mut inner = id3::Tag::new();
+    inner.set_text("TBPM", "120");
+    inner.set_text("TCMP", "1");
+    inner.set_text("TORY", "1965");
+    inner.set_text("TPUB", "Parlophone");
+    inner.add_frame(id3::frame::ExtendedText {
+        description: "catalognumber".to_owned(),
+        value: "PMC 1255".to_owned(),
+    });
+    let id3tag = Id3v2Tag::from(inner);
+    assert_eq!(id3tag.bpm(), Some(120));
+    assert!(id3tag.compilation());
+    assert_eq!(id3tag.original_date().map(|d| d.year), Some(1965));
+    assert_eq!(id3tag.label(), Some("Parlophone"));
+    assert_eq!(id3tag.catalog_number(), Some("PMC 1255"));
+
+    let mut flactag: FlacTag = id3tag.to_anytag().into();
+    assert_eq!(flactag.get_first("BPM"), Some("120"));
+    assert_eq!(flactag.get_first("COMPILATION"), Some("1"));
+    assert_eq!(flactag.get_first("ORIGINALDATE"), Some("1965"));
+    assert_eq!(flactag.get_first("LABEL"), Some("Parlophone"));
+    assert_eq!(flactag.get_first("CATALOGNUMBER"), Some("PMC 1255"));
+
+    // some taggers write fractional BPMs
+    flactag.set_first("BPM", "119.6");
+    assert_eq!(flactag.bpm(), Some(120));
+
+    for tag_type in [TagType::Mp4, TagType::Ape, TagType::Vorbis, TagType::Id3v2] {
+        let (converted, report) = flactag.to_dyn_tag_with_report(tag_type);
+        assert!(report.is_lossless(), "{:?}: {:?}", tag_type, report);
+        assert_eq!(converted.catalog_number(), Some("PMC 1255"));
+        assert!(converted.compilation());
+    }
+    let (_, report) = flactag.to_dyn_tag_with_report(TagType::Id3v1);
+    for field in ["bpm", "compilation", "original_date", "label"] {
+        assert!(report.lost.iter().any(|l| l.field == field), "{}", field);
+    }
+}
+
+#[test]
+fn test_rating_and_play_count_conversion() {
+    let mut inner = id3::Tag::new();
+    inner.add_frame(id3::frame::Popularimeter {
+        user: "other@player".to_owned(),
+        rating: 255,
+        counter: 7,
+    });
+    let mut id3tag = Id3v2Tag::from(inner);
+    id3tag.set_config(Config::default().popm_email("me@player"));
+    // the frames of other users are read when there is none of `popm_email`
+    assert_eq!(id3tag.rating(), Some(1.0));
+    assert_eq!(id3tag.play_count(), Some(7));
+
+    id3tag.set_rating(0.0);
+    id3tag.set_play_count(1000);
+    assert_eq!(id3tag.rating(), Some(0.0));
+    assert_eq!(id3tag.play_count(), Some(1000));
+    let inner = id3::Tag::from(id3tag);
+    let popms: Vec<_> = inner
+        .frames()
+        .filter_map(|f| f.content().popularimeter())
+        .map(|p| (p.user.as_str(), p.rating, p.counter))
+        .collect();
+    assert_eq!(popms, [("me@player", 1, 0), ("other@player", 255, 7)]);
+    match inner.get("PCNT").unwrap().content() {
+        id3::Content::Unknown(pcnt) => assert_eq!(pcnt.data, [0, 0, 3, 232]),
+        content => panic!("{:?}", content),
+    }
+
+    let mut flactag = FlacTag::new();
+    flactag.set_first("RATING", "80");
+    flactag.set_first("FMPS_PLAYCOUNT", "12.0");
+    assert_eq!(flactag.rating(), Some(0.8));
+    assert_eq!(flactag.play_count(), Some(12));
+
+    for tag_type in [TagType::Mp4, TagType::Ape, TagType::Vorbis] {
+        let (converted, report) = flactag.to_dyn_tag_with_report(tag_type);
+        assert!(report.is_lossless(), "{:?}: {:?}", tag_type, report);
+        assert_eq!(converted.rating(), Some(0.8));
+        assert_eq!(converted.play_count(), Some(12));
+    }
+    // `POPM` has 255 steps
+    let (converted, report) = flactag.to_dyn_tag_with_report(TagType::Id3v2);
+    assert_eq!(converted.play_count(), Some(12));
+    assert_eq!(report.lost.len(), 1);
+    assert_eq!(report.lost[0].field, "rating");
+}
+
+#[test]
+fn test_mp4_rating() {
+    // `rtng` is mostly the iTunes content advisory
+    for (rtng, rating) in [(4, None), (2, None), (0, None), (80, Some(0.8))] {
+        let mut inner = mp4ameta::Tag::default();
+        inner.set_data(
+            mp4ameta::ident::ADVISORY_RATING,
+            mp4ameta::Data::BeSigned(vec![rtng]),
+        );
+        assert_eq!(Mp4Tag::from(inner).rating(), rating, "{}", rtng);
+    }
+
+    let mut inner = mp4ameta::Tag::default();
+    inner.set_data(
+        mp4ameta::ident::ADVISORY_RATING,
+        mp4ameta::Data::BeSigned(vec![1]),
+    );
+    let mut mp4tag = Mp4Tag::from(inner);
+    mp4tag.set_rating(0.6);
+    assert_eq!(mp4tag.rating(), Some(0.6));
+    let inner = mp4ameta::Tag::from(mp4tag);
+    assert_eq!(
+        inner
+            .strings_of(&mp4ameta::FreeformIdent::new(
+                "com.apple.iTunes",
+                "FMPS_RATING"
+            ))
+            .collect::<Vec<_>>(),
+        ["0.6"]
+    );
+    assert_eq!(
+        inner.bytes_of(&mp4ameta::ident::ADVISORY_RATING).next(),
+        Some(&[1][..])
+    );
+
+    let mut mp4tag = Mp4Tag::from(inner);
+    mp4tag.remove_rating();
+    assert!(mp4tag.rating().is_none());
+}
+
+#[test]
+fn test_tag_keys() {
+    let artist = TagKey::Artist;
+    let custom = TagKey::Custom("ASSET_ID".to_owned());
+    let mut tags: Vec<Box<dyn AudioTag>> = vec![
+        Box::new(Id3v2Tag::new()),
+        Box::new(Mp4Tag::new()),
+        Box::new(FlacTag::new()),
+        Box::new(ApeTag::new()),
+    ];
+    for tag in &mut tags {
+        tag.push(&artist, "Simon");
+        tag.push(&artist, "Garfunkel");
+        tag.set(&custom, &["1", "2"]);
+        assert_eq!(tag.get(&artist), ["Simon", "Garfunkel"]);
+        assert_eq!(tag.get(&custom), ["1", "2"]);
+    }
+
+    let mut id3tag = Id3v2Tag::new();
+    id3tag.set(&artist, &["Simon", "Garfunkel"]);
+    id3tag.set(&custom, &["1", "2"]);
+    let inner = id3::Tag::from(id3tag);
+    assert_eq!(
+        inner.get("TPE1").unwrap().content().text(),
+        Some("Simon\0Garfunkel")
+    );
+    assert_eq!(inner.extended_texts().next().unwrap().value, "1\u{0}2");
+    let mut id3tag = Id3v2Tag::from(inner);
+    id3tag.set(&TagKey::Comment, &["first", "second"]);
+    assert_eq!(id3tag.get(&TagKey::Comment), ["first\nsecond"]);
+
+    let mut flactag = FlacTag::new();
+    flactag.set(&TagKey::Text(TextField::CatalogNumber), &["PMC 1255"]);
+    assert_eq!(flactag.get_first("CATALOGNUMBER"), Some("PMC 1255"));
+    flactag.remove_key(&TagKey::Text(TextField::CatalogNumber));
+    assert!(flactag.catalog_number().is_none());
+    flactag.set_first("CATALOGNUMBER", "PMC 1255");
+    // the inherent `remove` still takes a vorbis comment name
+    flactag.remove("CATALOGNUMBER");
+    assert!(flactag.catalog_number().is_none());
+
+    let mut apetag = ApeTag::new();
+    apetag.set(&TagKey::AlbumArtist, &["Simon & Garfunkel"]);
+    assert_eq!(apetag.get_first("Album Artist"), Some("Simon & Garfunkel"));
+
+    let mut id3v1tag = Id3v1Tag::new();
+    id3v1tag.set(&artist, &["Simon", "Garfunkel"]);
+    id3v1tag.set(&TagKey::Date, &["1970-01-26"]);
+    assert_eq!(id3v1tag.get(&artist), ["Simon;Garfunkel"]);
+    assert_eq!(id3v1tag.year(), Some(1970));
+    assert!(id3v1tag.get(&custom).is_empty());
+}
+
+#[test]
+fn test_items() {
+    let mut inner = id3::Tag::new();
+    inner.set_text("TKEY", "Am");
+    inner.set_text("TRCK", "3/12");
+    inner.add_frame(id3::frame::ExtendedText {
+        description: "MusicBrainz Album Id".to_owned(),
+        value: "a1".to_owned(),
+    });
+    inner.add_frame(id3::frame::ExtendedText {
+        description: "ASSET_ID".to_owned(),
+        value: "1\u{0}2".to_owned(),
+    });
+    inner.add_frame(id3::Frame::with_content(
+        "PCNT",
+        id3::Content::Unknown(id3::frame::Unknown {
+            data: vec![0, 0, 1, 0],
+            version: id3::Version::Id3v24,
+        }),
+    ));
+    let id3tag = Id3v2Tag::from(inner);
+    let items = id3tag.items();
+    let native = |name: &str| TagKey::Native(name.to_owned());
+    let custom = TagKey::Custom("ASSET_ID".to_owned());
+    assert_eq!(
+        items,
+        [
+            (native("TKEY"), Value::Text("Am".into())),
+            (native("TRCK"), Value::Text("3/12".into())),
+            (
+                TagKey::MusicBrainz(MusicBrainzId::Release),
+                Value::Text("a1".into())
+            ),
+            (custom.clone(), Value::Text("1".into())),
+            (custom.clone(), Value::Text("2".into())),
+            (native("PCNT"), Value::Number(256)),
+        ]
+    );
+    assert_eq!(id3tag.get(&native("TKEY")), ["Am"]);
+
+    let mut mp4tag = Mp4Tag::new();
+    mp4tag.set_track((3, 12));
+    mp4tag.set_bpm(120);
+    mp4tag.set(&native("----:com.example:ID"), &["x"]);
+    mp4tag.set(&native("©mvn"), &["Movement"]);
+    let items = mp4tag.items();
+    assert!(items.contains(&(native("trkn"), Value::Text("3/12".into()))));
+    assert!(items.contains(&(native("tmpo"), Value::Number(120))));
+    assert!(items.contains(&(native("----:com.example:ID"), Value::Text("x".into()))));
+    assert!(items.contains(&(native("©mvn"), Value::Text("Movement".into()))));
+    assert_eq!(mp4tag.get(&native("----:com.example:ID")), ["x"]);
+    assert!(mp4tag.get(&native("not an atom")).is_empty());
+
+    let mut flactag = FlacTag::new();
+    flactag.set_first("TITLE", "foo");
+    flactag.set_first("WHATEVER", "bar");
+    assert_eq!(
+        flactag.items(),
+        [
+            (TagKey::Title, Value::Text("foo".into())),
+            (
+                TagKey::Custom("WHATEVER".to_owned()),
+                Value::Text("bar".into())
+            ),
+        ]
+    );
+
+    let mut inner = ApeInnerTag::default();
+    inner.set_item(ApeItem::text("Album Artist", "foo"));
+    inner.set_item(ApeItem::binary("Data", vec![1, 2]));
+    let apetag = ApeTag::from(inner);
+    assert_eq!(
+        apetag.items(),
+        [
+            (TagKey::AlbumArtist, Value::Text("foo".into())),
+            (TagKey::Native("Data".to_owned()), Value::Binary(&[1, 2])),
+        ]
+    );
+}
+
+#[test]
+fn test_custom_fields_conversion() {
+    let mut inner = id3::Tag::new();
+    inner.add_frame(id3::frame::ExtendedText {
+        description: "ASSET_ID".to_owned(),
+        value: "1234\u{0}5678".to_owned(),
+    });
+    inner.add_frame(id3::frame::ExtendedText {
+        description: "REPLAYGAIN_TRACK_GAIN".to_owned(),
+        value: "-6.50 dB".to_owned(),
+    });
+    let id3tag = Id3v2Tag::from(inner);
+    assert_eq!(
+        id3tag.custom_fields(),
+        vec![("ASSET_ID", "1234"), ("ASSET_ID", "5678")]
+    );
+
+    for tag_type in [TagType::Flac, TagType::Mp4, TagType::Ape, TagType::Opus] {
+        let (converted, report) = id3tag.to_dyn_tag_with_report(tag_type);
+        assert!(report.is_lossless(), "{:?}: {:?}", tag_type, report);
+        assert_eq!(converted.custom_field("asset_id"), vec!["1234", "5678"]);
+        // the ReplayGain is carried by `replay_gain`
+        assert_eq!(converted.custom_fields().len(), 2, "{:?}", tag_type);
+        // and back
+        let back = converted.to_dyn_tag(TagType::Id3v2);
+        assert_eq!(back.custom_fields(), id3tag.custom_fields());
+    }
+
+    let mut mp4tag = Mp4Tag::default();
+    mp4tag.set_config(Config::default().mp4_freeform_mean("com.example"));
+    mp4tag.set_custom_field("ASSET_ID", &["1234"]);
+    let anytag = mp4tag.to_anytag();
+    assert_eq!(anytag.custom_fields, vec![("ASSET_ID", "1234")]);
+
+    let flactag: FlacTag = id3tag.to_anytag().into();
+    assert_eq!(flactag.get_first("ASSET_ID"), Some("1234"));
+    let (_, report) = flactag.to_dyn_tag_with_report(TagType::Id3v1);
+    assert!(report.lost.iter().any(|l| l.field == "custom_fields"));
+}
+
+#[test]
+fn test_multiple_values_conversion() {
+    let mut inner = id3::Tag::with_version(id3::Version::Id3v24);
+    inner.set_text_values("TPE1", ["Simon", "Garfunkel"]);
+    inner.set_text_values("TCON", ["Folk", "Rock"]);
+    inner.set_text_values("TCOM", ["Paul Simon", "Art Garfunkel"]);
+    let id3tag = Id3v2Tag::from(inner);
+    assert_eq!(id3tag.artist(), Some("Simon"));
+    assert_eq!(id3tag.artists(), Some(vec!["Simon", "Garfunkel"]));
+
+    for tag_type in [TagType::Flac, TagType::Mp4, TagType::Ape, TagType::Opus] {
+        let (converted, report) = id3tag.to_dyn_tag_with_report(tag_type);
+        assert!(report.is_lossless(), "{:?}: {:?}", tag_type, report);
+        assert_eq!(converted.artists(), Some(vec!["Simon", "Garfunkel"]));
+        assert_eq!(converted.genres(), Some(vec!["Folk", "Rock"]));
+        assert_eq!(
+            converted.composers(),
+            Some(vec!["Paul Simon", "Art Garfunkel"])
+        );
+        // and back
+        let back: id3::Tag = Id3v2Tag::from(converted.to_anytag()).into();
+        assert_eq!(
+            back.get("TPE1").unwrap().content().text(),
+            Some("Simon\0Garfunkel")
+        );
+    }
+
+    // repeated vorbis comments and MP4 atoms
+    let flactag: FlacTag = id3tag.to_anytag().into();
+    assert_eq!(flactag.get_first("ARTIST"), Some("Simon"));
+    assert_eq!(flactag.get(&TagKey::Artist), ["Simon", "Garfunkel"]);
+    let mp4tag: Mp4Tag = id3tag.to_anytag().into();
+    let inner = mp4ameta::Tag::from(mp4tag);
+    assert_eq!(inner.artists().collect::<Vec<_>>(), ["Simon", "Garfunkel"]);
+
+    let (id3v1tag, report) = id3tag.to_dyn_tag_with_report(TagType::Id3v1);
+    assert_eq!(id3v1tag.artist(), Some("Simon;Garfunkel"));
+    assert_eq!(id3v1tag.artists(), Some(vec!["Simon", "Garfunkel"]));
+    assert!(report.lost.iter().any(|l| l.field == "genres"));
+}
@@ -1,7 +1,13 @@
-use audiotags::{MimeType, Picture, Tag};
-use id3::Timestamp;
+use audiotags::{
+    AiffInnerTag, AiffTag, ApeInnerTag, ApeTag, AudioTagConfig, AudioTagEdit, AudioTagWrite,
+    Config, EmbeddedPicture, FlacTag, Id3v1Tag, Id3v1Write, Id3v2Tag, Id3v2Version, Lyrics,
+    MimeType, Mp4Tag, MusicBrainzId, OpusTag, Picture, PictureType, ReplayGain, SortField,
+    SyncedLyrics, Tag, TagKey, TagType, TextField, ToAnyTag, Value, WavInnerTag, WavTag,
+};
+use id3::{TagLike, Timestamp};
 use std::ffi::OsString;
 use std::fs;
+use std::io::Cursor;
 use std::path::Path;
 use std::str::FromStr;
 use tempfile::Builder;
@@ -86,3 +92,1396 @@ macro_rules! test_file {
 test_file!(test_mp3, "assets/a.mp3");
 test_file!(test_m4a, "assets/a.m4a");
 test_file!(test_flac, "assets/a.flac");
+test_file!(test_ogg, "assets/a.ogg");
+test_file!(test_opus, "assets/a.opus");
+test_file!(test_ape, "assets/a.ape");
+test_file!(test_wav, "assets/a.wav");
+test_file!(test_aiff, "assets/a.aiff");
+
+#[test]
+fn test_ogg_write() {
+    let tmp = Builder::new().suffix(".ogg").tempfile().unwrap();
+    fs::copy("assets/a.ogg", &tmp).unwrap();
+    let tmp_path = tmp.path();
+
+    // large enough for the comment header to span several pages
+    let data = vec![7u8; 100_000];
+    let cover = Picture {
+        mime_type: MimeType::Png,
+        data: &data,
+    };
+    let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+    let duration = tags.duration();
+    assert!(duration.is_some());
+    tags.set_title("foo title");
+    tags.set_album_cover(cover.clone());
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+    let tags = Tag::default().read_from_path(tmp_path).unwrap();
+    assert_eq!(tags.title(), Some("foo title"));
+    assert_eq!(tags.album_cover(), Some(cover));
+    assert_eq!(tags.duration(), duration);
+}
+
+#[test]
+fn test_ogg_write_in_place() {
+    for file in ["assets/a.ogg", "assets/a.opus"] {
+        let bytes = fs::read(file).unwrap();
+        let mut tags = Tag::new().read_from(Cursor::new(&bytes)).unwrap();
+        tags.set_title("foo title");
+        let mut cursor = Cursor::new(bytes);
+        tags.write_to(&mut cursor).unwrap();
+        let written = cursor.get_ref().clone();
+
+        // a comment header of the same size only overwrites the header pages
+        tags.set_title("bar title");
+        tags.write_to(&mut cursor).unwrap();
+        let rewritten = cursor.get_ref();
+        assert_eq!(rewritten.len(), written.len(), "{}", file);
+        let differing = (0..written.len())
+            .filter(|&i| written[i] != rewritten[i])
+            .max()
+            .unwrap();
+        assert!(differing < 4096, "{}", file);
+
+        let tags = Tag::new().read_from(Cursor::new(rewritten)).unwrap();
+        assert_eq!(tags.title(), Some("bar title"), "{}", file);
+        assert_eq!(
+            tags.duration(),
+            Tag::new().read_from_path(file).unwrap().duration(),
+            "{}",
+            file
+        );
+    }
+}
+
+#[test]
+fn test_opus_write() {
+    let tmp = Builder::new().suffix(".opus").tempfile().unwrap();
+    fs::copy("assets/a.opus", &tmp).unwrap();
+    let tmp_path = tmp.path();
+
+    let mut tags = OpusTag::read_from_path(tmp_path).unwrap();
+    assert_eq!(tags.duration(), Some(1.0));
+    assert_eq!(tags.output_gain(), Some(0.0));
+    tags.set_title("foo title");
+    tags.set_output_gain(-3.5);
+    tags.set_r128_track_gain(1.25);
+    tags.set_r128_album_gain(-0.5);
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+    let tags = OpusTag::read_from_path(tmp_path).unwrap();
+    assert_eq!(tags.title(), Some("foo title"));
+    assert_eq!(tags.output_gain(), Some(-3.5));
+    assert_eq!(tags.r128_track_gain(), Some(1.25));
+    assert_eq!(tags.get_first("R128_TRACK_GAIN"), Some("320"));
+    assert_eq!(tags.r128_album_gain(), Some(-0.5));
+    assert_eq!(tags.duration(), Some(1.0));
+}
+
+#[test]
+fn test_ape_write() {
+    let tmp = Builder::new().suffix(".ape").tempfile().unwrap();
+    fs::copy("assets/a.ape", &tmp).unwrap();
+    let tmp_path = tmp.path();
+    // the APE tag goes before an ID3v1 tag
+    let mut id3v1 = b"TAG".to_vec();
+    id3v1.resize(128, 0);
+    let mut audio = fs::read(tmp_path).unwrap();
+    audio.extend(&id3v1);
+    fs::write(tmp_path, &audio).unwrap();
+
+    let cover = Picture {
+        mime_type: MimeType::Png,
+        data: b"\x89PNG\r\n\x1a\nfoo",
+    };
+    let mut tags = ApeTag::read_from_path(tmp_path).unwrap();
+    assert!(tags.duration().is_some());
+    tags.set_title("foo title");
+    tags.set_track((3, 12));
+    tags.set_album_cover(cover.clone());
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+    // writing again replaces the tag instead of appending another one
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+    let tags = ApeTag::read_from_path(tmp_path).unwrap();
+    assert_eq!(tags.title(), Some("foo title"));
+    assert_eq!(tags.get_first("Track"), Some("3/12"));
+    assert_eq!(tags.album_cover(), Some(cover));
+    let written = fs::read(tmp_path).unwrap();
+    assert!(written.ends_with(&id3v1));
+    let tag_len = ApeInnerTag::from(tags).to_bytes().len();
+    assert_eq!(written.len(), audio.len() + tag_len);
+}
+
+#[test]
+fn test_ape_item_count() {
+    // a footer-only tag claiming 2^32 - 1 items
+    let mut bytes = b"APETAGEX".to_vec();
+    for field in [2000u32, 32, u32::MAX, 0, 0, 0] {
+        bytes.extend(field.to_le_bytes());
+    }
+    assert!(ApeTag::read_from(&mut Cursor::new(bytes)).is_err());
+}
+
+#[test]
+fn test_id3v1() {
+    let tmp = Builder::new().suffix(".mp3").tempfile().unwrap();
+    fs::copy("assets/a.mp3", &tmp).unwrap();
+    let tmp_path = tmp.path();
+
+    let mut tags = Id3v2Tag::read_from_path(tmp_path).unwrap();
+    tags.set_config(Config::default().id3v1_write(Id3v1Write::Update));
+    tags.set_title("a title that is too long to fit in an id3v1 tag");
+    tags.set_artist("foo artist");
+    tags.set_year(1999);
+    tags.set_track_number(7);
+    tags.set_genre("Rock");
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+    let v1 = Id3v1Tag::read_from_path(tmp_path).unwrap();
+    assert_eq!(v1.title(), Some("a title that is too long to fi"));
+    assert_eq!(v1.artist(), Some("foo artist"));
+    assert_eq!(v1.year(), Some(1999));
+    assert_eq!(v1.track_number(), Some(7));
+    assert_eq!(v1.genre(), Some("Rock"));
+    assert!(v1.album_title().is_none());
+
+    // without ID3v2 tag, reading fails unless falling back to ID3v1
+    id3::Tag::remove_from_path(tmp_path).unwrap();
+    assert!(Tag::new().read_from_path(tmp_path).is_err());
+    let tags = Tag::new()
+        .with_config(Config::default().id3v1_fallback(true))
+        .read_from_path(tmp_path)
+        .unwrap();
+    assert_eq!(tags.artist(), Some("foo artist"));
+
+    let mut tags = Id3v2Tag::new();
+    tags.set_config(Config::default().id3v1_write(Id3v1Write::Strip));
+    tags.set_title("foo title");
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+    assert!(Id3v1Tag::read_from_path(tmp_path).is_err());
+}
+
+#[test]
+fn test_id3v23_write() {
+    let tmp = Builder::new().suffix(".mp3").tempfile().unwrap();
+    fs::copy("assets/a.mp3", &tmp).unwrap();
+    let tmp_path = tmp.path();
+
+    let mut tags = Id3v2Tag::read_from_path(tmp_path).unwrap();
+    tags.set_config(Config::default().id3v2_version(Id3v2Version::Id3v23));
+    tags.set_date(Timestamp::from_str("2020-05-22T10:30").unwrap());
+    tags.set_artist("foo\0bar");
+    tags.set_musicbrainz_ids(MusicBrainzId::Artist, &["foo", "bar"]);
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+    let inner = id3::Tag::read_from_path(tmp_path).unwrap();
+    assert_eq!(inner.version(), Id3v2Version::Id3v23);
+    assert!(inner
+        .extended_texts()
+        .any(|t| t.description == "MusicBrainz Artist Id" && t.value == "foo\0bar"));
+    assert!(inner.get("TDRC").is_none());
+    assert_eq!(
+        inner.get("TYER").and_then(|f| f.content().text()),
+        Some("2020")
+    );
+    assert_eq!(
+        inner.get("TDAT").and_then(|f| f.content().text()),
+        Some("2205")
+    );
+    assert_eq!(
+        inner.get("TIME").and_then(|f| f.content().text()),
+        Some("1030")
+    );
+    assert_eq!(inner.artist(), Some("foo\0bar"));
+    let utf16 = "foo/bar"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<_>>();
+    assert!(fs::read(tmp_path)
+        .unwrap()
+        .windows(utf16.len())
+        .any(|w| w == utf16 || w.starts_with(b"foo/bar")));
+
+    // the version the file was read with is kept by default
+    let mut tags = Id3v2Tag::read_from_path(tmp_path).unwrap();
+    tags.set_title("foo title");
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+    let inner = id3::Tag::read_from_path(tmp_path).unwrap();
+    assert_eq!(inner.version(), Id3v2Version::Id3v23);
+    assert_eq!(inner.title(), Some("foo title"));
+    assert_eq!(
+        tags.musicbrainz_ids(MusicBrainzId::Artist),
+        vec!["foo", "bar"]
+    );
+
+    let mut tags = Id3v2Tag::read_from_path(tmp_path).unwrap();
+    tags.set_config(Config::default().id3v2_version(Id3v2Version::Id3v24));
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+    let inner = id3::Tag::read_from_path(tmp_path).unwrap();
+    assert_eq!(inner.version(), Id3v2Version::Id3v24);
+}
+
+#[test]
+fn test_id3v23_multiple_values() {
+    let tmp = Builder::new().suffix(".mp3").tempfile().unwrap();
+    fs::copy("assets/a.mp3", &tmp).unwrap();
+    let tmp_path = tmp.path();
+
+    let mut tags = Id3v2Tag::read_from_path(tmp_path).unwrap();
+    tags.set_config(Config::default().id3v2_version(Id3v2Version::Id3v23));
+    tags.set_artists(&["AC/DC", "B"]);
+    tags.set_custom_field("FOO", &["x", "y"]);
+    tags.set_custom_field("BAR", &["a/b"]);
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+    let tags = Id3v2Tag::read_from_path(tmp_path).unwrap();
+    // `/` separates the values of ID3v2.3 text frames
+    assert_eq!(tags.artists(), Some(vec!["AC", "DC", "B"]));
+    assert_eq!(tags.custom_field("FOO"), ["x", "y"]);
+    assert_eq!(tags.custom_field("BAR"), ["a/b"]);
+}
+
+#[test]
+fn test_id3v23_dates() {
+    let tmp = Builder::new().suffix(".mp3").tempfile().unwrap();
+    fs::copy("assets/a.mp3", &tmp).unwrap();
+    let tmp_path = tmp.path();
+    let date = Timestamp::from_str("2020-05-22T10:30").unwrap();
+
+    let mut tags = Id3v2Tag::read_from_path(tmp_path).unwrap();
+    tags.set_config(Config::default().id3v2_version(Id3v2Version::Id3v23));
+    tags.set_date(date);
+    tags.set_original_date(Timestamp::from_str("1999").unwrap());
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+    let inner = id3::Tag::read_from_path(tmp_path).unwrap();
+    for id in ["TYER", "TDAT", "TIME", "TORY"] {
+        assert!(inner.get(id).is_some(), "{id}");
+    }
+    assert!(inner.get("TDRC").is_none());
+    assert!(inner.get("TDOR").is_none());
+
+    let mut tags = Id3v2Tag::read_from_path(tmp_path).unwrap();
+    assert_eq!(tags.date(), Some(date));
+    assert_eq!(tags.year(), Some(2020));
+    assert_eq!(tags.original_date().map(|d| d.year), Some(1999));
+
+    tags.set_config(Config::default().id3v2_version(Id3v2Version::Id3v24));
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+    let inner = id3::Tag::read_from_path(tmp_path).unwrap();
+    assert_eq!(inner.version(), Id3v2Version::Id3v24);
+    for id in ["TYER", "TDAT", "TIME", "TORY"] {
+        assert!(inner.get(id).is_none(), "{id}");
+    }
+    assert_eq!(inner.date_recorded(), Some(date));
+    assert_eq!(
+        inner.original_date_released(),
+        Some(Timestamp::from_str("1999").unwrap())
+    );
+}
+
+#[test]
+fn test_id3_set_year() {
+    let tmp = Builder::new().suffix(".mp3").tempfile().unwrap();
+    fs::copy("assets/a.mp3", &tmp).unwrap();
+    let tmp_path = tmp.path();
+
+    let mut tags = Id3v2Tag::read_from_path(tmp_path).unwrap();
+    tags.set_date(Timestamp::from_str("2000-01-02").unwrap());
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+    for version in [Id3v2Version::Id3v24, Id3v2Version::Id3v23] {
+        let mut tags = Id3v2Tag::read_from_path(tmp_path).unwrap();
+        tags.set_config(Config::default().id3v2_version(version));
+        tags.set_year(2020);
+        assert_eq!(tags.year(), Some(2020));
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+        let mut tags = Id3v2Tag::read_from_path(tmp_path).unwrap();
+        assert_eq!(tags.year(), Some(2020), "{:?}", version);
+        assert_eq!(
+            tags.date(),
+            Timestamp::from_str("2020-01-02").ok(),
+            "{:?}",
+            version
+        );
+        // back to 2000 for the next version
+        tags.set_year(2000);
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+    }
+}
+
+#[test]
+fn test_detect() {
+    for (file, tag_type) in [
+        ("assets/a.mp3", TagType::Id3v2),
+        ("assets/a.m4a", TagType::Mp4),
+        ("assets/a.flac", TagType::Flac),
+        ("assets/a.ogg", TagType::Vorbis),
+        ("assets/a.opus", TagType::Opus),
+        ("assets/a.ape", TagType::Ape),
+        ("assets/a.wav", TagType::Wav),
+        ("assets/a.aiff", TagType::Aiff),
+    ] {
+        let mut f = fs::File::open(file).unwrap();
+        assert_eq!(TagType::detect(&mut f).unwrap(), tag_type, "{}", file);
+    }
+
+    // an m4a file with the wrong extension
+    let tmp = Builder::new().suffix(".mp3").tempfile().unwrap();
+    fs::copy("assets/a.m4a", &tmp).unwrap();
+    assert!(Tag::new().read_from_path(tmp.path()).is_err());
+    let mut tags = Tag::new()
+        .with_content_detection(true)
+        .read_from_path(tmp.path())
+        .unwrap();
+    tags.set_title("foo title");
+    tags.write_to_path(tmp.path().to_str().unwrap()).unwrap();
+    let tags = Tag::new()
+        .with_tag_type(TagType::Mp4)
+        .read_from_path(tmp.path())
+        .unwrap();
+    assert_eq!(tags.title(), Some("foo title"));
+
+    // a file without extension
+    let tmp = Builder::new().tempfile().unwrap();
+    fs::copy("assets/a.flac", &tmp).unwrap();
+    assert!(Tag::new().read_from_path(tmp.path()).is_err());
+    assert!(Tag::new()
+        .with_content_detection(true)
+        .read_from_path(tmp.path())
+        .is_ok());
+
+    // an MPEG frame sync, and a raw AAC (ADTS) stream, whose layer bits are zero
+    let mp3 = b"\xff\xfb\x90\x64\0\0\0\0".to_vec();
+    assert_eq!(
+        TagType::detect(&mut Cursor::new(mp3)).unwrap(),
+        TagType::Id3v2
+    );
+    for adts in [b"\xff\xf1\x50\x80\0\0\0\0", b"\xff\xf9\x50\x80\0\0\0\0"] {
+        assert!(TagType::detect(&mut Cursor::new(adts.to_vec())).is_err());
+    }
+
+    // a FLAC file preceded by an ID3v2 tag, which is kept when writing
+    let mut id3 = id3::Tag::new();
+    id3.set_title("id3 title");
+    let mut bytes = Vec::new();
+    id3.write_to(&mut bytes, Id3v2Version::Id3v24).unwrap();
+    let id3_len = bytes.len();
+    bytes.extend(fs::read("assets/a.flac").unwrap());
+    let mut cursor = Cursor::new(bytes);
+    assert_eq!(TagType::detect(&mut cursor).unwrap(), TagType::Flac);
+    let mut tags = Tag::new().read_from(&mut cursor).unwrap();
+    tags.set_title("foo title");
+    tags.write_to(&mut cursor).unwrap();
+    assert_eq!(&cursor.get_ref()[id3_len..id3_len + 4], b"fLaC");
+    let id3 = id3::Tag::read_from2(Cursor::new(cursor.get_ref())).unwrap();
+    assert_eq!(id3.title(), Some("id3 title"));
+    let tags = FlacTag::read_from(&mut Cursor::new(cursor.get_ref())).unwrap();
+    assert_eq!(tags.title(), Some("foo title"));
+}
+
+#[test]
+fn test_read_from() {
+    for file in [
+        "assets/a.mp3",
+        "assets/a.m4a",
+        "assets/a.flac",
+        "assets/a.ogg",
+        "assets/a.opus",
+        "assets/a.ape",
+        "assets/a.wav",
+        "assets/a.aiff",
+    ] {
+        let bytes = fs::read(file).unwrap();
+        let from_path = Tag::new().read_from_path(file).unwrap();
+        let from_reader = Tag::new().read_from(Cursor::new(&bytes)).unwrap();
+        assert_eq!(from_reader.title(), from_path.title(), "{}", file);
+        assert_eq!(from_reader.duration(), from_path.duration(), "{}", file);
+    }
+
+    let bytes = fs::read("assets/a.mp3").unwrap();
+    assert!(Id3v2Tag::read_from(&mut Cursor::new(&bytes)).is_ok());
+    let bytes = fs::read("assets/a.flac").unwrap();
+    assert!(FlacTag::read_from(&mut Cursor::new(&bytes)).is_ok());
+    let bytes = fs::read("assets/a.m4a").unwrap();
+    assert!(Mp4Tag::read_from(&mut Cursor::new(&bytes)).is_ok());
+    assert!(Tag::new()
+        .with_tag_type(TagType::Flac)
+        .read_from(Cursor::new(&bytes))
+        .is_err());
+}
+
+#[test]
+fn test_write_to_bytes() {
+    for file in [
+        "assets/a.mp3",
+        "assets/a.m4a",
+        "assets/a.flac",
+        "assets/a.ogg",
+        "assets/a.opus",
+        "assets/a.ape",
+        "assets/a.wav",
+        "assets/a.aiff",
+    ] {
+        let bytes = fs::read(file).unwrap();
+        let mut tags = Tag::new().read_from(Cursor::new(&bytes)).unwrap();
+        tags.set_title("foo title");
+        let written = tags.write_to_vec(&bytes).unwrap();
+        assert_eq!(fs::read(file).unwrap(), bytes);
+        let tags = Tag::new().read_from(Cursor::new(&written)).unwrap();
+        assert_eq!(tags.title(), Some("foo title"), "{}", file);
+
+        let mut tags = Tag::new().read_from(Cursor::new(&written)).unwrap();
+        tags.set_title("bar title");
+        let mut cursor = Cursor::new(written);
+        tags.write_to(&mut cursor).unwrap();
+        let tags = Tag::new().read_from(Cursor::new(cursor.get_ref())).unwrap();
+        assert_eq!(tags.title(), Some("bar title"), "{}", file);
+        assert_eq!(
+            tags.duration(),
+            Tag::new().read_from_path(file).unwrap().duration(),
+            "{}",
+            file
+        );
+    }
+
+    let tmp = Builder::new().suffix(".flac").tempfile().unwrap();
+    fs::copy("assets/a.flac", &tmp).unwrap();
+    let mut tags = Tag::new().read_from_path(tmp.path()).unwrap();
+    tags.set_title("foo title");
+    tags.write_to_file_path(tmp.path()).unwrap();
+    let tags = Tag::new().read_from_path(tmp.path()).unwrap();
+    assert_eq!(tags.title(), Some("foo title"));
+}
+
+#[test]
+fn test_flac_padding() {
+    let tmp = Builder::new().suffix(".flac").tempfile().unwrap();
+    fs::copy("assets/a.flac", &tmp).unwrap();
+    let tmp_path = tmp.path().to_str().unwrap();
+    let original = fs::read("assets/a.flac").unwrap();
+    let audio = &original[8787..];
+
+    // the metadata fits in the padding
+    let mut tags = FlacTag::read_from_path(tmp_path).unwrap();
+    tags.set_title("foo title");
+    tags.write_to_path(tmp_path).unwrap();
+    let written = fs::read(tmp_path).unwrap();
+    assert_eq!(written.len(), original.len());
+    assert!(written.ends_with(audio));
+
+    // it doesn't anymore, the file is rewritten with new padding
+    tags.set_custom_field("LONG", &[&"x".repeat(10_000)]);
+    tags.write_to_path(tmp_path).unwrap();
+    let written = fs::read(tmp_path).unwrap();
+    assert!(written.len() > original.len());
+    assert!(written.ends_with(audio));
+    let mut tags = FlacTag::read_from_path(tmp_path).unwrap();
+    assert_eq!(tags.title(), Some("foo title"));
+
+    // which is then used
+    tags.remove_custom_field("LONG");
+    let mut cursor = Cursor::new(written);
+    tags.write_to(&mut cursor).unwrap();
+    let rewritten = cursor.into_inner();
+    assert_eq!(rewritten.len(), fs::read(tmp_path).unwrap().len());
+    assert!(rewritten.ends_with(audio));
+    let tags = FlacTag::read_from(&mut Cursor::new(rewritten)).unwrap();
+    assert_eq!(tags.title(), Some("foo title"));
+    assert!(tags.custom_field("LONG").is_empty());
+}
+
+#[test]
+fn test_wav_write() {
+    let tmp = Builder::new().suffix(".wav").tempfile().unwrap();
+    fs::copy("assets/a.wav", &tmp).unwrap();
+    let tmp_path = tmp.path();
+
+    let mut tags = WavTag::read_from_path(tmp_path).unwrap();
+    assert!(tags.duration().is_some());
+    tags.set_title("foo title");
+    tags.set_track_number(3);
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+    tags.set_artist("foo artist");
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+    let inner = WavInnerTag::read_from_path(tmp_path).unwrap();
+    let info = inner.info().collect::<Vec<_>>();
+    assert!(info.contains(&("INAM", "foo title")));
+    assert!(info.contains(&("IART", "foo artist")));
+    assert!(info.contains(&("ITRK", "3")));
+    assert_eq!(inner.id3().title(), Some("foo title"));
+
+    // a file with only a LIST/INFO chunk
+    let mut info = b"INFOINAM\x0b\0\0\0info title\0\0IART\x05\0\0\0Bob\0\0".to_vec();
+    let mut bytes = fs::read("assets/a.wav").unwrap();
+    bytes.extend(b"LIST");
+    bytes.extend((info.len() as u32).to_le_bytes());
+    bytes.append(&mut info);
+    let riff_size = bytes.len() as u32 - 8;
+    bytes[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    fs::write(tmp_path, bytes).unwrap();
+
+    let mut tags = WavTag::read_from_path(tmp_path).unwrap();
+    assert_eq!(tags.title(), Some("info title"));
+    assert_eq!(tags.artist(), Some("Bob"));
+    tags.set_title("foo title");
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+    let inner = WavInnerTag::read_from_path(tmp_path).unwrap();
+    assert_eq!(inner.id3().title(), Some("foo title"));
+    assert!(inner.info().any(|f| f == ("INAM", "foo title")));
+    assert!(inner.info().any(|f| f == ("IART", "Bob")));
+}
+
+#[test]
+fn test_riff_rewrite() {
+    // a LIST/INFO chunk before the audio data
+    let mut info = b"INFOINAM\x0b\0\0\0info title\0\0".to_vec();
+    let original = fs::read("assets/a.wav").unwrap();
+    let (head, data) = original.split_at(760);
+    let mut bytes = head.to_vec();
+    bytes.extend(b"LIST");
+    bytes.extend((info.len() as u32).to_le_bytes());
+    bytes.append(&mut info);
+    bytes.extend(data);
+    let riff_size = bytes.len() as u32 - 8;
+    bytes[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+    let mut file = Cursor::new(bytes);
+    let mut tags = WavTag::read_from(&mut file).unwrap();
+    assert_eq!(tags.title(), Some("info title"));
+    tags.set_title("foo title");
+    tags.write_to(&mut file).unwrap();
+    let bytes = file.get_ref().clone();
+    // the old chunk is padding, the audio data is untouched
+    assert_eq!(&bytes[760..764], b"JUNK");
+    assert!(bytes[792..].starts_with(data));
+    let riff_size = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+    assert_eq!(riff_size as usize, bytes.len() - 8);
+
+    // the new chunks are at the end of the file and are replaced by the next write
+    tags.set_title("bar title");
+    tags.write_to(&mut file).unwrap();
+    assert_eq!(file.get_ref().len(), bytes.len());
+    let inner = WavInnerTag::read_from(&mut file).unwrap();
+    assert_eq!(inner.id3().title(), Some("bar title"));
+    assert!(inner.info().any(|f| f == ("INAM", "bar title")));
+}
+
+#[test]
+fn test_aiff_write() {
+    let tmp = Builder::new().suffix(".aiff").tempfile().unwrap();
+    fs::copy("assets/a.aiff", &tmp).unwrap();
+    let tmp_path = tmp.path();
+
+    // the title only lives in the NAME chunk
+    let mut tags = AiffTag::read_from_path(tmp_path).unwrap();
+    assert_eq!(tags.title(), Some("aiff title"));
+    assert_eq!(tags.duration(), Some(0.5));
+    tags.set_artist("foo artist");
+    tags.set_comment("foo comment".to_owned());
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+    let inner = AiffInnerTag::read_from_path(tmp_path).unwrap();
+    assert_eq!(inner.id3().title(), Some("aiff title"));
+    assert_eq!(inner.id3().artist(), Some("foo artist"));
+    let chunks = inner.text_chunks().collect::<Vec<_>>();
+    assert!(chunks.contains(&("NAME", "aiff title")));
+    assert!(chunks.contains(&("AUTH", "foo artist")));
+    assert!(chunks.contains(&("ANNO", "foo comment")));
+    assert_eq!(AiffTag::from(inner).duration(), Some(0.5));
+
+    // a file with two ANNO chunks: the first one is the comment, the second one is kept
+    let mut bytes = fs::read("assets/a.aiff").unwrap();
+    for text in [&b"first note"[..], b"second note"] {
+        bytes.extend(b"ANNO");
+        bytes.extend((text.len() as u32).to_be_bytes());
+        bytes.extend(text);
+        if text.len() % 2 == 1 {
+            bytes.push(0);
+        }
+    }
+    let form_size = bytes.len() as u32 - 8;
+    bytes[4..8].copy_from_slice(&form_size.to_be_bytes());
+    fs::write(tmp_path, bytes).unwrap();
+
+    let mut tags = AiffTag::read_from_path(tmp_path).unwrap();
+    assert_eq!(tags.comment(), Some("first note"));
+    tags.set_comment("foo comment".to_owned());
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+    let inner = AiffInnerTag::read_from_path(tmp_path).unwrap();
+    let annotations = inner
+        .text_chunks()
+        .filter(|(id, _)| *id == "ANNO")
+        .map(|(_, text)| text)
+        .collect::<Vec<_>>();
+    assert_eq!(annotations, ["foo comment", "second note"]);
+
+    let mut tags = AiffTag::from(inner);
+    tags.remove_comment();
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+    let inner = AiffInnerTag::read_from_path(tmp_path).unwrap();
+    assert!(inner.text_chunks().any(|c| c == ("ANNO", "second note")));
+}
+
+#[test]
+fn test_riff_id3v2_version() {
+    for file in ["assets/a.wav", "assets/a.aiff"] {
+        let suffix = Path::new(file).extension().unwrap().to_str().unwrap();
+        let tmp = Builder::new()
+            .suffix(&format!(".{suffix}"))
+            .tempfile()
+            .unwrap();
+        fs::copy(file, &tmp).unwrap();
+        let tmp_path = tmp.path().to_str().unwrap();
+        let id3_version = |path| match suffix {
+            "wav" => WavInnerTag::read_from_path(path).unwrap().id3().version(),
+            _ => AiffInnerTag::read_from_path(path).unwrap().id3().version(),
+        };
+
+        let mut tags = Tag::new()
+            .with_config(Config::default().id3v2_version(Id3v2Version::Id3v23))
+            .read_from_path(tmp_path)
+            .unwrap();
+        tags.set_date(Timestamp::from_str("2020-05-22").unwrap());
+        tags.write_to_path(tmp_path).unwrap();
+        assert_eq!(id3_version(tmp_path), Id3v2Version::Id3v23, "{file}");
+
+        // the version the file was read with is kept by default
+        let mut tags = Tag::new().read_from_path(tmp_path).unwrap();
+        assert_eq!(tags.date(), Timestamp::from_str("2020-05-22").ok());
+        tags.set_title("foo title");
+        tags.write_to_path(tmp_path).unwrap();
+        assert_eq!(id3_version(tmp_path), Id3v2Version::Id3v23, "{file}");
+
+        let mut tags = Tag::new()
+            .with_config(Config::default().id3v2_version(Id3v2Version::Id3v24))
+            .read_from_path(tmp_path)
+            .unwrap();
+        tags.write_to_path(tmp_path).unwrap();
+        assert_eq!(id3_version(tmp_path), Id3v2Version::Id3v24, "{file}");
+    }
+}
+
+#[test]
+fn test_pictures() {
+    for file in [
+        "assets/a.mp3",
+        "assets/a.flac",
+        "assets/a.ogg",
+        "assets/a.opus",
+        "assets/a.ape",
+        "assets/a.wav",
+        "assets/a.aiff",
+    ] {
+        let path = Path::new(file);
+        let mut suffix = OsString::from(".");
+        suffix.push(path.extension().unwrap());
+        let tmp = Builder::new().suffix(&suffix).tempfile().unwrap();
+        fs::copy(file, &tmp).unwrap();
+        let tmp_path = tmp.path();
+
+        let front = EmbeddedPicture::new(PictureType::CoverFront, MimeType::Jpeg, vec![1u8; 10]);
+        let back = EmbeddedPicture::new(PictureType::CoverBack, MimeType::Png, &[2u8; 10][..])
+            .with_description("foo back")
+            .with_dimensions(600, 400, 24);
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        tags.add_picture(front.clone());
+        tags.add_picture(back.clone());
+        tags.add_picture(back.clone().with_description("replaced"));
+        tags.add_picture(back.clone());
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        let pictures = tags.pictures();
+        assert_eq!(pictures.len(), 2, "{}", file);
+        assert_eq!(pictures[0], front, "{}", file);
+        assert_eq!(pictures[1].description, "foo back", "{}", file);
+        assert_eq!(pictures[1].data, back.data, "{}", file);
+        if [".flac", ".ogg", ".opus"].contains(&suffix.to_str().unwrap()) {
+            assert_eq!(pictures[1], back, "{}", file);
+        } else {
+            assert_eq!(pictures[1].width, 0, "{}", file);
+        }
+        assert_eq!(tags.album_cover(), Some(front.as_picture()), "{}", file);
+
+        tags.remove_pictures_by_type(PictureType::CoverFront);
+        assert!(tags.album_cover().is_none(), "{}", file);
+        assert_eq!(tags.pictures().len(), 1, "{}", file);
+    }
+}
+
+#[test]
+fn test_mp4_pictures() {
+    let tmp = Builder::new().suffix(".m4a").tempfile().unwrap();
+    fs::copy("assets/a.m4a", &tmp).unwrap();
+    let tmp_path = tmp.path();
+
+    let mut tags = Mp4Tag::read_from_path(tmp_path).unwrap();
+    tags.add_picture(EmbeddedPicture::new(
+        PictureType::CoverFront,
+        MimeType::Jpeg,
+        vec![1u8; 10],
+    ));
+    tags.add_picture(EmbeddedPicture::new(
+        PictureType::CoverBack,
+        MimeType::Png,
+        vec![2u8; 10],
+    ));
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+    let mut tags = Mp4Tag::read_from_path(tmp_path).unwrap();
+    let pictures = tags.pictures();
+    assert_eq!(pictures.len(), 2);
+    assert!(pictures
+        .iter()
+        .all(|p| p.picture_type == PictureType::CoverFront));
+    assert_eq!(pictures[1].data.as_ref(), &[2u8; 10]);
+    tags.remove_pictures_by_type(PictureType::CoverFront);
+    assert!(tags.pictures().is_empty());
+}
+
+#[test]
+fn test_mp4_cover_formats() {
+    let tmp = Builder::new().suffix(".m4a").tempfile().unwrap();
+    fs::copy("assets/a.m4a", &tmp).unwrap();
+    let tmp_path = tmp.path();
+
+    let bmp = Picture {
+        mime_type: MimeType::Bmp,
+        data: b"BM\0\0",
+    };
+    let gif = Picture {
+        mime_type: MimeType::Gif,
+        data: b"GIF89a",
+    };
+    let mut tags = Mp4Tag::read_from_path(tmp_path).unwrap();
+    tags.try_set_album_cover(bmp.clone()).unwrap();
+    assert!(matches!(
+        tags.try_set_album_cover(gif.clone()),
+        Err(audiotags::Error::UnsupportedPictureFormat(MimeType::Gif))
+    ));
+    tags.set_album_cover(gif.clone()); // should not panic
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+    let mut tags = Mp4Tag::read_from_path(tmp_path).unwrap();
+    assert_eq!(tags.album_cover(), Some(bmp));
+
+    fn to_png(_: Picture) -> audiotags::Result<Vec<u8>> {
+        Ok(b"\x89PNG".to_vec())
+    }
+    tags.set_config(Config::default().cover_transcoder(to_png));
+    tags.try_set_album_cover(gif.clone()).unwrap();
+    assert_eq!(
+        tags.album_cover(),
+        Some(Picture {
+            mime_type: MimeType::Png,
+            data: b"\x89PNG",
+        })
+    );
+
+    let mut flac = FlacTag::new();
+    flac.set_config(Config::default().cover_transcoder(to_png));
+    flac.set_album_cover(gif);
+    let mp4 = flac.to_dyn_tag(TagType::Mp4);
+    assert_eq!(mp4.album_cover().unwrap().mime_type, MimeType::Png);
+    let mut id3v1 = Id3v1Tag::new();
+    assert!(id3v1
+        .try_set_album_cover(mp4.album_cover().unwrap())
+        .is_err());
+}
+
+#[test]
+fn test_lyrics() {
+    for file in [
+        "assets/a.mp3",
+        "assets/a.m4a",
+        "assets/a.flac",
+        "assets/a.ogg",
+        "assets/a.opus",
+        "assets/a.ape",
+        "assets/a.wav",
+        "assets/a.aiff",
+    ] {
+        let path = Path::new(file);
+        let mut suffix = OsString::from(".");
+        suffix.push(path.extension().unwrap());
+        let tmp = Builder::new().suffix(&suffix).tempfile().unwrap();
+        fs::copy(file, &tmp).unwrap();
+        let tmp_path = tmp.path();
+        let is_id3 = [".mp3", ".wav", ".aiff"].contains(&suffix.to_str().unwrap());
+
+        let lyrics = Lyrics::new("foo\nbar").with_lang("eng");
+        let synced = SyncedLyrics {
+            lines: vec![(1000, "foo".to_owned()), (2500, "bar".to_owned())],
+            ..SyncedLyrics::default()
+        };
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        tags.set_lyrics(lyrics.clone());
+        tags.set_synced_lyrics(&synced);
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        assert_eq!(tags.synced_lyrics(), Some(synced.clone()), "{}", file);
+        if is_id3 {
+            assert_eq!(tags.lyrics(), Some(lyrics.clone()), "{}", file);
+        } else if [".m4a", ".ape"].contains(&suffix.to_str().unwrap()) {
+            // a single lyrics field, which now holds the LRC text
+            assert_eq!(tags.lyrics().unwrap().text, synced.to_lrc(), "{}", file);
+        } else {
+            assert_eq!(tags.lyrics().unwrap().text, "foo\nbar", "{}", file);
+        }
+
+        tags.remove_synced_lyrics();
+        assert!(tags.synced_lyrics().is_none(), "{}", file);
+        tags.remove_lyrics();
+        assert!(tags.lyrics().is_none(), "{}", file);
+    }
+}
+
+#[test]
+fn test_replay_gain() {
+    for file in [
+        "assets/a.mp3",
+        "assets/a.m4a",
+        "assets/a.flac",
+        "assets/a.ogg",
+        "assets/a.opus",
+        "assets/a.ape",
+        "assets/a.wav",
+        "assets/a.aiff",
+    ] {
+        let path = Path::new(file);
+        let mut suffix = OsString::from(".");
+        suffix.push(path.extension().unwrap());
+        let tmp = Builder::new().suffix(&suffix).tempfile().unwrap();
+        fs::copy(file, &tmp).unwrap();
+        let tmp_path = tmp.path();
+
+        let replay_gain = ReplayGain {
+            track_gain: Some(-6.5),
+            track_peak: Some(0.988831),
+            album_gain: Some(-7.25),
+            album_peak: None,
+        };
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        tags.set_replay_gain(replay_gain);
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        if suffix == ".opus" {
+            // stored as R128 gains, without peaks
+            let expected = ReplayGain {
+                track_peak: None,
+                ..replay_gain
+            };
+            assert_eq!(tags.replay_gain(), Some(expected), "{}", file);
+        } else {
+            assert_eq!(tags.replay_gain(), Some(replay_gain), "{}", file);
+        }
+
+        tags.remove_replay_gain();
+        assert!(tags.replay_gain().is_none(), "{}", file);
+    }
+}
+
+#[test]
+fn test_musicbrainz_ids() {
+    for file in [
+        "assets/a.mp3",
+        "assets/a.m4a",
+        "assets/a.flac",
+        "assets/a.ogg",
+        "assets/a.opus",
+        "assets/a.ape",
+        "assets/a.wav",
+        "assets/a.aiff",
+    ] {
+        let path = Path::new(file);
+        let mut suffix = OsString::from(".");
+        suffix.push(path.extension().unwrap());
+        let tmp = Builder::new().suffix(&suffix).tempfile().unwrap();
+        fs::copy(file, &tmp).unwrap();
+        let tmp_path = tmp.path();
+
+        let artists = [
+            "5b11f4ce-a62d-471e-81fc-a69a8278c7da",
+            "ba0d6274-db14-4ef5-b28d-657ebde1a396",
+        ];
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        for id in MusicBrainzId::ALL {
+            tags.set_musicbrainz_id(id, "d6118046-407d-4e06-a1ba-49c399a4c42f");
+        }
+        tags.set_musicbrainz_ids(MusicBrainzId::Artist, &artists);
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        assert_eq!(
+            tags.musicbrainz_id(MusicBrainzId::Recording),
+            Some("d6118046-407d-4e06-a1ba-49c399a4c42f"),
+            "{}",
+            file
+        );
+        assert_eq!(
+            tags.musicbrainz_ids(MusicBrainzId::Artist),
+            artists,
+            "{}",
+            file
+        );
+        assert_eq!(tags.all_musicbrainz_ids().len(), 9, "{}", file);
+
+        for id in MusicBrainzId::ALL {
+            tags.remove_musicbrainz_id(id);
+        }
+        assert!(tags.all_musicbrainz_ids().is_empty(), "{}", file);
+    }
+}
+
+#[test]
+fn test_sort_order() {
+    for file in [
+        "assets/a.mp3",
+        "assets/a.m4a",
+        "assets/a.flac",
+        "assets/a.ogg",
+        "assets/a.opus",
+        "assets/a.ape",
+        "assets/a.wav",
+        "assets/a.aiff",
+    ] {
+        let path = Path::new(file);
+        let mut suffix = OsString::from(".");
+        suffix.push(path.extension().unwrap());
+        let tmp = Builder::new().suffix(&suffix).tempfile().unwrap();
+        fs::copy(file, &tmp).unwrap();
+        let tmp_path = tmp.path();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        tags.set_title_sort("Yesterday");
+        tags.set_artist_sort("Beatles, The");
+        tags.set_album_sort("Help!");
+        tags.set_album_artist_sort("Beatles, The");
+        tags.set_composer_sort("Lennon, John");
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        assert_eq!(tags.title_sort(), Some("Yesterday"), "{}", file);
+        assert_eq!(tags.artist_sort(), Some("Beatles, The"), "{}", file);
+        assert_eq!(tags.album_sort(), Some("Help!"), "{}", file);
+        assert_eq!(tags.album_artist_sort(), Some("Beatles, The"), "{}", file);
+        assert_eq!(tags.composer_sort(), Some("Lennon, John"), "{}", file);
+
+        for field in SortField::ALL {
+            tags.remove_sort_order(field);
+            assert!(tags.sort_order(field).is_none(), "{}", file);
+        }
+    }
+}
+
+#[test]
+fn test_extended_fields() {
+    for file in [
+        "assets/a.mp3",
+        "assets/a.m4a",
+        "assets/a.flac",
+        "assets/a.ogg",
+        "assets/a.opus",
+        "assets/a.ape",
+        "assets/a.wav",
+        "assets/a.aiff",
+    ] {
+        let path = Path::new(file);
+        let mut suffix = OsString::from(".");
+        suffix.push(path.extension().unwrap());
+        let tmp = Builder::new().suffix(&suffix).tempfile().unwrap();
+        fs::copy(file, &tmp).unwrap();
+        let tmp_path = tmp.path();
+        let original_date = Timestamp::from_str("1965-08-06").unwrap();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        for field in TextField::ALL {
+            tags.set_text_field(field, &format!("{:?} value", field));
+        }
+        tags.set_isrc("GBAYE6500524");
+        tags.set_bpm(97);
+        tags.set_compilation(true);
+        tags.set_original_date(original_date);
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        assert_eq!(tags.isrc(), Some("GBAYE6500524"), "{}", file);
+        assert_eq!(tags.label(), Some("Label value"), "{}", file);
+        assert_eq!(
+            tags.catalog_number(),
+            Some("CatalogNumber value"),
+            "{}",
+            file
+        );
+        assert_eq!(tags.copyright(), Some("Copyright value"), "{}", file);
+        assert_eq!(tags.encoded_by(), Some("EncodedBy value"), "{}", file);
+        assert_eq!(tags.grouping(), Some("Grouping value"), "{}", file);
+        assert_eq!(tags.mood(), Some("Mood value"), "{}", file);
+        assert_eq!(tags.language(), Some("Language value"), "{}", file);
+        assert_eq!(tags.conductor(), Some("Conductor value"), "{}", file);
+        assert_eq!(tags.lyricist(), Some("Lyricist value"), "{}", file);
+        assert_eq!(tags.bpm(), Some(97), "{}", file);
+        assert!(tags.compilation(), "{}", file);
+        assert_eq!(tags.original_date(), Some(original_date), "{}", file);
+
+        for field in TextField::ALL {
+            tags.remove_text_field(field);
+            assert!(tags.text_field(field).is_none(), "{}", file);
+        }
+        tags.remove_bpm();
+        tags.set_compilation(false);
+        tags.remove_original_date();
+        assert!(tags.bpm().is_none(), "{}", file);
+        assert!(!tags.compilation(), "{}", file);
+        assert!(tags.original_date().is_none(), "{}", file);
+    }
+}
+
+#[test]
+fn test_rating_and_play_count() {
+    for file in [
+        "assets/a.mp3",
+        "assets/a.m4a",
+        "assets/a.flac",
+        "assets/a.ogg",
+        "assets/a.opus",
+        "assets/a.ape",
+        "assets/a.wav",
+        "assets/a.aiff",
+    ] {
+        let path = Path::new(file);
+        let mut suffix = OsString::from(".");
+        suffix.push(path.extension().unwrap());
+        let tmp = Builder::new().suffix(&suffix).tempfile().unwrap();
+        fs::copy(file, &tmp).unwrap();
+        let tmp_path = tmp.path();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        tags.set_rating(0.5);
+        tags.set_play_count(300);
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        assert_eq!(tags.rating(), Some(0.5), "{}", file);
+        assert_eq!(tags.play_count(), Some(300), "{}", file);
+
+        tags.set_rating(1.5);
+        assert_eq!(tags.rating(), Some(1.0), "{}", file);
+        tags.remove_rating();
+        tags.remove_play_count();
+        assert!(tags.rating().is_none(), "{}", file);
+        assert!(tags.play_count().is_none(), "{}", file);
+    }
+}
+
+#[test]
+fn test_tag_keys() {
+    for file in [
+        "assets/a.mp3",
+        "assets/a.m4a",
+        "assets/a.flac",
+        "assets/a.ogg",
+        "assets/a.opus",
+        "assets/a.ape",
+        "assets/a.wav",
+        "assets/a.aiff",
+    ] {
+        let path = Path::new(file);
+        let mut suffix = OsString::from(".");
+        suffix.push(path.extension().unwrap());
+        let tmp = Builder::new().suffix(&suffix).tempfile().unwrap();
+        fs::copy(file, &tmp).unwrap();
+        let tmp_path = tmp.path();
+        let value = |key: &TagKey| match key {
+            TagKey::Date => "2020-01-02".to_owned(),
+            key => format!("{:?}", key),
+        };
+        let custom = TagKey::Custom("ASSET_ID".to_owned());
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        for key in TagKey::standard().chain([custom.clone()]) {
+            tags.set(&key, &[&value(&key)]);
+        }
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        for key in TagKey::standard().chain([custom.clone()]) {
+            assert_eq!(tags.get(&key), [value(&key)], "{}: {:?}", file, key);
+        }
+        assert_eq!(tags.title(), Some("Title"), "{}", file);
+        assert_eq!(tags.isrc(), Some("Text(Isrc)"), "{}", file);
+        assert_eq!(tags.artist_sort(), Some("Sort(Artist)"), "{}", file);
+
+        for key in TagKey::standard().chain([custom]) {
+            tags.remove_key(&key);
+            assert!(tags.get(&key).is_empty(), "{}: {:?}", file, key);
+        }
+    }
+}
+
+#[test]
+fn test_items() {
+    for file in [
+        "assets/a.mp3",
+        "assets/a.m4a",
+        "assets/a.flac",
+        "assets/a.ogg",
+        "assets/a.opus",
+        "assets/a.ape",
+        "assets/a.wav",
+        "assets/a.aiff",
+    ] {
+        let path = Path::new(file);
+        let mut suffix = OsString::from(".");
+        suffix.push(path.extension().unwrap());
+        let tmp = Builder::new().suffix(&suffix).tempfile().unwrap();
+        fs::copy(file, &tmp).unwrap();
+        let tmp_path = tmp.path();
+        let custom = TagKey::Custom("ASSET_ID".to_owned());
+        let cover = Picture {
+            mime_type: MimeType::Png,
+            data: b"\x89PNG\r\n\x1a\nfoo",
+        };
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        tags.set_title("foo title");
+        tags.set(&custom, &["1234"]);
+        tags.set_album_cover(cover.clone());
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+        let tags = Tag::default().read_from_path(tmp_path).unwrap();
+        let items = tags.items();
+        assert!(
+            items.contains(&(TagKey::Title, Value::Text("foo title".into()))),
+            "{}: {:?}",
+            file,
+            items
+        );
+        assert!(
+            items.contains(&(custom.clone(), Value::Text("1234".into()))),
+            "{}",
+            file
+        );
+        assert!(
+            items
+                .iter()
+                .any(|(_, v)| matches!(v, Value::Picture(p) if p.data == cover.data)),
+            "{}",
+            file
+        );
+        // the items of a standard or custom key are its values
+        for (key, value) in &items {
+            if let (false, Value::Text(text)) = (matches!(key, TagKey::Native(_)), value) {
+                assert!(
+                    tags.get(key).contains(&text.as_ref()),
+                    "{}: {:?}",
+                    file,
+                    key
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_custom_fields() {
+    for file in [
+        "assets/a.mp3",
+        "assets/a.m4a",
+        "assets/a.flac",
+        "assets/a.ogg",
+        "assets/a.opus",
+        "assets/a.ape",
+        "assets/a.wav",
+        "assets/a.aiff",
+    ] {
+        let path = Path::new(file);
+        let mut suffix = OsString::from(".");
+        suffix.push(path.extension().unwrap());
+        let tmp = Builder::new().suffix(&suffix).tempfile().unwrap();
+        fs::copy(file, &tmp).unwrap();
+        let tmp_path = tmp.path();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        tags.set_custom_field("ASSET_ID", &["1234", "5678"]);
+        tags.set_custom_field("SOURCE", &["archive"]);
+        tags.set_replay_gain(ReplayGain {
+            track_gain: Some(-6.5),
+            ..ReplayGain::default()
+        });
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        assert_eq!(
+            tags.custom_field("asset_id"),
+            vec!["1234", "5678"],
+            "{}",
+            file
+        );
+        let fields = tags.custom_fields();
+        for field in [
+            ("ASSET_ID", "1234"),
+            ("ASSET_ID", "5678"),
+            ("SOURCE", "archive"),
+        ] {
+            assert!(fields.contains(&field), "{}: {:?}", file, fields);
+        }
+        // fields read by other accessors are not custom
+        assert!(
+            !fields
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("REPLAYGAIN_TRACK_GAIN")),
+            "{}",
+            file
+        );
+        tags.remove_custom_field("ASSET_ID");
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+        let tags = Tag::default().read_from_path(tmp_path).unwrap();
+        assert!(tags.custom_field("ASSET_ID").is_empty(), "{}", file);
+        assert_eq!(tags.custom_field("SOURCE"), vec!["archive"], "{}", file);
+    }
+
+    // the mean of MP4 custom fields
+    let tmp = Builder::new().suffix(".m4a").tempfile().unwrap();
+    fs::copy("assets/a.m4a", &tmp).unwrap();
+    let tmp_path = tmp.path();
+    let config = Config::default().mp4_freeform_mean("com.example");
+    let mut tags = Tag::new()
+        .with_config(config)
+        .read_from_path(tmp_path)
+        .unwrap();
+    tags.set_custom_field("ASSET_ID", &["1234"]);
+    tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+    let tags = Tag::default().read_from_path(tmp_path).unwrap();
+    assert!(tags.custom_field("ASSET_ID").is_empty());
+    assert_eq!(
+        tags.get(&TagKey::Native("----:com.example:ASSET_ID".to_owned())),
+        vec!["1234"]
+    );
+    let tags = Tag::new()
+        .with_config(config)
+        .read_from_path(tmp_path)
+        .unwrap();
+    assert_eq!(tags.custom_fields(), vec![("ASSET_ID", "1234")]);
+}
+
+#[test]
+fn test_multiple_values() {
+    for file in [
+        "assets/a.mp3",
+        "assets/a.m4a",
+        "assets/a.flac",
+        "assets/a.ogg",
+        "assets/a.opus",
+        "assets/a.ape",
+        "assets/a.wav",
+        "assets/a.aiff",
+    ] {
+        let path = Path::new(file);
+        let mut suffix = OsString::from(".");
+        suffix.push(path.extension().unwrap());
+        let tmp = Builder::new().suffix(&suffix).tempfile().unwrap();
+        fs::copy(file, &tmp).unwrap();
+        let tmp_path = tmp.path();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        tags.set_artists(&["Simon", "Garfunkel"]);
+        tags.add_artist("AC;DC");
+        tags.set_album_artists(&["Simon", "Garfunkel"]);
+        tags.set_genres(&["Folk", "Rock"]);
+        tags.set_composers(&["Paul Simon"]);
+        tags.add_composer("Art Garfunkel");
+        tags.write_to_path(tmp_path.to_str().unwrap()).unwrap();
+
+        let mut tags = Tag::default().read_from_path(tmp_path).unwrap();
+        // values are stored separately, so the separator is not parsed
+        assert_eq!(
+            tags.artists(),
+            Some(vec!["Simon", "Garfunkel", "AC;DC"]),
+            "{}",
+            file
+        );
+        assert_eq!(tags.artist(), Some("Simon"), "{}", file);
+        assert_eq!(
+            tags.album_artists(),
+            Some(vec!["Simon", "Garfunkel"]),
+            "{}",
+            file
+        );
+        assert_eq!(tags.genres(), Some(vec!["Folk", "Rock"]), "{}", file);
+        assert_eq!(tags.genre(), Some("Folk"), "{}", file);
+        assert_eq!(
+            tags.composers(),
+            Some(vec!["Paul Simon", "Art Garfunkel"]),
+            "{}",
+            file
+        );
+        assert_eq!(tags.composer(), Some("Paul Simon"), "{}", file);
+
+        tags.set_artist("Simon & Garfunkel");
+        assert_eq!(tags.artists(), Some(vec!["Simon & Garfunkel"]), "{}", file);
+        tags.remove_genre();
+        assert!(tags.genres().is_none(), "{}", file);
+    }
+
+    // ID3v1 has no multiple values
+    let tmp = Builder::new().suffix(".mp3").tempfile().unwrap();
+    fs::copy("assets/a.mp3", &tmp).unwrap();
+    let mut tags = Id3v1Tag::new();
+    tags.set_artists(&["Simon", "Garfunkel"]);
+    tags.add_artist("Art");
+    tags.set_genres(&["Folk", "Rock"]);
+    tags.write_to_path(tmp.path().to_str().unwrap()).unwrap();
+    let tags = Id3v1Tag::read_from_path(tmp.path()).unwrap();
+    assert_eq!(tags.artist(), Some("Simon;Garfunkel;Art"));
+    assert_eq!(tags.artists(), Some(vec!["Simon", "Garfunkel", "Art"]));
+    assert_eq!(tags.genres(), Some(vec!["Folk"]));
+}
+
+#[test]
+fn test_riff_multiple_values() {
+    for file in ["assets/a.wav", "assets/a.aiff"] {
+        let suffix = Path::new(file).extension().unwrap().to_str().unwrap();
+        let tmp = Builder::new()
+            .suffix(&format!(".{suffix}"))
+            .tempfile()
+            .unwrap();
+        fs::copy(file, &tmp).unwrap();
+        let tmp_path = tmp.path().to_str().unwrap();
+
+        let mut tags = Tag::new()
+            .with_config(Config::default().sep_artist(" / "))
+            .read_from_path(tmp_path)
+            .unwrap();
+        tags.set_artists(&["Simon", "Garfunkel"]);
+        tags.set_genres(&["Folk", "Rock"]);
+        tags.write_to_path(tmp_path).unwrap();
+
+        if suffix == "wav" {
+            let inner = WavInnerTag::read_from_path(tmp_path).unwrap();
+            let info = inner.info().collect::<Vec<_>>();
+            assert!(info.contains(&("IART", "Simon / Garfunkel")), "{:?}", info);
+            assert!(info.contains(&("IGNR", "Folk / Rock")), "{:?}", info);
+        } else {
+            let inner = AiffInnerTag::read_from_path(tmp_path).unwrap();
+            let chunks = inner.text_chunks().collect::<Vec<_>>();
+            assert!(
+                chunks.contains(&("AUTH", "Simon / Garfunkel")),
+                "{:?}",
+                chunks
+            );
+        }
+        let tags = Tag::new().read_from_path(tmp_path).unwrap();
+        assert_eq!(tags.artists(), Some(vec!["Simon", "Garfunkel"]), "{}", file);
+    }
+}